@@ -0,0 +1,121 @@
+//! Pregenerate a region of a world offline, without starting the server.
+//!
+//! ```sh
+//! cargo run --release --example pregen -- --worldgen worldgen.json --save-dir data/worlds/pregen \
+//!     --min -32,-32 --max 31,31 --seed 1213 --threads 8 --export data/worlds/pregen/meshes
+//! ```
+
+use std::{env, process, time::Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use voxelize::{MeshFormat, Pregenerator, Vec2, WorldConfig, WorldGenConfig};
+
+#[path = "../server/registry.rs"]
+mod registry;
+
+const USAGE: &str = "Usage: pregen --worldgen <file> --save-dir <dir> --min <x,z> --max <x,z> \
+[--seed <n>] [--chunk-size <n>] [--max-height <n>] [--sub-chunks <n>] [--threads <n>] \
+[--tile-size <n>] [--export <dir>] [--obj]";
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{message}\n{USAGE}");
+    process::exit(1);
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: Option<String>) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| exit_with(&format!("Invalid value for {name}.")))
+}
+
+fn parse_coords(name: &str, value: Option<String>) -> Vec2<i32> {
+    let value = value.unwrap_or_else(|| exit_with(&format!("Missing value for {name}.")));
+    let mut parts = value.split(',').map(|part| part.trim().parse::<i32>());
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(x)), Some(Ok(z)), None) => Vec2(x, z),
+        _ => exit_with(&format!("Invalid coordinates for {name}, expected x,z.")),
+    }
+}
+
+fn main() {
+    let mut worldgen = None;
+    let mut save_dir = None;
+    let mut min = None;
+    let mut max = None;
+    let mut export = None;
+    let mut format = MeshFormat::Gltf;
+    let mut threads = 0;
+    let mut tile_size = 8;
+
+    let mut config = WorldConfig::new().saving(true);
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--worldgen" => worldgen = args.next(),
+            "--save-dir" => save_dir = args.next(),
+            "--min" => min = Some(parse_coords(&arg, args.next())),
+            "--max" => max = Some(parse_coords(&arg, args.next())),
+            "--seed" => config = config.seed(parse(&arg, args.next())),
+            "--chunk-size" => config = config.chunk_size(parse(&arg, args.next())),
+            "--max-height" => config = config.max_height(parse(&arg, args.next())),
+            "--sub-chunks" => config = config.sub_chunks(parse(&arg, args.next())),
+            "--threads" => threads = parse(&arg, args.next()),
+            "--tile-size" => tile_size = parse(&arg, args.next()),
+            "--export" => export = args.next(),
+            "--obj" => format = MeshFormat::Obj,
+            _ => exit_with(&format!("Unknown argument {arg}.")),
+        }
+    }
+
+    let (worldgen, save_dir, min, max) = match (worldgen, save_dir, min, max) {
+        (Some(worldgen), Some(save_dir), Some(min), Some(max)) => (worldgen, save_dir, min, max),
+        _ => exit_with("Missing a required argument."),
+    };
+
+    let config = config.save_dir(&save_dir).build();
+    let registry = registry::setup_registry();
+
+    let worldgen = WorldGenConfig::load(&worldgen)
+        .unwrap_or_else(|e| exit_with(&format!("Could not load {worldgen}: {e}")));
+
+    let mut pregenerator = Pregenerator::from_worldgen(&worldgen, &config, &registry)
+        .unwrap_or_else(|e| exit_with(&format!("Could not build the stages: {e}")));
+
+    if threads > 0 {
+        pregenerator.set_threads(threads);
+    }
+
+    pregenerator.set_tile_size(tile_size);
+
+    if let Some(export) = export {
+        pregenerator.set_mesh_export(export, format);
+    }
+
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template(
+            "[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} chunks {spinner:.green}",
+        )
+        .unwrap()
+        .progress_chars("#>-"),
+    );
+
+    let start = Instant::now();
+
+    let count = pregenerator
+        .run(&min, &max, |done, total| {
+            bar.set_length(total as u64);
+            bar.set_position(done as u64);
+        })
+        .unwrap_or_else(|e| exit_with(&format!("Pregenerating failed: {e}")));
+
+    bar.finish_and_clear();
+
+    println!(
+        "Pregenerated {count} chunks into {save_dir} in {:.2?}.",
+        start.elapsed()
+    );
+}
@@ -182,7 +182,7 @@ pub fn setup_registry() -> Registry {
             .is_transparent(true)
             .is_see_through(true)
             .light_reduce(true)
-            .is_fluid(true)
+            .fluid_flow(5, 7)
             .faces(
                 &BlockFaces::six_faces()
                     .scale_y(0.8)
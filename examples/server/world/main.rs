@@ -2,59 +2,16 @@ use log::info;
 use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
 use specs::{Builder, Component, NullStorage, WorldExt};
-use std::f64;
+use std::{f64, sync::Arc};
 use voxelize::{
-    default_client_parser, BaseTerrainStage, Chunk, ChunkStage, CurrentChunkComp, ETypeComp,
-    EntityFlag, IDComp, InteractorComp, LSystem, MetadataComp, NoiseOptions, PositionComp,
-    Resources, RigidBody, RigidBodyComp, SeededNoise, Space, Terrain, TerrainLayer, Tree, Trees,
-    Vec3, VoxelAccess, World, WorldConfig, AABB,
+    default_client_parser, BaseTerrainStage, CurrentChunkComp, ETypeComp, EntityFlag, IDComp,
+    InteractorComp, LSystem, MetadataComp, NoiseOptions, PositionComp, RigidBody, RigidBodyComp,
+    SeededNoise, Structure, StructurePlacement, StructureStage, Terrain, TerrainLayer, Tree, Trees,
+    Vec3, World, WorldConfig, AABB,
 };
 
 use super::shared::{SoilingStage, MOUNTAIN_HEIGHT, PLAINS_HEIGHT, RIVER_HEIGHT, RIVER_TO_PLAINS};
 
-struct TreeStage {
-    trees: Trees,
-}
-
-impl TreeStage {
-    pub fn new(trees: Trees) -> Self {
-        Self { trees }
-    }
-}
-
-impl ChunkStage for TreeStage {
-    fn name(&self) -> String {
-        "Trees".to_owned()
-    }
-
-    fn process(&self, mut chunk: Chunk, resources: Resources, _: Option<Space>) -> Chunk {
-        let dirt = resources.registry.get_block_by_name("Dirt");
-        let grass_block = resources.registry.get_block_by_name("Grass Block");
-
-        for vx in chunk.min.0..chunk.max.0 {
-            for vz in chunk.min.2..chunk.max.2 {
-                let height = chunk.get_max_height(vx, vz) as i32;
-                let id = chunk.get_voxel(vx, height, vz);
-
-                if id != dirt.id && id != grass_block.id {
-                    continue;
-                }
-
-                if self.trees.should_plant(&Vec3(vx, height, vz)) {
-                    self.trees
-                        .generate("Palm", &Vec3(vx, height, vz))
-                        .into_iter()
-                        .for_each(|(Vec3(ux, uy, uz), id)| {
-                            chunk.set_voxel(ux, uy, uz, id);
-                        });
-                }
-            }
-        }
-
-        chunk
-    }
-}
-
 #[derive(Default, Component)]
 #[storage(NullStorage)]
 struct BoxFlag;
@@ -154,17 +111,35 @@ pub fn setup_main_world() -> World {
             config.seed,
             &NoiseOptions::new().frequency(0.4).lacunarity(2.9).build(),
         );
-        trees.set_threshold(1.5);
 
         trees.register("Oak", oak);
         trees.register("Palm", palm);
 
+        let trees = Arc::new(trees);
+
         pipeline.add_stage(terrain_stage);
         pipeline.add_stage(SoilingStage::new(
             config.seed,
             &NoiseOptions::new().frequency(0.04).lacunarity(3.0).build(),
         ));
-        pipeline.add_stage(TreeStage::new(trees));
+        pipeline.add_stage(
+            StructureStage::new(
+                config.seed,
+                StructurePlacement::Noise {
+                    options: NoiseOptions::new().frequency(0.4).lacunarity(2.9).build(),
+                    threshold: 1.5,
+                },
+            )
+            .name("Trees")
+            .add_structure(
+                Structure::new("Palm")
+                    .generator(move |at, _| trees.generate("Palm", at))
+                    .offset(Vec3(0, 0, 0))
+                    .ground(&["Dirt", "Grass Block"])
+                    .build(),
+            )
+            .unwrap(),
+        );
 
         // pipeline.add_stage(FlatlandStage::new(10, 2, 2, 2));
     }
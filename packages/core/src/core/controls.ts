@@ -506,7 +506,9 @@ export class RigidControls extends EventEmitter implements NetIntercept {
 
         for (const event of events) {
           switch (event.name.toLowerCase()) {
-            case "position": {
+            case "position":
+            // Sent by servers with authoritative movement, when this client strays too far.
+            case "vox-builtin:position": {
               this.body.setPosition(event.payload);
               break;
             }
@@ -87,6 +87,14 @@ fn default_info_handle(server: &Server) -> Value {
             world_info.insert("stats".to_owned(), json!(stats_info));
         }
 
+        world_info.insert(
+            "preload".to_owned(),
+            json!({
+                "preloading": world.preloading,
+                "progress": world.preload_progress,
+            }),
+        );
+
         {
             let chunks = world.chunks();
             let pipeline = world.pipeline();
@@ -115,7 +123,8 @@ fn default_info_handle(server: &Server) -> Value {
                     "pipeline_queue": pipeline.queue,
                     "mesher_chunks": mesher.map,
                     "mesher_queue": mesher.queue,
-                    "active_voxels": chunks.active_voxels.len()
+                    "active_voxels": chunks.scheduled_ticks.len(),
+                    "neighbor_updates": chunks.neighbor_updates.len()
                 }),
             );
         }
@@ -354,6 +363,12 @@ impl Server {
 
             world.preload();
 
+            // Background preloading is progressed by the regular ticks once the server starts.
+            if world.config().preload_in_background {
+                bars.push(None);
+                continue;
+            }
+
             let bar = m.insert_from_back(0, ProgressBar::new(100));
             bar.set_message(world.name.clone());
             bar.set_style(sty.clone());
@@ -395,7 +410,7 @@ impl Server {
         let preload_len = self
             .worlds
             .values()
-            .filter(|world| world.config().preload)
+            .filter(|world| world.config().preload && !world.config().preload_in_background)
             .collect::<Vec<&World>>()
             .len();
 
@@ -0,0 +1,87 @@
+use specs::{Entity, ReadStorage};
+
+use crate::{PathComp, RigidBody, RigidBodyComp, Search, Vec3};
+
+/// The result of ticking a behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BehaviorStatus {
+    /// The behavior is done, and did what it set out to do.
+    Success,
+
+    /// The behavior could not run, or gave up.
+    Failure,
+
+    /// The behavior needs more ticks to finish.
+    Running,
+}
+
+/// A node of an entity's behavior tree, ticked once per game tick while it runs.
+pub trait Behavior: Send + Sync {
+    /// Run the behavior for a tick.
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus;
+
+    /// Forget any progress, as the behavior was interrupted or finished.
+    fn reset(&mut self) {}
+}
+
+/// What a behavior can see and control of its entity and the world around it.
+pub struct BrainContext<'a> {
+    /// The entity the behavior belongs to.
+    pub entity: Entity,
+
+    /// The rigid body of the entity.
+    pub body: &'a RigidBody,
+
+    /// Where the entity is walking to. Behaviors move the entity by setting its goal.
+    pub path: &'a mut PathComp,
+
+    /// The time since the last tick, in seconds.
+    pub delta: f32,
+
+    search: &'a Search,
+    bodies: &'a ReadStorage<'a, RigidBodyComp>,
+}
+
+impl<'a> BrainContext<'a> {
+    /// Create the context of an entity for a tick.
+    pub fn new(
+        entity: Entity,
+        body: &'a RigidBody,
+        path: &'a mut PathComp,
+        delta: f32,
+        search: &'a Search,
+        bodies: &'a ReadStorage<'a, RigidBodyComp>,
+    ) -> Self {
+        Self {
+            entity,
+            body,
+            path,
+            delta,
+            search,
+            bodies,
+        }
+    }
+
+    /// The voxel the entity's feet are in.
+    pub fn feet(&self) -> Vec3<i32> {
+        let Vec3(px, _, pz) = self.body.get_position();
+
+        Vec3(
+            px.floor() as i32,
+            (self.body.aabb.min_y + 0.01).floor() as i32,
+            pz.floor() as i32,
+        )
+    }
+
+    /// The rigid body of the client closest to the entity, if any is within `range` blocks.
+    pub fn nearest_client(&self, range: f32) -> Option<&'a RigidBody> {
+        let position = self.body.get_position();
+
+        self.search
+            .search_clients(&position, 1)
+            .into_iter()
+            .find(|(distance, _)| *distance <= range * range)
+            .and_then(|(_, client)| self.bodies.get(*client))
+            .map(|body| &body.0)
+    }
+}
@@ -0,0 +1,87 @@
+use super::behavior::{Behavior, BehaviorStatus, BrainContext};
+
+/// Runs its children one after the other, failing as soon as one of them fails.
+pub struct Sequence {
+    children: Vec<Box<dyn Behavior>>,
+    current: usize,
+}
+
+impl Sequence {
+    /// Create a sequence of behaviors, run in order.
+    pub fn new(children: Vec<Box<dyn Behavior>>) -> Self {
+        Self {
+            children,
+            current: 0,
+        }
+    }
+}
+
+impl Behavior for Sequence {
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus {
+        while let Some(child) = self.children.get_mut(self.current) {
+            match child.tick(ctx) {
+                BehaviorStatus::Running => return BehaviorStatus::Running,
+                BehaviorStatus::Failure => {
+                    self.reset();
+                    return BehaviorStatus::Failure;
+                }
+                BehaviorStatus::Success => self.current += 1,
+            }
+        }
+
+        self.reset();
+        BehaviorStatus::Success
+    }
+
+    fn reset(&mut self) {
+        self.children.iter_mut().for_each(|child| child.reset());
+        self.current = 0;
+    }
+}
+
+/// Runs the first of its children that does not fail, checking them in order of priority every tick. A running
+/// child is interrupted as soon as a child before it stops failing.
+pub struct Selector {
+    children: Vec<Box<dyn Behavior>>,
+    running: Option<usize>,
+}
+
+impl Selector {
+    /// Create a selector of behaviors, from the highest priority to the lowest.
+    pub fn new(children: Vec<Box<dyn Behavior>>) -> Self {
+        Self {
+            children,
+            running: None,
+        }
+    }
+}
+
+impl Behavior for Selector {
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus {
+        for index in 0..self.children.len() {
+            let status = self.children[index].tick(ctx);
+
+            if status == BehaviorStatus::Failure {
+                continue;
+            }
+
+            // A higher priority child took over.
+            if let Some(running) = self.running {
+                if running != index {
+                    self.children[running].reset();
+                }
+            }
+
+            self.running = (status == BehaviorStatus::Running).then_some(index);
+            return status;
+        }
+
+        self.reset();
+        BehaviorStatus::Failure
+    }
+
+    fn reset(&mut self) {
+        self.children.iter_mut().for_each(|child| child.reset());
+        self.running = None;
+    }
+}
@@ -0,0 +1,7 @@
+mod behavior;
+mod composites;
+mod nodes;
+
+pub use behavior::*;
+pub use composites::*;
+pub use nodes::*;
@@ -0,0 +1,192 @@
+use crate::Vec3;
+
+use super::behavior::{Behavior, BehaviorStatus, BrainContext};
+
+/// Stands still for a while.
+pub struct Idle {
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Idle {
+    /// Create a behavior standing still for `duration` seconds.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl Behavior for Idle {
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus {
+        if self.elapsed == 0.0 {
+            ctx.path.stop();
+        }
+
+        self.elapsed += ctx.delta;
+
+        if self.elapsed < self.duration {
+            return BehaviorStatus::Running;
+        }
+
+        self.reset();
+        BehaviorStatus::Success
+    }
+
+    fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+/// Walks to a random voxel nearby, succeeding once there or once the voxel turns out to be unreachable.
+pub struct Wander {
+    radius: i32,
+    goal: Option<Vec3<i32>>,
+}
+
+impl Wander {
+    /// Create a behavior wandering up to `radius` blocks away horizontally.
+    pub fn new(radius: i32) -> Self {
+        Self { radius, goal: None }
+    }
+}
+
+impl Behavior for Wander {
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus {
+        match &self.goal {
+            // The path component drops the goal once arrived, or once it cannot get any closer.
+            Some(goal) if ctx.path.goal.as_ref() != Some(goal) => {
+                self.reset();
+                BehaviorStatus::Success
+            }
+            Some(_) => BehaviorStatus::Running,
+            None => {
+                let Vec3(vx, vy, vz) = ctx.feet();
+                let goal = Vec3(
+                    vx + fastrand::i32(-self.radius..=self.radius),
+                    vy,
+                    vz + fastrand::i32(-self.radius..=self.radius),
+                );
+
+                ctx.path.navigate_to(&goal);
+                self.goal = Some(goal);
+
+                BehaviorStatus::Running
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.goal = None;
+    }
+}
+
+/// Walks after the closest client within range, succeeding while close enough to it. Fails when no client is
+/// within range.
+pub struct Follow {
+    range: f32,
+    distance: f32,
+}
+
+impl Follow {
+    /// Create a behavior following clients within `range` blocks, up to `distance` blocks away from them.
+    pub fn new(range: f32, distance: f32) -> Self {
+        Self { range, distance }
+    }
+}
+
+impl Behavior for Follow {
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus {
+        let client = match ctx.nearest_client(self.range) {
+            Some(client) => client,
+            None => return BehaviorStatus::Failure,
+        };
+
+        let Vec3(px, _, pz) = ctx.body.get_position();
+        let Vec3(cx, _, cz) = client.get_position();
+
+        if (cx - px).hypot(cz - pz) <= self.distance {
+            ctx.path.stop();
+            return BehaviorStatus::Success;
+        }
+
+        let target = Vec3(
+            cx.floor() as i32,
+            (client.aabb.min_y + 0.01).floor() as i32,
+            cz.floor() as i32,
+        );
+
+        // Only search again once the client moved to another voxel.
+        if ctx.path.goal.as_ref() != Some(&target) {
+            ctx.path.navigate_to(&target);
+        }
+
+        BehaviorStatus::Running
+    }
+}
+
+/// Runs away from the closest client within range. Fails when no client is within range, so that the entity
+/// can go back to something else once safe.
+pub struct Flee {
+    range: f32,
+    distance: f32,
+    goal: Option<Vec3<i32>>,
+}
+
+impl Flee {
+    /// Create a behavior fleeing from clients within `range` blocks, `distance` blocks at a time.
+    pub fn new(range: f32, distance: f32) -> Self {
+        Self {
+            range,
+            distance,
+            goal: None,
+        }
+    }
+}
+
+impl Behavior for Flee {
+    fn tick(&mut self, ctx: &mut BrainContext) -> BehaviorStatus {
+        let client = match ctx.nearest_client(self.range) {
+            Some(client) => client,
+            None => {
+                self.reset();
+                return BehaviorStatus::Failure;
+            }
+        };
+
+        // Still running to the last voxel picked.
+        if self.goal.is_some() && ctx.path.goal == self.goal {
+            return BehaviorStatus::Running;
+        }
+
+        let Vec3(px, _, pz) = ctx.body.get_position();
+        let Vec3(cx, _, cz) = client.get_position();
+
+        // Standing right on top of each other, so pick any direction.
+        let (mut dx, mut dz) = (px - cx, pz - cz);
+        let length = dx.hypot(dz);
+        if length < f32::EPSILON {
+            let angle = fastrand::f32() * std::f32::consts::TAU;
+            (dx, dz) = (angle.cos(), angle.sin());
+        } else {
+            (dx, dz) = (dx / length, dz / length);
+        }
+
+        let Vec3(_, vy, _) = ctx.feet();
+        let goal = Vec3(
+            (px + dx * self.distance).floor() as i32,
+            vy,
+            (pz + dz * self.distance).floor() as i32,
+        );
+
+        ctx.path.navigate_to(&goal);
+        self.goal = Some(goal);
+
+        BehaviorStatus::Running
+    }
+
+    fn reset(&mut self) {
+        self.goal = None;
+    }
+}
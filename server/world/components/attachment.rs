@@ -0,0 +1,50 @@
+use rapier3d::prelude::ImpulseJointHandle;
+use serde::{Deserialize, Serialize};
+use specs::{Component, VecStorage};
+
+use crate::{JointKind, Vec3};
+
+/// Attaches this entity to a parent entity, moving it along with the parent.
+#[derive(Debug, Clone, Component, Serialize, Deserialize)]
+#[storage(VecStorage)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentComp {
+    /// The ID of the parent entity.
+    pub parent: String,
+
+    /// How this entity is held to the parent.
+    pub joint: JointKind,
+
+    /// Where this entity is held on the parent, relative to the parent's position.
+    pub parent_anchor: Vec3<f32>,
+
+    /// Where this entity is held by, relative to its own position.
+    pub child_anchor: Vec3<f32>,
+
+    /// The rapier joint between the two bodies, once both are physical.
+    #[serde(skip)]
+    pub(crate) handle: Option<ImpulseJointHandle>,
+}
+
+impl AttachmentComp {
+    /// Create a component attaching this entity to the parent of the ID, at the anchors.
+    pub fn new(
+        parent: &str,
+        joint: JointKind,
+        parent_anchor: &Vec3<f32>,
+        child_anchor: &Vec3<f32>,
+    ) -> Self {
+        Self {
+            parent: parent.to_owned(),
+            joint,
+            parent_anchor: parent_anchor.to_owned(),
+            child_anchor: child_anchor.to_owned(),
+            handle: None,
+        }
+    }
+
+    /// The rapier joint between the two bodies, once both are physical.
+    pub fn handle(&self) -> Option<&ImpulseJointHandle> {
+        self.handle.as_ref()
+    }
+}
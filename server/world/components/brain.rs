@@ -0,0 +1,16 @@
+use specs::{Component, VecStorage};
+
+use crate::Behavior;
+
+/// Drives an entity with a behavior tree, ticked every game tick. Entities with a brain also need a `PathComp`
+/// to move around.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct BrainComp(pub Box<dyn Behavior>);
+
+impl BrainComp {
+    /// Create a brain running the behavior as the root of its tree.
+    pub fn new<B: Behavior + 'static>(root: B) -> Self {
+        Self(Box::new(root))
+    }
+}
@@ -7,4 +7,5 @@ use crate::Vec2;
 pub struct CurrentChunkComp {
     pub coords: Vec2<i32>,
     pub changed: bool,
+    pub assigned: bool,
 }
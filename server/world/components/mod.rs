@@ -1,4 +1,6 @@
 mod addr;
+mod attachment;
+mod brain;
 mod chunk_requests;
 mod collisions;
 mod current_chunk;
@@ -8,11 +10,15 @@ mod flags;
 mod id;
 mod interactor;
 mod metadata;
+mod movement;
 mod name;
+mod path;
 mod position;
 mod rigidbody;
 
 pub use addr::AddrComp;
+pub use attachment::AttachmentComp;
+pub use brain::BrainComp;
 pub use chunk_requests::ChunkRequestsComp;
 pub use collisions::*;
 pub use current_chunk::CurrentChunkComp;
@@ -22,6 +28,8 @@ pub use flags::*;
 pub use id::IDComp;
 pub use interactor::InteractorComp;
 pub use metadata::MetadataComp;
+pub use movement::ClientMovementComp;
 pub use name::NameComp;
+pub use path::PathComp;
 pub use position::PositionComp;
 pub use rigidbody::RigidBodyComp;
@@ -0,0 +1,21 @@
+use specs::{Component, VecStorage};
+
+use crate::Vec3;
+
+/// The movement a client intends, validated by the server under authoritative movement.
+#[derive(Debug, Default, Component)]
+#[storage(VecStorage)]
+pub struct ClientMovementComp {
+    /// The position the client last sent, waiting to be validated.
+    pub target: Option<Vec3<f32>>,
+
+    /// Whether the client is allowed to fly, skipping the fall checks.
+    pub can_fly: bool,
+}
+
+impl ClientMovementComp {
+    /// Create a new component of the movement a client intends.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,56 @@
+use specs::{Component, VecStorage};
+
+use crate::{Path, PathOptions, Vec3};
+
+/// Walks an entity to a goal, finding a path on the pathfinding workers and steering its rigid body along it.
+#[derive(Debug, Default, Component)]
+#[storage(VecStorage)]
+pub struct PathComp {
+    /// The voxel the entity is walking to, if any.
+    pub goal: Option<Vec3<i32>>,
+
+    /// The parameters of the path searches.
+    pub options: PathOptions,
+
+    /// How fast the entity walks, in blocks per second.
+    pub speed: f32,
+
+    /// The path being followed, once found.
+    pub path: Option<Path>,
+
+    /// The index of the next node of the path to reach.
+    pub index: usize,
+
+    /// The goal of the search in progress.
+    pub(crate) requested: Option<Vec3<i32>>,
+}
+
+impl PathComp {
+    /// Create a new component of an entity walking at `speed` blocks per second.
+    pub fn new(speed: f32, options: &PathOptions) -> Self {
+        Self {
+            speed,
+            options: options.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Start walking to a voxel, dropping the current path.
+    pub fn navigate_to(&mut self, goal: &Vec3<i32>) {
+        self.goal = Some(goal.to_owned());
+        self.path = None;
+        self.index = 0;
+    }
+
+    /// Stop walking.
+    pub fn stop(&mut self) {
+        self.goal = None;
+        self.path = None;
+        self.index = 0;
+    }
+
+    /// Whether the entity is walking to a goal.
+    pub fn is_navigating(&self) -> bool {
+        self.goal.is_some()
+    }
+}
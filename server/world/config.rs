@@ -1,7 +1,56 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::Vec2;
 
 use super::generators::NoiseOptions;
 
+/// A region of chunks to be preloaded before the world is considered ready.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PreloadShape {
+    /// A square of chunks within `radius` chunks of the center on both axes.
+    Square { center: Vec2<i32>, radius: usize },
+
+    /// A circle of chunks within `radius` chunks of the center.
+    Circle { center: Vec2<i32>, radius: usize },
+
+    /// A rectangle of chunks from `min` to `max`, inclusive.
+    Rect { min: Vec2<i32>, max: Vec2<i32> },
+}
+
+impl PreloadShape {
+    /// Get the chunk coordinates covered by this shape.
+    pub fn chunks(&self) -> Vec<Vec2<i32>> {
+        let mut list = vec![];
+
+        match self {
+            Self::Square { center, radius } | Self::Circle { center, radius } => {
+                let radius = *radius as i32;
+                let is_circle = matches!(self, Self::Circle { .. });
+
+                for x in -radius..=radius {
+                    for z in -radius..=radius {
+                        if is_circle && x * x + z * z > radius * radius {
+                            continue;
+                        }
+
+                        list.push(Vec2(center.0 + x, center.1 + z));
+                    }
+                }
+            }
+            Self::Rect { min, max } => {
+                for x in min.0..=max.0 {
+                    for z in min.1..=max.1 {
+                        list.push(Vec2(x, z));
+                    }
+                }
+            }
+        }
+
+        list
+    }
+}
+
 /// World configuration, storing information of how a world is constructed.
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +76,12 @@ pub struct WorldConfig {
     /// The radius at which the world should preload.
     pub preload_radius: usize,
 
+    /// The regions of chunks to preload. If empty, a square of `preload_radius` around the origin is preloaded.
+    pub preload_regions: Vec<PreloadShape>,
+
+    /// Whether to preload after the server starts accepting connections instead of blocking startup.
+    pub preload_in_background: bool,
+
     /// Max height of the world. Default is 256 blocks high.
     pub max_height: usize,
 
@@ -39,6 +94,14 @@ pub struct WorldConfig {
     /// Maximum voxel updates to be processed per tick. Default is 1000 voxels.
     pub max_updates_per_tick: usize,
 
+    /// Maximum voxel updates caused by `Block::on_neighbor_update` per tick. The rest of a chain reaction is
+    /// deferred to the next ticks. Default is 1024 voxels.
+    pub max_neighbor_updates_per_tick: usize,
+
+    /// Number of random voxels per sub-chunk that get a `Block::random_tick` each tick, in chunks that clients
+    /// are interested in. Zero disables random ticks. Default is 3 voxels.
+    pub random_tick_speed: usize,
+
     /// Maximum responses to send to client per tick to prevent bottle-necking. Default is 4 chunks.
     pub max_response_per_tick: usize,
 
@@ -70,6 +133,34 @@ pub struct WorldConfig {
     /// The repulsion factor when a collision is detected between entities.
     pub collision_repulsion: f32,
 
+    /// Whether the server validates client movements instead of trusting the positions clients send, by
+    /// simulating the client bodies against the voxels and correcting clients that diverge. Default is false.
+    pub authoritative_movement: bool,
+
+    /// Maximum speed of client movements under authoritative movement, in blocks per second. Default is 16.
+    pub max_client_speed: f32,
+
+    /// How far a client can diverge from the position validated by the server before being corrected, in
+    /// blocks. Default is 1 block.
+    pub movement_tolerance: f32,
+
+    /// Length of a physics step, in seconds. Physics is stepped in fixed steps of this length, and entity
+    /// positions are interpolated in between. Set to 0 to step physics once per tick with the tick's delta.
+    /// Default is 1/60 of a second.
+    pub physics_step: f32,
+
+    /// Maximum number of physics steps run in a single tick. Time beyond that is dropped so that a slow tick
+    /// does not snowball into slower ones. Default is 5.
+    pub max_physics_substeps: usize,
+
+    /// Whether ticks advance by exactly `physics_step` instead of the wall-clock time, so that physics runs
+    /// one step per tick regardless of timing. Default is false.
+    pub deterministic_physics: bool,
+
+    /// Whether the bodies of entities in chunks no client is interested in are frozen, skipping their physics
+    /// entirely until a client comes close again. Default is false.
+    pub freeze_uninterested_bodies: bool,
+
     /// Seed of the world. Default is "Voxelize".
     pub seed: u32,
 
@@ -100,6 +191,39 @@ impl WorldConfig {
     pub fn new() -> WorldConfigBuilder {
         WorldConfigBuilder::new()
     }
+
+    /// Get the sorted list of chunks that need to be ready for preloading to finish. Without any preload
+    /// regions, this is the square of `preload_radius` around the origin, minus the light padding.
+    pub fn preload_chunks(&self) -> Vec<Vec2<i32>> {
+        let regions = if self.preload_regions.is_empty() {
+            let light_padding =
+                (self.max_light_level as f32 / self.chunk_size as f32).ceil() as usize;
+
+            vec![PreloadShape::Square {
+                center: Vec2(0, 0),
+                radius: self.preload_radius.saturating_sub(light_padding),
+            }]
+        } else {
+            self.preload_regions.to_owned()
+        };
+
+        let mut chunks = regions
+            .iter()
+            .flat_map(|region| region.chunks())
+            .filter(|coords| {
+                coords.0 >= self.min_chunk[0]
+                    && coords.0 <= self.max_chunk[0]
+                    && coords.1 >= self.min_chunk[1]
+                    && coords.1 <= self.max_chunk[1]
+            })
+            .map(|Vec2(x, z)| (x, z))
+            .collect::<Vec<_>>();
+
+        chunks.sort_unstable();
+        chunks.dedup();
+
+        chunks.into_iter().map(|(x, z)| Vec2(x, z)).collect()
+    }
 }
 
 const DEFAULT_MAX_CLIENT: usize = 100;
@@ -109,10 +233,13 @@ const DEFAULT_MIN_CHUNK: [i32; 2] = [i32::MIN + 1, i32::MIN + 1];
 const DEFAULT_MAX_CHUNK: [i32; 2] = [i32::MAX - 1, i32::MAX - 1];
 const DEFAULT_PRELOAD: bool = false;
 const DEFAULT_PRELOAD_RADIUS: usize = 8;
+const DEFAULT_PRELOAD_IN_BACKGROUND: bool = false;
 const DEFAULT_MAX_HEIGHT: usize = 256;
 const DEFAULT_MAX_LIGHT_LEVEL: u32 = 15;
 const DEFAULT_MAX_CHUNKS_PER_TICK: usize = 8;
 const DEFAULT_MAX_UPDATES_PER_TICK: usize = 1000;
+const DEFAULT_MAX_NEIGHBOR_UPDATES_PER_TICK: usize = 1024;
+const DEFAULT_RANDOM_TICK_SPEED: usize = 3;
 const DEFAULT_MAX_RESPONSE_PER_TICK: usize = 4;
 const DEFAULT_MAX_SAVES_PER_TICK: usize = 2;
 const DEFAULT_TICKS_PER_DAY: u64 = 24000;
@@ -124,6 +251,13 @@ const DEFAULT_AIR_DRAG: f32 = 0.1;
 const DEFAULT_FLUID_DRAG: f32 = 1.4;
 const DEFAULT_FLUID_DENSITY: f32 = 0.8;
 const DEFAULT_COLLISION_REPULSION: f32 = 2.3;
+const DEFAULT_AUTHORITATIVE_MOVEMENT: bool = false;
+const DEFAULT_MAX_CLIENT_SPEED: f32 = 16.0;
+const DEFAULT_MOVEMENT_TOLERANCE: f32 = 1.0;
+const DEFAULT_PHYSICS_STEP: f32 = 1.0 / 60.0;
+const DEFAULT_MAX_PHYSICS_SUBSTEPS: usize = 5;
+const DEFAULT_DETERMINISTIC_PHYSICS: bool = false;
+const DEFAULT_FREEZE_UNINTERESTED_BODIES: bool = false;
 const DEFAULT_SAVING: bool = false;
 const DEFAULT_SAVE_DIR: &str = "";
 const DEFAULT_SAVE_INTERVAL: usize = 300;
@@ -138,10 +272,14 @@ pub struct WorldConfigBuilder {
     max_chunk: [i32; 2],
     preload: bool,
     preload_radius: usize,
+    preload_regions: Vec<PreloadShape>,
+    preload_in_background: bool,
     max_height: usize,
     max_light_level: u32,
     max_chunks_per_tick: usize,
     max_updates_per_tick: usize,
+    max_neighbor_updates_per_tick: usize,
+    random_tick_speed: usize,
     max_response_per_tick: usize,
     max_saves_per_tick: usize,
     time_per_day: u64,
@@ -153,6 +291,13 @@ pub struct WorldConfigBuilder {
     fluid_drag: f32,
     fluid_density: f32,
     collision_repulsion: f32,
+    authoritative_movement: bool,
+    max_client_speed: f32,
+    movement_tolerance: f32,
+    physics_step: f32,
+    max_physics_substeps: usize,
+    deterministic_physics: bool,
+    freeze_uninterested_bodies: bool,
     terrain: NoiseOptions,
     saving: bool,
     save_dir: String,
@@ -171,10 +316,14 @@ impl WorldConfigBuilder {
             max_chunk: DEFAULT_MAX_CHUNK,
             preload: DEFAULT_PRELOAD,
             preload_radius: DEFAULT_PRELOAD_RADIUS,
+            preload_regions: vec![],
+            preload_in_background: DEFAULT_PRELOAD_IN_BACKGROUND,
             max_height: DEFAULT_MAX_HEIGHT,
             max_light_level: DEFAULT_MAX_LIGHT_LEVEL,
             max_chunks_per_tick: DEFAULT_MAX_CHUNKS_PER_TICK,
             max_updates_per_tick: DEFAULT_MAX_UPDATES_PER_TICK,
+            max_neighbor_updates_per_tick: DEFAULT_MAX_NEIGHBOR_UPDATES_PER_TICK,
+            random_tick_speed: DEFAULT_RANDOM_TICK_SPEED,
             max_response_per_tick: DEFAULT_MAX_RESPONSE_PER_TICK,
             max_saves_per_tick: DEFAULT_MAX_SAVES_PER_TICK,
             time_per_day: DEFAULT_TICKS_PER_DAY,
@@ -186,6 +335,13 @@ impl WorldConfigBuilder {
             gravity: DEFAULT_GRAVITY,
             min_bounce_impulse: DEFAULT_MIN_BOUNCE_IMPULSE,
             collision_repulsion: DEFAULT_COLLISION_REPULSION,
+            authoritative_movement: DEFAULT_AUTHORITATIVE_MOVEMENT,
+            max_client_speed: DEFAULT_MAX_CLIENT_SPEED,
+            movement_tolerance: DEFAULT_MOVEMENT_TOLERANCE,
+            physics_step: DEFAULT_PHYSICS_STEP,
+            max_physics_substeps: DEFAULT_MAX_PHYSICS_SUBSTEPS,
+            deterministic_physics: DEFAULT_DETERMINISTIC_PHYSICS,
+            freeze_uninterested_bodies: DEFAULT_FREEZE_UNINTERESTED_BODIES,
             saving: DEFAULT_SAVING,
             save_dir: DEFAULT_SAVE_DIR.to_owned(),
             save_interval: DEFAULT_SAVE_INTERVAL,
@@ -240,6 +396,20 @@ impl WorldConfigBuilder {
         self
     }
 
+    /// Add a region of chunks to preload, such as the area around a spawn point. Turns on preloading.
+    pub fn add_preload_region(mut self, region: PreloadShape) -> Self {
+        self.preload = true;
+        self.preload_regions.push(region);
+        self
+    }
+
+    /// Configure whether the world preloads in the background after the server starts, instead of
+    /// blocking startup. Progress is exposed through the `/info` endpoint. Default is false.
+    pub fn preload_in_background(mut self, preload_in_background: bool) -> Self {
+        self.preload_in_background = preload_in_background;
+        self
+    }
+
     /// Configure the maximum height of the world. Default is 256 blocks high.
     pub fn max_height(mut self, max_height: usize) -> Self {
         self.max_height = max_height;
@@ -265,6 +435,18 @@ impl WorldConfigBuilder {
         self
     }
 
+    /// Configure the maximum amount of voxel updates caused by neighbor update callbacks per tick. Default is 1024 voxels.
+    pub fn max_neighbor_updates_per_tick(mut self, max_neighbor_updates_per_tick: usize) -> Self {
+        self.max_neighbor_updates_per_tick = max_neighbor_updates_per_tick;
+        self
+    }
+
+    /// Configure the amount of random voxels ticked per sub-chunk per tick. Default is 3 voxels.
+    pub fn random_tick_speed(mut self, random_tick_speed: usize) -> Self {
+        self.random_tick_speed = random_tick_speed;
+        self
+    }
+
     /// Configure the maximum amount of chunks to be sent to the client per tick. Default is 3 chunks.
     pub fn max_response_per_tick(mut self, max_response_per_tick: usize) -> Self {
         self.max_response_per_tick = max_response_per_tick;
@@ -307,6 +489,48 @@ impl WorldConfigBuilder {
         self
     }
 
+    /// Configure whether the server validates client movements. Defaults to `false`.
+    pub fn authoritative_movement(mut self, authoritative_movement: bool) -> Self {
+        self.authoritative_movement = authoritative_movement;
+        self
+    }
+
+    /// Configure the maximum speed of validated client movements. Defaults to `16.0` blocks per second.
+    pub fn max_client_speed(mut self, max_client_speed: f32) -> Self {
+        self.max_client_speed = max_client_speed;
+        self
+    }
+
+    /// Configure how far clients can diverge before being corrected. Defaults to `1.0` block.
+    pub fn movement_tolerance(mut self, movement_tolerance: f32) -> Self {
+        self.movement_tolerance = movement_tolerance;
+        self
+    }
+
+    /// Configure the length of a fixed physics step, 0 to step once per tick. Defaults to `1/60` of a second.
+    pub fn physics_step(mut self, physics_step: f32) -> Self {
+        self.physics_step = physics_step;
+        self
+    }
+
+    /// Configure the maximum number of physics steps per tick. Defaults to `5`.
+    pub fn max_physics_substeps(mut self, max_physics_substeps: usize) -> Self {
+        self.max_physics_substeps = max_physics_substeps;
+        self
+    }
+
+    /// Configure whether ticks advance by exactly one physics step. Defaults to `false`.
+    pub fn deterministic_physics(mut self, deterministic_physics: bool) -> Self {
+        self.deterministic_physics = deterministic_physics;
+        self
+    }
+
+    /// Configure whether bodies in chunks without interested clients are frozen. Defaults to `false`.
+    pub fn freeze_uninterested_bodies(mut self, freeze_uninterested_bodies: bool) -> Self {
+        self.freeze_uninterested_bodies = freeze_uninterested_bodies;
+        self
+    }
+
     /// Configure whether or not this world should have the chunk data saved.
     pub fn saving(mut self, saving: bool) -> Self {
         self.saving = saving;
@@ -354,6 +578,8 @@ impl WorldConfigBuilder {
             max_light_level: self.max_light_level,
             max_chunks_per_tick: self.max_chunks_per_tick,
             max_updates_per_tick: self.max_updates_per_tick,
+            max_neighbor_updates_per_tick: self.max_neighbor_updates_per_tick,
+            random_tick_speed: self.random_tick_speed,
             max_response_per_tick: self.max_response_per_tick,
             max_saves_per_tick: self.max_saves_per_tick,
             time_per_day: self.time_per_day,
@@ -363,12 +589,21 @@ impl WorldConfigBuilder {
             max_chunk: self.max_chunk,
             preload: self.preload,
             preload_radius: self.preload_radius,
+            preload_regions: self.preload_regions,
+            preload_in_background: self.preload_in_background,
             air_drag: self.air_drag,
             fluid_drag: self.fluid_drag,
             fluid_density: self.fluid_density,
             gravity: self.gravity,
             min_bounce_impulse: self.min_bounce_impulse,
             collision_repulsion: self.collision_repulsion,
+            authoritative_movement: self.authoritative_movement,
+            max_client_speed: self.max_client_speed,
+            movement_tolerance: self.movement_tolerance,
+            physics_step: self.physics_step,
+            max_physics_substeps: self.max_physics_substeps,
+            deterministic_physics: self.deterministic_physics,
+            freeze_uninterested_bodies: self.freeze_uninterested_bodies,
             terrain: self.terrain,
             saving: self.saving,
             save_dir: self.save_dir,
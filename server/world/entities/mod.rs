@@ -1,3 +1,5 @@
 mod saver;
+mod spawner;
 
 pub use saver::*;
+pub use spawner::*;
@@ -0,0 +1,429 @@
+use hashbrown::HashMap;
+use log::warn;
+use specs::Entity;
+
+use crate::{
+    ChunkUtils, Chunks, LightColor, Registry, Terrain, Vec2, Vec3, VoxelAccess, WorldConfig,
+};
+
+/// When and where an entity type naturally spawns around the clients, checked by a `Spawner`.
+#[derive(Debug, Clone)]
+pub struct SpawnRule {
+    /// The type of entity to spawn, as registered with `World::set_entity_loader`.
+    pub etype: String,
+
+    /// Names of biomes this entity spawns in. Empty means any biome.
+    pub biomes: Vec<String>,
+
+    /// Names of the blocks this entity spawns on. Empty means any solid block.
+    pub ground: Vec<String>,
+
+    /// The lowest y-coordinate of the voxel the entity's feet spawn in, inclusive.
+    pub min_height: i32,
+
+    /// The highest y-coordinate of the voxel the entity's feet spawn in, inclusive.
+    pub max_height: i32,
+
+    /// The range of sunlight levels this entity spawns in, inclusive.
+    pub sunlight: (u32, u32),
+
+    /// The range of torch light levels this entity spawns in, inclusive. The brightest color counts.
+    pub torch_light: (u32, u32),
+
+    /// The number of open voxels this entity needs above the ground.
+    pub height: usize,
+
+    /// The most entities of this type in a chunk for more to spawn in it.
+    pub max_per_chunk: usize,
+
+    /// The most entities of this type in the world for more to spawn.
+    pub max_total: usize,
+
+    /// How close to a client, horizontally, this entity can spawn.
+    pub min_distance: f32,
+
+    /// How far from a client, horizontally, this entity can spawn.
+    pub max_distance: f32,
+
+    /// How far from every client this entity is despawned.
+    pub despawn_distance: f32,
+
+    /// How many spots are tried around each client every spawning round.
+    pub attempts: usize,
+}
+
+impl SpawnRule {
+    /// Create a spawn rule builder with the type of entity to spawn.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(etype: &str) -> SpawnRuleBuilder {
+        SpawnRuleBuilder::new(etype)
+    }
+}
+
+/// Builder for a spawn rule.
+pub struct SpawnRuleBuilder {
+    etype: String,
+    biomes: Vec<String>,
+    ground: Vec<String>,
+    min_height: i32,
+    max_height: i32,
+    sunlight: (u32, u32),
+    torch_light: (u32, u32),
+    height: usize,
+    max_per_chunk: usize,
+    max_total: usize,
+    min_distance: f32,
+    max_distance: f32,
+    despawn_distance: f32,
+    attempts: usize,
+}
+
+impl SpawnRuleBuilder {
+    /// Create a spawn rule builder with default values.
+    pub fn new(etype: &str) -> Self {
+        Self {
+            etype: etype.to_owned(),
+            biomes: vec![],
+            ground: vec![],
+            min_height: 0,
+            max_height: i32::MAX,
+            sunlight: (0, u32::MAX),
+            torch_light: (0, u32::MAX),
+            height: 2,
+            max_per_chunk: 4,
+            max_total: 32,
+            min_distance: 24.0,
+            max_distance: 64.0,
+            despawn_distance: 128.0,
+            attempts: 4,
+        }
+    }
+
+    /// Configure the biomes this entity spawns in. Defaults to any biome.
+    pub fn biomes(mut self, biomes: &[&str]) -> Self {
+        self.biomes = biomes.iter().map(|name| (*name).to_owned()).collect();
+        self
+    }
+
+    /// Configure the blocks this entity spawns on. Defaults to any solid block.
+    pub fn ground(mut self, ground: &[&str]) -> Self {
+        self.ground = ground.iter().map(|name| (*name).to_owned()).collect();
+        self
+    }
+
+    /// Configure the range of y-coordinates this entity's feet spawn at, inclusive. Defaults to any height.
+    pub fn height_range(mut self, min_height: i32, max_height: i32) -> Self {
+        self.min_height = min_height;
+        self.max_height = max_height;
+        self
+    }
+
+    /// Configure the range of sunlight levels this entity spawns in, inclusive. Defaults to any level.
+    pub fn sunlight(mut self, min: u32, max: u32) -> Self {
+        self.sunlight = (min, max);
+        self
+    }
+
+    /// Configure the range of torch light levels this entity spawns in, inclusive. Defaults to any level.
+    pub fn torch_light(mut self, min: u32, max: u32) -> Self {
+        self.torch_light = (min, max);
+        self
+    }
+
+    /// Configure the number of open voxels this entity needs above the ground. Defaults to 2.
+    pub fn height(mut self, height: usize) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Configure the most entities of this type in a chunk for more to spawn in it. Defaults to 4.
+    pub fn max_per_chunk(mut self, max_per_chunk: usize) -> Self {
+        self.max_per_chunk = max_per_chunk;
+        self
+    }
+
+    /// Configure the most entities of this type in the world for more to spawn. Defaults to 32.
+    pub fn max_total(mut self, max_total: usize) -> Self {
+        self.max_total = max_total;
+        self
+    }
+
+    /// Configure how close to and how far from a client this entity spawns, horizontally. Defaults to 24 and 64.
+    pub fn distance_range(mut self, min_distance: f32, max_distance: f32) -> Self {
+        self.min_distance = min_distance;
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Configure how far from every client this entity is despawned. Defaults to 128.
+    pub fn despawn_distance(mut self, despawn_distance: f32) -> Self {
+        self.despawn_distance = despawn_distance;
+        self
+    }
+
+    /// Configure how many spots are tried around each client every spawning round. Defaults to 4.
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Create a spawn rule instance.
+    pub fn build(self) -> SpawnRule {
+        SpawnRule {
+            etype: self.etype,
+            biomes: self.biomes,
+            ground: self.ground,
+            min_height: self.min_height,
+            max_height: self.max_height,
+            sunlight: self.sunlight,
+            torch_light: self.torch_light,
+            height: self.height,
+            max_per_chunk: self.max_per_chunk,
+            max_total: self.max_total,
+            min_distance: self.min_distance,
+            max_distance: self.max_distance,
+            despawn_distance: self.despawn_distance,
+            attempts: self.attempts,
+        }
+    }
+}
+
+/// An entity counted towards the spawn caps, as its entity, type and position.
+pub type SpawnedEntity = (Entity, String, Vec3<f32>);
+
+/// Periodically spawns entities around the clients following the spawn rules, and despawns the entities of
+/// those types that are far from every client. Runs every `interval` ticks.
+pub struct Spawner {
+    rules: Vec<SpawnRule>,
+    terrain: Option<Terrain>,
+    interval: u64,
+}
+
+impl Default for Spawner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Spawner {
+    /// Create a spawner without any rules, running every 20 ticks.
+    pub fn new() -> Self {
+        Self {
+            rules: vec![],
+            terrain: None,
+            interval: 20,
+        }
+    }
+
+    /// Add a spawn rule. Rules with biomes need the terrain to be set first.
+    pub fn add_rule(&mut self, rule: SpawnRule) {
+        if !rule.biomes.is_empty() && self.terrain.is_none() {
+            warn!(
+                "Spawn rule for {} has biomes but the spawner has no terrain, biomes will be ignored.",
+                rule.etype
+            );
+        }
+
+        self.rules.push(rule);
+    }
+
+    /// Set the terrain that the biomes of the spawn rules are looked up in. Without it, biomes are ignored.
+    pub fn set_terrain(&mut self, terrain: Terrain) {
+        self.terrain = Some(terrain);
+    }
+
+    /// Set how many ticks apart the spawning rounds are.
+    pub fn set_interval(&mut self, interval: u64) {
+        self.interval = interval.max(1);
+    }
+
+    /// Whether a spawning round is due on this tick.
+    pub fn is_due(&self, tick: u64) -> bool {
+        !self.rules.is_empty() && tick.is_multiple_of(self.interval)
+    }
+
+    /// Pick the entities to spawn this round around the clients, as their types and positions, keeping the
+    /// populations within the caps of the rules.
+    pub fn spawns(
+        &self,
+        chunks: &Chunks,
+        registry: &Registry,
+        config: &WorldConfig,
+        clients: &[Vec3<f32>],
+        population: &[SpawnedEntity],
+    ) -> Vec<(String, Vec3<f32>)> {
+        let chunk_of = |Vec3(px, py, pz): &Vec3<f32>| {
+            ChunkUtils::map_voxel_to_chunk(
+                px.floor() as i32,
+                py.floor() as i32,
+                pz.floor() as i32,
+                config.chunk_size,
+            )
+        };
+
+        let mut spawns = vec![];
+
+        for rule in &self.rules {
+            let mut total = 0;
+            let mut per_chunk: HashMap<Vec2<i32>, usize> = HashMap::new();
+
+            population
+                .iter()
+                .filter(|(_, etype, _)| etype.eq_ignore_ascii_case(&rule.etype))
+                .for_each(|(_, _, position)| {
+                    total += 1;
+                    *per_chunk.entry(chunk_of(position)).or_default() += 1;
+                });
+
+            for Vec3(cx, _, cz) in clients {
+                for _ in 0..rule.attempts {
+                    if total >= rule.max_total {
+                        break;
+                    }
+
+                    let angle = fastrand::f32() * std::f32::consts::TAU;
+                    let distance = rule.min_distance
+                        + fastrand::f32() * (rule.max_distance - rule.min_distance).max(0.0);
+                    let vx = (cx + angle.cos() * distance).floor() as i32;
+                    let vz = (cz + angle.sin() * distance).floor() as i32;
+
+                    let vy = match self.find_ground(rule, chunks, registry, config, vx, vz) {
+                        Some(vy) => vy,
+                        None => continue,
+                    };
+
+                    let position = Vec3(
+                        vx as f32 + 0.5,
+                        vy as f32 + rule.height as f32 / 2.0,
+                        vz as f32 + 0.5,
+                    );
+
+                    // Too close to another client.
+                    if clients.iter().any(|Vec3(ox, _, oz)| {
+                        (position.0 - ox).hypot(position.2 - oz) < rule.min_distance
+                    }) {
+                        continue;
+                    }
+
+                    let count = per_chunk.entry(chunk_of(&position)).or_default();
+                    if *count >= rule.max_per_chunk {
+                        continue;
+                    }
+
+                    *count += 1;
+                    total += 1;
+                    spawns.push((rule.etype.to_owned(), position));
+                }
+            }
+        }
+
+        spawns
+    }
+
+    /// Pick the spawnable entities that are too far from every client, to be despawned.
+    pub fn despawns(&self, clients: &[Vec3<f32>], population: &[SpawnedEntity]) -> Vec<Entity> {
+        population
+            .iter()
+            .filter(|(_, etype, Vec3(px, _, pz))| {
+                let rule = match self
+                    .rules
+                    .iter()
+                    .find(|rule| rule.etype.eq_ignore_ascii_case(etype))
+                {
+                    Some(rule) => rule,
+                    None => return false,
+                };
+
+                clients
+                    .iter()
+                    .all(|Vec3(cx, _, cz)| (px - cx).hypot(pz - cz) > rule.despawn_distance)
+            })
+            .map(|(entity, _, _)| *entity)
+            .collect()
+    }
+
+    /// Find the voxel an entity following the rule could spawn with its feet in, in a column. Looks down from a
+    /// random height within the rule's range, so that caves get spawns too.
+    fn find_ground(
+        &self,
+        rule: &SpawnRule,
+        chunks: &Chunks,
+        registry: &Registry,
+        config: &WorldConfig,
+        vx: i32,
+        vz: i32,
+    ) -> Option<i32> {
+        if !chunks.is_chunk_ready(&ChunkUtils::map_voxel_to_chunk(
+            vx,
+            0,
+            vz,
+            config.chunk_size,
+        )) {
+            return None;
+        }
+
+        if let Some(terrain) = &self.terrain {
+            if !rule.biomes.is_empty() {
+                let biome = terrain.get_biome_at(vx, vz);
+                if !rule
+                    .biomes
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&biome.name))
+                {
+                    return None;
+                }
+            }
+        }
+
+        let min_height = rule.min_height.max(1);
+        let max_height = rule
+            .max_height
+            .min(config.max_height as i32 - rule.height as i32);
+        if min_height > max_height {
+            return None;
+        }
+
+        let is_open = |vy: i32| {
+            let block = registry.get_block_by_id(chunks.get_voxel(vx, vy, vz));
+            (block.is_empty || block.is_passable) && !block.is_fluid
+        };
+
+        let start = fastrand::i32(min_height..=max_height);
+
+        let vy = (min_height..=start).rev().find(|&vy| {
+            let ground = registry.get_block_by_id(chunks.get_voxel(vx, vy - 1, vz));
+            !ground.is_empty
+                && !ground.is_fluid
+                && !ground.is_passable
+                && (0..rule.height as i32).all(|dy| is_open(vy + dy))
+        })?;
+
+        if !rule.ground.is_empty() {
+            let ground = registry.get_block_by_id(chunks.get_voxel(vx, vy - 1, vz));
+            if !rule
+                .ground
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&ground.name))
+            {
+                return None;
+            }
+        }
+
+        let sunlight = chunks.get_sunlight(vx, vy, vz);
+        let torch_light = [LightColor::Red, LightColor::Green, LightColor::Blue]
+            .iter()
+            .map(|color| chunks.get_torch_light(vx, vy, vz, color))
+            .max()
+            .unwrap_or(0);
+
+        if sunlight < rule.sunlight.0
+            || sunlight > rule.sunlight.1
+            || torch_light < rule.torch_light.0
+            || torch_light > rule.torch_light.1
+        {
+            return None;
+        }
+
+        Some(vy)
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{Chunk, ChunkStage, NoiseOptions, Resources, SeededNoise, Space, Vec3, VoxelAccess};
+
+/// A chunk stage that carves 3D caves, tunnels and overhangs out of the terrain.
+///
+/// Two kinds of caves are supported, and both can be enabled at once:
+/// - Cheese caves: large open pockets wherever the 3D noise is above a threshold.
+/// - Worm caves: winding tunnels where two 3D noises are both close to zero.
+///
+/// Carved voxels at or below the world's `water_level` can optionally be filled with a fluid. The
+/// height map and lighting stay correct since the pipeline recalculates the max heights after each
+/// stage, and the lights are flood-filled from the carved voxels once the chunk is done.
+pub struct CaveStage {
+    cheese: Option<(SeededNoise, f64)>,
+    worms: Option<(SeededNoise, SeededNoise, f64)>,
+    min_height: i32,
+    max_height: i32,
+    surface_margin: i32,
+    fluid: Option<u32>,
+}
+
+impl CaveStage {
+    /// Create a cave stage without any caves. Add caves with `cheese` and `worms`.
+    pub fn new() -> Self {
+        Self {
+            cheese: None,
+            worms: None,
+            min_height: 1,
+            max_height: i32::MAX,
+            surface_margin: 0,
+            fluid: None,
+        }
+    }
+
+    /// Carve cheese caves where the 3D noise is above the threshold, from -1.0 to 1.0.
+    pub fn cheese(mut self, seed: u32, options: &NoiseOptions, threshold: f64) -> Self {
+        self.cheese = Some((SeededNoise::new(seed, options), threshold));
+        self
+    }
+
+    /// Carve worm caves where two 3D noises are both within the thickness of zero.
+    pub fn worms(mut self, seed: u32, options: &NoiseOptions, thickness: f64) -> Self {
+        self.worms = Some((
+            SeededNoise::new(seed, options),
+            SeededNoise::new(seed.wrapping_add(1), options),
+            thickness,
+        ));
+        self
+    }
+
+    /// Configure the range of y-coordinates that can be carved, inclusive. Defaults to everything above 0.
+    pub fn depth(mut self, min_height: i32, max_height: i32) -> Self {
+        self.min_height = min_height;
+        self.max_height = max_height;
+        self
+    }
+
+    /// Configure how many voxels under the surface are kept uncarved. Defaults to 0, which lets caves
+    /// open up to the surface and form overhangs.
+    pub fn surface_margin(mut self, margin: i32) -> Self {
+        self.surface_margin = margin;
+        self
+    }
+
+    /// Configure the fluid to fill carved voxels at or below the world's water level. Defaults to air.
+    pub fn fluid(mut self, fluid: u32) -> Self {
+        self.fluid = Some(fluid);
+        self
+    }
+
+    /// Check if a voxel should be carved out.
+    pub fn should_carve(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        if let Some((noise, threshold)) = &self.cheese {
+            if noise.get3d(vx, vy, vz) > *threshold {
+                return true;
+            }
+        }
+
+        if let Some((first, second, thickness)) = &self.worms {
+            if first.get3d(vx, vy, vz).abs() < *thickness
+                && second.get3d(vx, vy, vz).abs() < *thickness
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Default for CaveStage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkStage for CaveStage {
+    fn name(&self) -> String {
+        "Caves".to_owned()
+    }
+
+    fn process(&self, mut chunk: Chunk, resources: Resources, _: Option<Space>) -> Chunk {
+        let registry = resources.registry;
+        let water_level = resources.config.water_level as i32;
+
+        let Vec3(min_x, _, min_z) = chunk.min;
+        let Vec3(max_x, max_y, max_z) = chunk.max;
+
+        let min_y = self.min_height.max(1);
+        let max_y = self.max_height.min(max_y - 1);
+
+        for vx in min_x..max_x {
+            for vz in min_z..max_z {
+                let surface = chunk.get_max_height(vx, vz) as i32 - self.surface_margin;
+
+                // Go top-down so that voxels below existing fluids are never carved, keeping oceans
+                // and lakes from draining into the caves.
+                let mut above_carved = false;
+
+                for vy in (min_y..=max_y.min(surface)).rev() {
+                    let carved = above_carved;
+                    above_carved = false;
+
+                    let id = chunk.get_voxel(vx, vy, vz);
+
+                    if registry.is_air(id) || registry.get_block_by_id(id).is_fluid {
+                        continue;
+                    }
+
+                    let above = chunk.get_voxel(vx, vy + 1, vz);
+                    if !carved && registry.get_block_by_id(above).is_fluid {
+                        continue;
+                    }
+
+                    if !self.should_carve(vx, vy, vz) {
+                        continue;
+                    }
+
+                    above_carved = true;
+
+                    match self.fluid {
+                        Some(fluid) if vy <= water_level => chunk.set_voxel(vx, vy, vz, fluid),
+                        _ => chunk.set_voxel(vx, vy, vz, 0),
+                    };
+                }
+            }
+        }
+
+        chunk
+    }
+}
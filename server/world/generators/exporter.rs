@@ -0,0 +1,396 @@
+use std::{fs, io, path::Path};
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json::json;
+
+use crate::{Chunks, GeometryProtocol, LightUtils, Registry, Space, Vec2, Vec3, VoxelAccess};
+
+use super::mesher::Mesher;
+
+/// Default ambient occlusion table used by the client, from darkest to brightest.
+const AO_TABLE: [f32; 4] = [100.0 / 255.0, 170.0 / 255.0, 210.0 / 255.0, 1.0];
+
+/// Voxel light levels are stored in 4 bits each.
+const MAX_LIGHT: f32 = 15.0;
+
+/// File formats that meshes can be exported to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshFormat {
+    /// A self-contained glTF 2.0 JSON file with an embedded buffer.
+    Gltf,
+
+    /// A Wavefront OBJ file with vertex colors appended to the vertex positions.
+    Obj,
+}
+
+/// A single block geometry and where it sits in the world.
+#[derive(Debug, Clone)]
+struct ExportedGeometry {
+    /// Name of the geometry, the block name with the optional face name.
+    name: String,
+
+    /// The voxel coordinate that the geometry positions are relative to.
+    origin: Vec3<i32>,
+
+    /// The geometry generated by the mesher.
+    geometry: GeometryProtocol,
+}
+
+/// A headless helper to write server-generated meshes into glTF or OBJ files.
+///
+/// Positions are placed in world space, UVs are kept as texture coordinates, and the
+/// per-vertex light and ambient occlusion are kept as vertex colors.
+#[derive(Debug, Clone, Default)]
+pub struct MeshExporter {
+    geometries: Vec<ExportedGeometry>,
+}
+
+impl MeshExporter {
+    /// Create an empty mesh exporter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an exporter with the center chunk of a space meshed. The space needs voxels, lights and height maps.
+    pub fn from_space(space: &Space, registry: &Registry) -> Self {
+        let mut exporter = Self::new();
+
+        let chunk_size = space.options.chunk_size as i32;
+        let Vec2(cx, cz) = space.coords;
+
+        exporter.add_space(
+            &Vec3(cx * chunk_size, 0, cz * chunk_size),
+            &Vec3(
+                (cx + 1) * chunk_size,
+                space.options.max_height as i32,
+                (cz + 1) * chunk_size,
+            ),
+            space,
+            registry,
+        );
+
+        exporter
+    }
+
+    /// Create an exporter with a set of chunks meshed. Chunks that do not exist are skipped.
+    pub fn from_chunks(chunks: &Chunks, coords: &[Vec2<i32>], registry: &Registry) -> Self {
+        let mut exporter = Self::new();
+
+        coords.iter().for_each(|coords| {
+            if let Some(chunk) = chunks.raw(coords) {
+                exporter.add_space(&chunk.min, &chunk.max, chunks, registry);
+            }
+        });
+
+        exporter
+    }
+
+    /// Mesh a region of voxels with `Mesher::mesh_space` and add the results to this exporter.
+    pub fn add_space(
+        &mut self,
+        min: &Vec3<i32>,
+        max: &Vec3<i32>,
+        space: &dyn VoxelAccess,
+        registry: &Registry,
+    ) -> &mut Self {
+        let geometries = Mesher::mesh_space(min, max, space, registry);
+        self.add_geometries(min, geometries, registry)
+    }
+
+    /// Add a list of already generated geometries, with positions relative to `origin`.
+    pub fn add_geometries(
+        &mut self,
+        origin: &Vec3<i32>,
+        geometries: Vec<GeometryProtocol>,
+        registry: &Registry,
+    ) -> &mut Self {
+        geometries.into_iter().for_each(|geometry| {
+            let block_name = if registry.has_type(geometry.voxel) {
+                registry.get_block_by_id(geometry.voxel).name.to_owned()
+            } else {
+                format!("voxel-{}", geometry.voxel)
+            };
+
+            let name = if let Some(face_name) = &geometry.face_name {
+                format!("{}::{}", block_name, face_name)
+            } else {
+                block_name
+            };
+
+            self.geometries.push(ExportedGeometry {
+                name,
+                origin: origin.to_owned(),
+                geometry,
+            });
+        });
+
+        self
+    }
+
+    /// Check if there is nothing to export.
+    pub fn is_empty(&self) -> bool {
+        self.geometries.is_empty()
+    }
+
+    /// Export the geometries into the given format.
+    pub fn export(&self, format: &MeshFormat) -> String {
+        match format {
+            MeshFormat::Gltf => self.to_gltf(),
+            MeshFormat::Obj => self.to_obj(),
+        }
+    }
+
+    /// Export the geometries and write them to a file.
+    pub fn write<P: AsRef<Path>>(&self, path: P, format: &MeshFormat) -> io::Result<()> {
+        fs::write(path, self.export(format))
+    }
+
+    /// Export the geometries as a Wavefront OBJ string. Each geometry becomes its own object, and the
+    /// light and ambient occlusion are baked into a single vertex color after each position.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::from("# Exported by Voxelize\n");
+        let mut offset = 1;
+
+        for ExportedGeometry {
+            name,
+            origin,
+            geometry,
+        } in self.geometries.iter()
+        {
+            let &Vec3(ox, oy, oz) = origin;
+            let vertex_count = geometry.positions.len() / 3;
+
+            obj.push_str(&format!("o {}\n", name.replace(' ', "_")));
+
+            for i in 0..vertex_count {
+                let [r, g, b, _] = Self::shade(geometry.lights.get(i).copied().unwrap_or(0));
+
+                obj.push_str(&format!(
+                    "v {} {} {} {} {} {}\n",
+                    geometry.positions[i * 3] + ox as f32,
+                    geometry.positions[i * 3 + 1] + oy as f32,
+                    geometry.positions[i * 3 + 2] + oz as f32,
+                    r,
+                    g,
+                    b
+                ));
+            }
+
+            for i in 0..vertex_count {
+                obj.push_str(&format!(
+                    "vt {} {}\n",
+                    geometry.uvs.get(i * 2).copied().unwrap_or(0.0),
+                    geometry.uvs.get(i * 2 + 1).copied().unwrap_or(0.0)
+                ));
+            }
+
+            geometry.indices.chunks(3).for_each(|face| {
+                if face.len() < 3 {
+                    return;
+                }
+
+                let a = face[0] as usize + offset;
+                let b = face[1] as usize + offset;
+                let c = face[2] as usize + offset;
+
+                obj.push_str(&format!("f {a}/{a} {b}/{b} {c}/{c}\n"));
+            });
+
+            offset += vertex_count;
+        }
+
+        obj
+    }
+
+    /// Export the geometries as a self-contained glTF 2.0 JSON string. Each geometry becomes a node
+    /// translated to its origin, with `COLOR_0` holding the red, green, blue and sunlight levels and
+    /// `COLOR_1` holding the ambient occlusion. UVs are flipped to glTF's top-left origin.
+    pub fn to_gltf(&self) -> String {
+        let mut buffer: Vec<u8> = vec![];
+        let mut buffer_views = vec![];
+        let mut accessors = vec![];
+        let mut meshes = vec![];
+        let mut nodes = vec![];
+
+        let mut push_view = |buffer: &mut Vec<u8>, bytes: Vec<u8>, target: u32| {
+            let byte_offset = buffer.len();
+            let byte_length = bytes.len();
+            buffer.extend(bytes);
+
+            buffer_views.push(json!({
+                "buffer": 0,
+                "byteOffset": byte_offset,
+                "byteLength": byte_length,
+                "target": target,
+            }));
+
+            buffer_views.len() - 1
+        };
+
+        for ExportedGeometry {
+            name,
+            origin,
+            geometry,
+        } in self.geometries.iter()
+        {
+            let vertex_count = geometry.positions.len() / 3;
+
+            if vertex_count == 0 || geometry.indices.is_empty() {
+                continue;
+            }
+
+            let mut min = [f32::MAX; 3];
+            let mut max = [f32::MIN; 3];
+
+            geometry.positions.chunks(3).for_each(|position| {
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(position[axis]);
+                    max[axis] = max[axis].max(position[axis]);
+                }
+            });
+
+            let uvs = (0..vertex_count)
+                .flat_map(|i| {
+                    [
+                        geometry.uvs.get(i * 2).copied().unwrap_or(0.0),
+                        1.0 - geometry.uvs.get(i * 2 + 1).copied().unwrap_or(0.0),
+                    ]
+                })
+                .collect::<Vec<_>>();
+
+            let (lights, aos): (Vec<_>, Vec<_>) = (0..vertex_count)
+                .map(|i| {
+                    let light = geometry.lights.get(i).copied().unwrap_or(0);
+                    let ao = AO_TABLE[((light >> 16) as usize).min(3)];
+                    (Self::unpack_light(light), [ao, ao, ao])
+                })
+                .unzip();
+
+            let indices = geometry
+                .indices
+                .iter()
+                .map(|&index| index as u32)
+                .collect::<Vec<_>>();
+
+            const ARRAY_BUFFER: u32 = 34962;
+            const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+            const FLOAT: u32 = 5126;
+            const UNSIGNED_INT: u32 = 5125;
+
+            let position_view = push_view(
+                &mut buffer,
+                Self::f32_bytes(&geometry.positions),
+                ARRAY_BUFFER,
+            );
+            let uv_view = push_view(&mut buffer, Self::f32_bytes(&uvs), ARRAY_BUFFER);
+            let light_view =
+                push_view(&mut buffer, Self::f32_bytes(&lights.concat()), ARRAY_BUFFER);
+            let ao_view = push_view(&mut buffer, Self::f32_bytes(&aos.concat()), ARRAY_BUFFER);
+
+            let mut index_bytes = vec![0; indices.len() * 4];
+            LittleEndian::write_u32_into(&indices, &mut index_bytes);
+            let index_view = push_view(&mut buffer, index_bytes, ELEMENT_ARRAY_BUFFER);
+
+            let first = accessors.len();
+
+            accessors.push(json!({
+                "bufferView": position_view,
+                "componentType": FLOAT,
+                "count": vertex_count,
+                "type": "VEC3",
+                "min": min,
+                "max": max,
+            }));
+            accessors.push(json!({
+                "bufferView": uv_view,
+                "componentType": FLOAT,
+                "count": vertex_count,
+                "type": "VEC2",
+            }));
+            accessors.push(json!({
+                "bufferView": light_view,
+                "componentType": FLOAT,
+                "count": vertex_count,
+                "type": "VEC4",
+            }));
+            accessors.push(json!({
+                "bufferView": ao_view,
+                "componentType": FLOAT,
+                "count": vertex_count,
+                "type": "VEC3",
+            }));
+            accessors.push(json!({
+                "bufferView": index_view,
+                "componentType": UNSIGNED_INT,
+                "count": indices.len(),
+                "type": "SCALAR",
+            }));
+
+            meshes.push(json!({
+                "name": name,
+                "primitives": [{
+                    "attributes": {
+                        "POSITION": first,
+                        "TEXCOORD_0": first + 1,
+                        "COLOR_0": first + 2,
+                        "COLOR_1": first + 3,
+                    },
+                    "indices": first + 4,
+                    "mode": 4,
+                }],
+            }));
+
+            nodes.push(json!({
+                "name": name,
+                "mesh": meshes.len() - 1,
+                "translation": [origin.0 as f32, origin.1 as f32, origin.2 as f32],
+            }));
+        }
+
+        // Every buffer view and accessor offset is a multiple of 4, so no padding is needed.
+        let gltf = json!({
+            "asset": { "version": "2.0", "generator": "Voxelize" },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+            "nodes": nodes,
+            "meshes": meshes,
+            "accessors": accessors,
+            "bufferViews": buffer_views,
+            "buffers": [{
+                "byteLength": buffer.len(),
+                "uri": format!(
+                    "data:application/octet-stream;base64,{}",
+                    base64::encode(&buffer)
+                ),
+            }],
+        });
+
+        serde_json::to_string(&gltf).unwrap()
+    }
+
+    /// Unpack a mesher light value into normalized red, green, blue and sunlight levels.
+    fn unpack_light(light: i32) -> [f32; 4] {
+        let light = (light & 0xFFFF) as u32;
+
+        [
+            LightUtils::extract_red_light(light) as f32 / MAX_LIGHT,
+            LightUtils::extract_green_light(light) as f32 / MAX_LIGHT,
+            LightUtils::extract_blue_light(light) as f32 / MAX_LIGHT,
+            LightUtils::extract_sunlight(light) as f32 / MAX_LIGHT,
+        ]
+    }
+
+    /// Bake a mesher light value and its ambient occlusion into a single color.
+    fn shade(light: i32) -> [f32; 4] {
+        let [r, g, b, s] = Self::unpack_light(light);
+        let ao = AO_TABLE[((light >> 16) as usize).min(3)];
+
+        [r.max(s) * ao, g.max(s) * ao, b.max(s) * ao, 1.0]
+    }
+
+    fn f32_bytes(data: &[f32]) -> Vec<u8> {
+        let mut bytes = vec![0; data.len() * 4];
+        LittleEndian::write_f32_into(data, &mut bytes);
+        bytes
+    }
+}
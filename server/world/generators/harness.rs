@@ -0,0 +1,273 @@
+use std::{collections::BTreeMap, env, fs, io, path::Path, sync::Arc, thread};
+
+use crate::{
+    Chunk, ChunkInterests, ChunkOptions, ChunkStatus, ChunkUtils, Chunks, Registry, Vec2,
+    VoxelAccess, VoxelUpdate, WorldConfig,
+};
+
+use super::{
+    mesher::Mesher,
+    pipeline::{merge_stages, ChunkStage, Pipeline},
+};
+
+/// Environment variable that makes `WorldGenHarness::check_golden` rewrite the golden file instead
+/// of comparing against it.
+pub const UPDATE_GOLDEN_ENV: &str = "VOXELIZE_UPDATE_GOLDEN";
+
+/// A headless runner for chunk stages that generates a region of chunks without a server.
+///
+/// The chunks go through a `Pipeline` with the same scheduling as `ChunkGeneratingSystem`: the same
+/// thread pool, neighbor checks and handling of changes that spill out of a chunk. Only the lighting
+/// and meshing are skipped. The result only depends on the seed, the registry and the stages, so it
+/// can be hashed and compared against golden hashes to catch changes that would break existing saved
+/// worlds.
+pub struct WorldGenHarness {
+    /// The configuration of the world to generate.
+    pub config: WorldConfig,
+
+    /// The registry of blocks used by the stages.
+    pub registry: Registry,
+
+    /// The chunk stages, in order.
+    pub stages: Vec<Arc<dyn ChunkStage + Send + Sync>>,
+
+    /// Number of threads in the pipeline's thread pool.
+    threads: usize,
+
+    /// Number of extra chunks generated around the region so that spilled changes and neighbor
+    /// reads match the real world. Computed from the stages if not set, as the padding chunks need
+    /// neighbors of their own to get through the stages that read them.
+    padding: Option<usize>,
+}
+
+impl WorldGenHarness {
+    /// Create a harness without any stages.
+    pub fn new(config: &WorldConfig, registry: &Registry) -> Self {
+        Self {
+            config: config.to_owned(),
+            registry: registry.to_owned(),
+            stages: vec![],
+            threads: 1,
+            padding: None,
+        }
+    }
+
+    /// Create a harness that runs the stages of a pipeline.
+    pub fn from_pipeline(pipeline: &Pipeline, config: &WorldConfig, registry: &Registry) -> Self {
+        let mut harness = Self::new(config, registry);
+        harness.stages = pipeline.stages.to_owned();
+        harness
+    }
+
+    /// Add a stage to the harness.
+    pub fn add_stage<T>(&mut self, stage: T)
+    where
+        T: 'static + ChunkStage + Send + Sync,
+    {
+        self.stages.push(Arc::new(stage));
+    }
+
+    /// Set the number of threads to process chunks with.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Set the number of extra chunks generated around the requested region.
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = Some(padding);
+    }
+
+    /// Merge the stages the same way `World` does before it starts.
+    pub fn merge_stages(&mut self) {
+        self.stages = merge_stages(&self.stages);
+    }
+
+    /// Generate every chunk from `min` to `max` inclusive, sorted by x then z.
+    pub fn generate(&self, min: &Vec2<i32>, max: &Vec2<i32>) -> Vec<Chunk> {
+        let mut config = self.config.to_owned();
+        config.saving = false;
+
+        let padding = self.padding.unwrap_or_else(|| {
+            1 + self
+                .stages
+                .iter()
+                .map(|stage| {
+                    (stage.neighbors(&config) as f32 / config.chunk_size as f32).ceil() as usize
+                })
+                .sum::<usize>()
+        }) as i32;
+
+        let mut pipeline = Pipeline::new();
+        pipeline.set_threads(self.threads);
+        pipeline.stages = self.stages.to_owned();
+
+        let mut mesher = Mesher::new();
+        let interests = ChunkInterests::new();
+
+        let options = ChunkOptions {
+            size: config.chunk_size,
+            max_height: config.max_height,
+            sub_chunks: config.sub_chunks,
+        };
+
+        let mut chunks = Chunks::new(&config);
+
+        for cx in (min.0 - padding)..=(max.0 + padding) {
+            for cz in (min.1 - padding)..=(max.1 + padding) {
+                let name = ChunkUtils::get_chunk_name(cx, cz);
+                let mut chunk = Chunk::new(&name, cx, cz, &options);
+
+                if self.stages.is_empty() {
+                    chunk.status = ChunkStatus::Ready;
+                } else {
+                    pipeline.add_chunk(&chunk.coords, false);
+                }
+
+                chunks.renew(chunk);
+            }
+        }
+
+        // The outermost padding chunks wait forever on neighbors that don't exist, so the pipeline goes
+        // idle once every chunk that can finish has.
+        loop {
+            let advanced = pipeline.advance(&mut chunks, &mut mesher, &config);
+
+            // Chunks done with every stage would be lit and meshed next. The harness only needs their
+            // voxels, so they are ready right away.
+            while let Some(coords) = mesher.get() {
+                if let Some(chunk) = chunks.raw_mut(&coords) {
+                    chunk.status = ChunkStatus::Ready;
+                }
+
+                pipeline.notify_listeners(&mut chunks, &mut mesher, &coords);
+            }
+
+            pipeline.dispatch(
+                &mut chunks,
+                &mut mesher,
+                &interests,
+                &self.registry,
+                &config,
+            );
+
+            if pipeline.is_idle() {
+                break;
+            }
+
+            if !advanced {
+                thread::yield_now();
+            }
+        }
+
+        // Changes that spilled into a chunk are applied when the chunk is meshed, either from the
+        // pipeline's leftovers or as voxel updates if it was already done.
+        let coords: Vec<Vec2<i32>> = chunks.map.keys().cloned().collect();
+        coords.iter().for_each(|coords| {
+            pipeline.apply_leftovers(&mut chunks, &self.registry, coords);
+        });
+
+        let updates: Vec<VoxelUpdate> = chunks.updates.drain(..).collect();
+        updates.into_iter().for_each(|(voxel, val)| {
+            chunks.set_raw_voxel(voxel.0, voxel.1, voxel.2, val);
+        });
+
+        let mut region: Vec<Chunk> = chunks
+            .map
+            .into_values()
+            .filter(|chunk| {
+                let Vec2(cx, cz) = chunk.coords;
+                cx >= min.0 && cx <= max.0 && cz >= min.1 && cz <= max.1
+            })
+            .map(|mut chunk| {
+                chunk.extra_changes.clear();
+                chunk.calculate_max_height(&self.registry);
+                chunk
+            })
+            .collect();
+
+        region.sort_by_key(|chunk| (chunk.coords.0, chunk.coords.1));
+
+        region
+    }
+
+    /// Hash the voxels of a chunk with 64-bit FNV-1a. Stable across platforms and runs.
+    pub fn hash_chunk(chunk: &Chunk) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+        for voxel in chunk.voxels.data.iter() {
+            for byte in voxel.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+
+        hash
+    }
+
+    /// Generate a region and hash each chunk, keyed by the chunk name.
+    pub fn hash_region(&self, min: &Vec2<i32>, max: &Vec2<i32>) -> BTreeMap<String, u64> {
+        self.generate(min, max)
+            .iter()
+            .map(|chunk| (chunk.name.to_owned(), Self::hash_chunk(chunk)))
+            .collect()
+    }
+
+    /// Compare the hashes of a region against a golden file, returning the names of the chunks that
+    /// differ. The golden file is written instead if `VOXELIZE_UPDATE_GOLDEN` is set, and a missing
+    /// golden file is an error otherwise.
+    pub fn check_golden<P: AsRef<Path>>(
+        &self,
+        path: P,
+        min: &Vec2<i32>,
+        max: &Vec2<i32>,
+    ) -> io::Result<Vec<String>> {
+        let path = path.as_ref();
+
+        let hashes: BTreeMap<String, String> = self
+            .hash_region(min, max)
+            .into_iter()
+            .map(|(name, hash)| (name, format!("{hash:016x}")))
+            .collect();
+
+        if env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let json = serde_json::to_string_pretty(&hashes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            fs::write(path, json + "\n")?;
+
+            return Ok(vec![]);
+        }
+
+        if !path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Golden file not found: {}, run with {} set to create it",
+                    path.display(),
+                    UPDATE_GOLDEN_ENV
+                ),
+            ));
+        }
+
+        let golden: BTreeMap<String, String> = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut mismatches: Vec<String> = hashes
+            .iter()
+            .filter(|(name, hash)| golden.get(*name) != Some(*hash))
+            .map(|(name, _)| name.to_owned())
+            .collect();
+
+        mismatches.extend(
+            golden
+                .keys()
+                .filter(|name| !hashes.contains_key(*name))
+                .cloned(),
+        );
+
+        Ok(mismatches)
+    }
+}
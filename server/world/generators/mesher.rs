@@ -101,6 +101,11 @@ impl Mesher {
         self.queue.retain(|c| c != coords);
     }
 
+    /// Whether no chunk is being meshed at the moment.
+    pub fn is_idle(&self) -> bool {
+        self.map.is_empty()
+    }
+
     /// Pop the first chunk coordinate in the queue.
     pub fn get(&mut self) -> Option<Vec2<i32>> {
         self.queue.pop_front()
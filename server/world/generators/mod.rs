@@ -1,17 +1,34 @@
+mod caves;
+mod exporter;
+mod harness;
 mod lights;
 mod lsystem;
 mod mesher;
 mod noise;
+mod noise_graph;
+mod ores;
 mod pipeline;
+mod pregen;
+mod rng;
 mod spline;
+mod structures;
 mod terrain;
 mod trees;
+mod worldgen;
 
 pub use self::noise::*;
+pub use caves::CaveStage;
+pub use exporter::{MeshExporter, MeshFormat};
+pub use harness::{WorldGenHarness, UPDATE_GOLDEN_ENV};
 pub use lights::{LightNode, Lights};
 pub use lsystem::*;
 pub use mesher::Mesher;
+pub use noise_graph::{NoiseGraph, NoiseNode};
+pub use ores::*;
 pub use pipeline::*;
+pub use pregen::Pregenerator;
 pub use spline::SplineMap;
+pub use structures::*;
 pub use terrain::*;
 pub use trees::*;
+pub use worldgen::*;
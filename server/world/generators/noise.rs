@@ -1,5 +1,5 @@
 use noise::{Fbm, HybridMulti, MultiFractal, NoiseFn, Perlin, RidgedMulti, Seedable};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use splines::interpolate::Interpolator;
 use std::f64;
 
@@ -37,20 +37,30 @@ impl SeededNoise {
     /// Get the 2D multi-fractal value at voxel column with noise options.
     /// Noise values are attempted to be scaled to -1.0 to 1.0, but noise options may change that.
     pub fn get2d(&self, vx: i32, vz: i32) -> f64 {
-        if self.options.ridged {
-            self.ridged.get([vx as f64, vz as f64])
-        } else {
-            self.regular.get([vx as f64, vz as f64])
-        }
+        self.sample2d(vx as f64, vz as f64)
     }
 
     /// Get the 3D multi-fractal value at voxel column with noise options.
     /// Noise values are attempted to be scaled to -1.0 to 1.0, but noise options may change that.
     pub fn get3d(&self, vx: i32, vy: i32, vz: i32) -> f64 {
+        self.sample3d(vx as f64, vy as f64, vz as f64)
+    }
+
+    /// Get the 2D multi-fractal value at a non-integer point, used by warped noise graphs.
+    pub(crate) fn sample2d(&self, x: f64, z: f64) -> f64 {
+        if self.options.ridged {
+            self.ridged.get([x, z])
+        } else {
+            self.regular.get([x, z])
+        }
+    }
+
+    /// Get the 3D multi-fractal value at a non-integer point, used by warped noise graphs.
+    pub(crate) fn sample3d(&self, x: f64, y: f64, z: f64) -> f64 {
         if self.options.ridged {
-            self.ridged.get([vx as f64, vy as f64, vz as f64])
+            self.ridged.get([x, y, z])
         } else {
-            self.regular.get([vx as f64, vy as f64, vz as f64])
+            self.regular.get([x, y, z])
         }
     }
 
@@ -62,8 +72,9 @@ impl SeededNoise {
     }
 }
 
-/// Multi-fractal noise options.
-#[derive(Clone, Default, Serialize, Debug)]
+/// Multi-fractal noise options. Missing fields are deserialized with the builder defaults.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+#[serde(default = "default_noise_options")]
 pub struct NoiseOptions {
     pub seed: u32,
 
@@ -96,6 +107,10 @@ const DEFAULT_OCTAVES: usize = 6;
 const DEFAULT_PERSISTENCE: f64 = 1.0;
 const DEFAULT_RIDGED: bool = false;
 
+fn default_noise_options() -> NoiseOptions {
+    NoiseOptions::new().build()
+}
+
 impl NoiseOptions {
     pub fn new() -> NoiseOptionsBuilder {
         NoiseOptionsBuilder {
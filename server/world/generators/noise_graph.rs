@@ -0,0 +1,432 @@
+use noise::{NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
+
+use super::noise::{NoiseOptions, SeededNoise};
+
+/// A node of a noise graph. Nodes are plain data so that graphs can be described in code or in a
+/// world generation file, and are compiled into a seeded `NoiseGraph` to be sampled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NoiseNode {
+    /// The multi-fractal noise of `SeededNoise`, ridged if `options.ridged` is true.
+    Fractal { options: NoiseOptions },
+
+    /// Billowy multi-fractal Perlin noise, good for clouds and rolling hills. Ignores `options.ridged`.
+    Billow { options: NoiseOptions },
+
+    /// Worley, or cellular, noise. Returns the distance to the nearest cell point if `distance` is
+    /// true, otherwise a random value per cell. Both range from -1.0 to 1.0.
+    Worley {
+        frequency: f64,
+        #[serde(default)]
+        distance: bool,
+    },
+
+    /// A constant value.
+    Constant { value: f64 },
+
+    /// The sum of two nodes.
+    Add {
+        a: Box<NoiseNode>,
+        b: Box<NoiseNode>,
+    },
+
+    /// The product of two nodes.
+    Multiply {
+        a: Box<NoiseNode>,
+        b: Box<NoiseNode>,
+    },
+
+    /// A node multiplied by `scale` and then added with `bias`.
+    ScaleBias {
+        source: Box<NoiseNode>,
+        scale: f64,
+        bias: f64,
+    },
+
+    /// A node clamped between `min` and `max`.
+    Clamp {
+        source: Box<NoiseNode>,
+        min: f64,
+        max: f64,
+    },
+
+    /// Selects `b` where `control` is above `threshold` and `a` elsewhere, linearly blending the two
+    /// within `falloff` of the threshold.
+    Select {
+        control: Box<NoiseNode>,
+        a: Box<NoiseNode>,
+        b: Box<NoiseNode>,
+        threshold: f64,
+        #[serde(default)]
+        falloff: f64,
+    },
+
+    /// Samples `source` at coordinates displaced by the `warp` node times `strength`, with each axis
+    /// displaced by a differently seeded copy of `warp`.
+    DomainWarp {
+        source: Box<NoiseNode>,
+        warp: Box<NoiseNode>,
+        strength: f64,
+    },
+}
+
+impl NoiseNode {
+    /// Create a fractal node from noise options, which samples the same as `SeededNoise`.
+    pub fn fractal(options: &NoiseOptions) -> Self {
+        Self::Fractal {
+            options: options.to_owned(),
+        }
+    }
+}
+
+/// A compiled and seeded node of a noise graph.
+#[derive(Clone, Debug)]
+enum SeededNode {
+    Fractal(SeededNoise),
+    Billow(Vec<Perlin>, NoiseOptions),
+    Worley {
+        seed: u32,
+        frequency: f64,
+        distance: bool,
+    },
+    Constant(f64),
+    Add(Box<SeededNode>, Box<SeededNode>),
+    Multiply(Box<SeededNode>, Box<SeededNode>),
+    ScaleBias(Box<SeededNode>, f64, f64),
+    Clamp(Box<SeededNode>, f64, f64),
+    Select {
+        control: Box<SeededNode>,
+        a: Box<SeededNode>,
+        b: Box<SeededNode>,
+        threshold: f64,
+        falloff: f64,
+    },
+    DomainWarp {
+        source: Box<SeededNode>,
+        warps: [Box<SeededNode>; 3],
+        strength: f64,
+    },
+}
+
+/// A seeded noise graph compiled from a `NoiseNode`, sampled in 2D or 3D.
+///
+/// Fractal nodes are seeded with `seed + options.seed`, exactly like `SeededNoise::set_seed`, so
+/// a graph of a single fractal node samples the same values as the `SeededNoise` it replaces. Every
+/// other node gets its own seed derived from its position in the graph.
+#[derive(Clone, Debug)]
+pub struct NoiseGraph {
+    /// The node this graph was compiled from.
+    pub node: NoiseNode,
+
+    root: SeededNode,
+}
+
+impl NoiseGraph {
+    /// Compile a noise node with a seed, usually `WorldConfig::seed`.
+    pub fn new(seed: u32, node: &NoiseNode) -> Self {
+        let mut counter = 0;
+
+        Self {
+            node: node.to_owned(),
+            root: Self::compile(seed, node, &mut counter),
+        }
+    }
+
+    /// Recompile this graph with a new seed.
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        let mut counter = 0;
+        self.root = Self::compile(seed, &self.node, &mut counter);
+        self
+    }
+
+    /// Get the 2D noise value at a voxel column.
+    pub fn get2d(&self, vx: i32, vz: i32) -> f64 {
+        Self::sample2d(&self.root, vx as f64, vz as f64)
+    }
+
+    /// Get the 3D noise value at a voxel.
+    pub fn get3d(&self, vx: i32, vy: i32, vz: i32) -> f64 {
+        Self::sample3d(&self.root, vx as f64, vy as f64, vz as f64)
+    }
+
+    fn compile(seed: u32, node: &NoiseNode, counter: &mut u32) -> SeededNode {
+        *counter += 1;
+        let node_seed = seed.wrapping_add(counter.wrapping_mul(7919));
+
+        let mut compile = |node: &NoiseNode| Box::new(Self::compile(seed, node, counter));
+
+        match node {
+            NoiseNode::Fractal { options } => {
+                // Offset by the node's index so that identical fractals in a graph differ, with the root
+                // keeping the plain seed so that a single fractal matches `SeededNoise`.
+                let fractal_seed = seed.wrapping_add((*counter - 1).wrapping_mul(7919));
+
+                let mut noise = SeededNoise::new(fractal_seed, options);
+                noise.set_seed(fractal_seed);
+                SeededNode::Fractal(noise)
+            }
+            NoiseNode::Billow { options } => SeededNode::Billow(
+                (0..options.octaves.max(1) as u32)
+                    .map(|octave| {
+                        Perlin::new(node_seed.wrapping_add(options.seed).wrapping_add(octave))
+                    })
+                    .collect(),
+                options.to_owned(),
+            ),
+            NoiseNode::Worley {
+                frequency,
+                distance,
+            } => SeededNode::Worley {
+                seed: node_seed,
+                frequency: *frequency,
+                distance: *distance,
+            },
+            NoiseNode::Constant { value } => SeededNode::Constant(*value),
+            NoiseNode::Add { a, b } => SeededNode::Add(compile(a), compile(b)),
+            NoiseNode::Multiply { a, b } => SeededNode::Multiply(compile(a), compile(b)),
+            NoiseNode::ScaleBias {
+                source,
+                scale,
+                bias,
+            } => SeededNode::ScaleBias(compile(source), *scale, *bias),
+            NoiseNode::Clamp { source, min, max } => SeededNode::Clamp(compile(source), *min, *max),
+            NoiseNode::Select {
+                control,
+                a,
+                b,
+                threshold,
+                falloff,
+            } => SeededNode::Select {
+                control: compile(control),
+                a: compile(a),
+                b: compile(b),
+                threshold: *threshold,
+                falloff: *falloff,
+            },
+            NoiseNode::DomainWarp {
+                source,
+                warp,
+                strength,
+            } => SeededNode::DomainWarp {
+                source: compile(source),
+                warps: [0, 1, 2].map(|axis: u32| {
+                    Box::new(Self::compile(
+                        seed.wrapping_add(axis.wrapping_mul(104_729)),
+                        warp,
+                        counter,
+                    ))
+                }),
+                strength: *strength,
+            },
+        }
+    }
+
+    fn sample2d(node: &SeededNode, x: f64, z: f64) -> f64 {
+        match node {
+            SeededNode::Fractal(noise) => noise.sample2d(x, z),
+            SeededNode::Billow(octaves, options) => billow(octaves, options, &[x, z]),
+            SeededNode::Worley {
+                seed,
+                frequency,
+                distance,
+            } => worley(*seed, &[x * frequency, z * frequency], *distance),
+            SeededNode::Constant(value) => *value,
+            SeededNode::Add(a, b) => Self::sample2d(a, x, z) + Self::sample2d(b, x, z),
+            SeededNode::Multiply(a, b) => Self::sample2d(a, x, z) * Self::sample2d(b, x, z),
+            SeededNode::ScaleBias(source, scale, bias) => {
+                Self::sample2d(source, x, z) * scale + bias
+            }
+            SeededNode::Clamp(source, min, max) => Self::sample2d(source, x, z).clamp(*min, *max),
+            SeededNode::Select {
+                control,
+                a,
+                b,
+                threshold,
+                falloff,
+            } => select(
+                Self::sample2d(control, x, z),
+                *threshold,
+                *falloff,
+                || Self::sample2d(a, x, z),
+                || Self::sample2d(b, x, z),
+            ),
+            SeededNode::DomainWarp {
+                source,
+                warps,
+                strength,
+            } => Self::sample2d(
+                source,
+                x + Self::sample2d(&warps[0], x, z) * strength,
+                z + Self::sample2d(&warps[2], x, z) * strength,
+            ),
+        }
+    }
+
+    fn sample3d(node: &SeededNode, x: f64, y: f64, z: f64) -> f64 {
+        match node {
+            SeededNode::Fractal(noise) => noise.sample3d(x, y, z),
+            SeededNode::Billow(octaves, options) => billow(octaves, options, &[x, y, z]),
+            SeededNode::Worley {
+                seed,
+                frequency,
+                distance,
+            } => worley(
+                *seed,
+                &[x * frequency, y * frequency, z * frequency],
+                *distance,
+            ),
+            SeededNode::Constant(value) => *value,
+            SeededNode::Add(a, b) => Self::sample3d(a, x, y, z) + Self::sample3d(b, x, y, z),
+            SeededNode::Multiply(a, b) => Self::sample3d(a, x, y, z) * Self::sample3d(b, x, y, z),
+            SeededNode::ScaleBias(source, scale, bias) => {
+                Self::sample3d(source, x, y, z) * scale + bias
+            }
+            SeededNode::Clamp(source, min, max) => {
+                Self::sample3d(source, x, y, z).clamp(*min, *max)
+            }
+            SeededNode::Select {
+                control,
+                a,
+                b,
+                threshold,
+                falloff,
+            } => select(
+                Self::sample3d(control, x, y, z),
+                *threshold,
+                *falloff,
+                || Self::sample3d(a, x, y, z),
+                || Self::sample3d(b, x, y, z),
+            ),
+            SeededNode::DomainWarp {
+                source,
+                warps,
+                strength,
+            } => Self::sample3d(
+                source,
+                x + Self::sample3d(&warps[0], x, y, z) * strength,
+                y + Self::sample3d(&warps[1], x, y, z) * strength,
+                z + Self::sample3d(&warps[2], x, y, z) * strength,
+            ),
+        }
+    }
+}
+
+/// Blend between two values by a control value, only sampling the values that are needed.
+fn select<A: Fn() -> f64, B: Fn() -> f64>(
+    control: f64,
+    threshold: f64,
+    falloff: f64,
+    a: A,
+    b: B,
+) -> f64 {
+    if falloff <= 0.0 {
+        return if control > threshold { b() } else { a() };
+    }
+
+    if control < threshold - falloff {
+        return a();
+    }
+
+    if control > threshold + falloff {
+        return b();
+    }
+
+    let alpha = (control - threshold + falloff) / (2.0 * falloff);
+    a() * (1.0 - alpha) + b() * alpha
+}
+
+/// Sample billowy noise, the sum of the absolute values of each Perlin octave, normalized to -1.0 to 1.0.
+fn billow(octaves: &[Perlin], options: &NoiseOptions, point: &[f64]) -> f64 {
+    let mut frequency = options.frequency;
+    let mut amplitude = 1.0;
+    let mut total = 0.0;
+    let mut max = 0.0;
+
+    for perlin in octaves {
+        let value = if point.len() == 2 {
+            perlin.get([point[0] * frequency, point[1] * frequency])
+        } else {
+            perlin.get([
+                point[0] * frequency,
+                point[1] * frequency,
+                point[2] * frequency,
+            ])
+        };
+
+        total += (value.abs() * 2.0 - 1.0) * amplitude;
+        max += amplitude;
+
+        frequency *= options.lacunarity;
+        amplitude *= options.persistence;
+    }
+
+    if max == 0.0 {
+        return 0.0;
+    }
+
+    total / max
+}
+
+/// Hash a seed and a cell into a 64-bit value.
+fn hash_cell(seed: u32, cell: &[i64]) -> u64 {
+    let mut hash = (seed as u64) ^ 0x9E37_79B9_7F4A_7C15;
+
+    cell.iter().for_each(|&value| {
+        hash = (hash ^ value as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash ^= hash >> 31;
+    });
+
+    hash
+}
+
+/// Map the bits of a hash into 0.0 to 1.0.
+fn unit(hash: u64, shift: u32) -> f64 {
+    ((hash >> shift) & 0xFFFF) as f64 / 65535.0
+}
+
+/// Sample Worley noise at a 2D or 3D point, checking one point per neighboring cell. The cells are kept on
+/// the stack, as this runs for every sample.
+fn worley(seed: u32, point: &[f64], distance: bool) -> f64 {
+    let dims = point.len();
+    debug_assert!(dims <= 3, "Worley noise is 2D or 3D");
+
+    let mut base = [0i64; 3];
+    for (base, value) in base.iter_mut().zip(point) {
+        *base = value.floor() as i64;
+    }
+
+    let mut nearest = f64::MAX;
+    let mut nearest_hash = 0;
+
+    let neighbors = 3usize.pow(dims as u32);
+    let mut cell = [0i64; 3];
+
+    for index in 0..neighbors {
+        let mut rest = index;
+        for d in 0..dims {
+            cell[d] = base[d] + (rest % 3) as i64 - 1;
+            rest /= 3;
+        }
+
+        let hash = hash_cell(seed, &cell[..dims]);
+
+        let mut dist = 0.0;
+        for d in 0..dims {
+            let feature = cell[d] as f64 + unit(hash, d as u32 * 16);
+            dist += (feature - point[d]).powi(2);
+        }
+
+        if dist < nearest {
+            nearest = dist;
+            nearest_hash = hash;
+        }
+    }
+
+    if distance {
+        // The nearest point is at most sqrt(dims) away, mostly within one cell.
+        (nearest.sqrt() * 2.0 - 1.0).clamp(-1.0, 1.0)
+    } else {
+        unit(nearest_hash, 48) * 2.0 - 1.0
+    }
+}
@@ -0,0 +1,247 @@
+use std::io;
+
+use crate::{Chunk, ChunkStage, Registry, Resources, Space, Terrain, Vec3, VoxelAccess};
+
+use super::rng::seeded_rng;
+
+/// A vein of ore to be scattered by an `OreStage`.
+#[derive(Debug, Clone)]
+pub struct Ore {
+    /// Name of the block that this ore is made of.
+    pub block: String,
+
+    /// The lowest y-coordinate a vein can start at, inclusive.
+    pub min_height: i32,
+
+    /// The highest y-coordinate a vein can start at, inclusive.
+    pub max_height: i32,
+
+    /// Number of voxels each vein tries to place.
+    pub size: usize,
+
+    /// Average number of veins per chunk. Fractions are rounded randomly.
+    pub frequency: f64,
+
+    /// Names of the blocks that the ore can replace. Empty means any non-air, non-fluid block.
+    pub replaceable: Vec<String>,
+
+    /// Names of biomes this ore can be placed in. Empty means any biome.
+    pub biomes: Vec<String>,
+}
+
+impl Ore {
+    /// Create an ore builder with the name of the ore block.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(block: &str) -> OreBuilder {
+        OreBuilder::new(block)
+    }
+}
+
+/// Builder for an ore.
+pub struct OreBuilder {
+    block: String,
+    min_height: i32,
+    max_height: i32,
+    size: usize,
+    frequency: f64,
+    replaceable: Vec<String>,
+    biomes: Vec<String>,
+}
+
+impl OreBuilder {
+    /// Create an ore builder with default values.
+    pub fn new(block: &str) -> Self {
+        Self {
+            block: block.to_owned(),
+            min_height: 0,
+            max_height: i32::MAX,
+            size: 8,
+            frequency: 1.0,
+            replaceable: vec![],
+            biomes: vec![],
+        }
+    }
+
+    /// Configure the range of y-coordinates veins can start at, inclusive. Defaults to any height.
+    pub fn height_range(mut self, min_height: i32, max_height: i32) -> Self {
+        self.min_height = min_height;
+        self.max_height = max_height;
+        self
+    }
+
+    /// Configure the number of voxels each vein tries to place. Defaults to 8.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Configure the average number of veins per chunk. Defaults to 1.0.
+    pub fn frequency(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Configure the blocks the ore can replace. Defaults to any non-air, non-fluid block.
+    pub fn replaceable(mut self, replaceable: &[&str]) -> Self {
+        self.replaceable = replaceable.iter().map(|name| (*name).to_owned()).collect();
+        self
+    }
+
+    /// Configure the biomes this ore can be placed in. Defaults to any biome.
+    pub fn biomes(mut self, biomes: &[&str]) -> Self {
+        self.biomes = biomes.iter().map(|name| (*name).to_owned()).collect();
+        self
+    }
+
+    /// Create an ore instance.
+    pub fn build(self) -> Ore {
+        Ore {
+            block: self.block,
+            min_height: self.min_height,
+            max_height: self.max_height,
+            size: self.size,
+            frequency: self.frequency,
+            replaceable: self.replaceable,
+            biomes: self.biomes,
+        }
+    }
+}
+
+/// A chunk stage that scatters ore veins. Veins are deterministic from `WorldConfig::seed` and the
+/// chunk coordinates, so a regenerated chunk always gets the same ores. Veins are random walks that
+/// stay within the chunk they started in.
+#[derive(Default)]
+pub struct OreStage {
+    terrain: Option<Terrain>,
+
+    /// The ores with the IDs of their block and of the blocks they can replace.
+    ores: Vec<(Ore, u32, Vec<u32>)>,
+}
+
+impl OreStage {
+    /// Create a new ore stage without any ores.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the terrain used to filter ores by their biomes. Needs to be set before adding ores with biomes.
+    pub fn terrain(mut self, terrain: Terrain) -> Self {
+        self.terrain = Some(terrain);
+        self
+    }
+
+    /// Add an ore to be scattered by this stage, resolving its blocks in the registry. Errors if the ore or
+    /// any of its replaceable blocks are not in the registry, or if the ore has biomes but the stage has no
+    /// terrain to look them up in.
+    pub fn add_ore(mut self, ore: Ore, registry: &Registry) -> io::Result<Self> {
+        let block_id = |name: &str| {
+            registry
+                .blocks_by_name
+                .get(&name.to_lowercase())
+                .map(|block| block.id)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Ore block not found in registry: {}", name),
+                    )
+                })
+        };
+
+        if !ore.biomes.is_empty() && self.terrain.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Ore {} has biomes but the ore stage has no terrain to look them up in.",
+                    ore.block
+                ),
+            ));
+        }
+
+        let id = block_id(&ore.block)?;
+        let replaceable = ore
+            .replaceable
+            .iter()
+            .map(|name| block_id(name))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        self.ores.push((ore, id, replaceable));
+        Ok(self)
+    }
+}
+
+impl ChunkStage for OreStage {
+    fn name(&self) -> String {
+        "Ores".to_owned()
+    }
+
+    fn process(&self, mut chunk: Chunk, resources: Resources, _: Option<Space>) -> Chunk {
+        let registry = resources.registry;
+
+        let Vec3(min_x, _, min_z) = chunk.min;
+        let Vec3(max_x, max_y, max_z) = chunk.max;
+
+        for (index, (ore, id, replaceable)) in self.ores.iter().enumerate() {
+            let min_height = ore.min_height.max(0);
+            let max_height = ore.max_height.min(max_y - 1);
+
+            if min_height > max_height {
+                continue;
+            }
+
+            let rng = seeded_rng(
+                resources.config.seed,
+                &[
+                    index as u64,
+                    chunk.coords.0 as u32 as u64,
+                    chunk.coords.1 as u32 as u64,
+                ],
+            );
+
+            let mut count = ore.frequency.floor() as usize;
+            if rng.f64() < ore.frequency.fract() {
+                count += 1;
+            }
+
+            for _ in 0..count {
+                let mut vx = rng.i32(min_x..max_x);
+                let mut vy = rng.i32(min_height..=max_height);
+                let mut vz = rng.i32(min_z..max_z);
+
+                if let Some(terrain) = &self.terrain {
+                    if !ore.biomes.is_empty()
+                        && !ore.biomes.contains(&terrain.get_biome_at(vx, vz).name)
+                    {
+                        continue;
+                    }
+                }
+
+                for _ in 0..ore.size {
+                    if chunk.contains(vx, vy, vz) {
+                        let current = chunk.get_voxel(vx, vy, vz);
+
+                        let can_replace = if replaceable.is_empty() {
+                            !registry.is_air(current) && !registry.get_block_by_id(current).is_fluid
+                        } else {
+                            replaceable.contains(&current)
+                        };
+
+                        if can_replace {
+                            chunk.set_voxel(vx, vy, vz, *id);
+                        }
+                    }
+
+                    match rng.u8(0..6) {
+                        0 => vx += 1,
+                        1 => vx -= 1,
+                        2 => vy += 1,
+                        3 => vy -= 1,
+                        4 => vz += 1,
+                        _ => vz -= 1,
+                    }
+                }
+            }
+        }
+
+        chunk
+    }
+}
@@ -1,15 +1,18 @@
-use std::{collections::VecDeque, sync::Arc};
+use std::{collections::VecDeque, io, sync::Arc};
 
 use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
 use hashbrown::{HashMap, HashSet};
+use nanoid::nanoid;
 use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use crate::{
-    Chunk, ChunkStatus, Registry, Space, SpaceData, Terrain, Vec2, Vec3, VoxelAccess, VoxelUpdate,
-    WorldConfig,
+    BlockUtils, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, ChunkUtils, Chunks, Registry,
+    Space, SpaceData, Terrain, Vec2, Vec3, VoxelAccess, VoxelUpdate, WorldConfig,
 };
 
+use super::mesher::Mesher;
+
 #[derive(Clone)]
 pub struct Resources<'a> {
     pub registry: &'a Registry,
@@ -192,7 +195,8 @@ impl ChunkStage for BaseTerrainStage {
                 let (bias, offset) = self.terrain.get_bias_offset(vx, vz);
 
                 for vy in min_y..max_y {
-                    let density = self.terrain.get_density_from_bias_offset(bias, offset, vy);
+                    let density = self.terrain.get_density_from_bias_offset(bias, offset, vy)
+                        + self.terrain.get_3d_density(vx, vy, vz);
 
                     if density > self.threshold {
                         chunk.set_voxel(vx, vy, vz, self.base);
@@ -205,6 +209,105 @@ impl ChunkStage for BaseTerrainStage {
     }
 }
 
+/// A chunk stage to apply the surface and subsurface blocks of each column's biome, picked with
+/// `Terrain::get_surface_biome_at` so that materials blend across biome borders. Columns under fluids
+/// have their surface rules applied to the ground beneath the fluid.
+pub struct BiomeSurfaceStage {
+    terrain: Terrain,
+
+    /// The resolved surface and subsurface block IDs of each biome, keyed by biome name.
+    blocks: HashMap<String, (Option<u32>, Option<u32>)>,
+}
+
+impl BiomeSurfaceStage {
+    /// Create a biome surface stage, resolving the surface and subsurface block names of every biome
+    /// in the terrain. Errors if any of them are not in the registry.
+    pub fn new(terrain: Terrain, registry: &Registry) -> io::Result<Self> {
+        let resolve = |name: &Option<String>| -> io::Result<Option<u32>> {
+            match name {
+                Some(name) => registry
+                    .blocks_by_name
+                    .get(&name.to_lowercase())
+                    .map(|block| Some(block.id))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Biome surface block not found in registry: {}", name),
+                        )
+                    }),
+                None => Ok(None),
+            }
+        };
+
+        let mut blocks = HashMap::new();
+
+        for biome in terrain.biomes() {
+            blocks.insert(
+                biome.name.clone(),
+                (
+                    resolve(&biome.surface_block)?,
+                    resolve(&biome.subsurface_block)?,
+                ),
+            );
+        }
+
+        Ok(Self { terrain, blocks })
+    }
+}
+
+impl ChunkStage for BiomeSurfaceStage {
+    fn name(&self) -> String {
+        "Biome Surface".to_owned()
+    }
+
+    fn process(&self, mut chunk: Chunk, resources: Resources, _: Option<Space>) -> Chunk {
+        let Vec3(min_x, _, min_z) = chunk.min;
+        let Vec3(max_x, _, max_z) = chunk.max;
+
+        let registry = resources.registry;
+
+        for vx in min_x..max_x {
+            for vz in min_z..max_z {
+                let biome = self.terrain.get_surface_biome_at(vx, vz);
+
+                let (surface, subsurface) = match self.blocks.get(&biome.name) {
+                    Some(&(None, None)) | None => continue,
+                    Some(&blocks) => blocks,
+                };
+
+                let mut vy = chunk.get_max_height(vx, vz) as i32;
+
+                while vy > 0
+                    && registry
+                        .get_block_by_id(chunk.get_voxel(vx, vy, vz))
+                        .is_fluid
+                {
+                    vy -= 1;
+                }
+
+                for depth in 0..=biome.subsurface_depth as i32 {
+                    let id = chunk.get_voxel(vx, vy - depth, vz);
+
+                    if vy - depth < 0
+                        || registry.is_air(id)
+                        || registry.get_block_by_id(id).is_fluid
+                    {
+                        break;
+                    }
+
+                    let block = if depth == 0 { surface } else { subsurface };
+
+                    if let Some(block) = block {
+                        chunk.set_voxel(vx, vy - depth, vz, block);
+                    }
+                }
+            }
+        }
+
+        chunk
+    }
+}
+
 /// A pipeline is strictly for holding the stages necessary to build the chunks.
 pub struct Pipeline {
     /// A list of stages that chunks are in.
@@ -248,6 +351,20 @@ impl Pipeline {
         }
     }
 
+    /// Set the number of threads to process chunks with. Defaults to one per CPU.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.pool = ThreadPoolBuilder::new()
+            .num_threads(threads.max(1))
+            .thread_name(|index| format!("voxelize-chunking-{index}"))
+            .build()
+            .unwrap();
+    }
+
+    /// Whether no chunk is waiting to be processed or being processed at the moment.
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty() && self.chunks.is_empty()
+    }
+
     /// Add a chunk coordinate to the pipeline to be processed.
     pub fn add_chunk(&mut self, coords: &Vec2<i32>, prioritized: bool) {
         if self.has_chunk(coords) {
@@ -366,36 +483,320 @@ impl Pipeline {
         Some(result)
     }
 
-    /// Merge consecutive chunk stages that don't require spaces together into meta stages.
-    pub(crate) fn merge_stages(&mut self) {
-        let mut new_stages: Vec<Arc<dyn ChunkStage + Send + Sync>> = vec![];
+    /// Take the next processed chunk, if any, and move it along: its extra changes are applied to the chunks
+    /// that are done or kept as leftovers for the ones still generating, and the chunk goes on to its next stage
+    /// or to the mesher after the last one. Returns whether a chunk was taken.
+    pub(crate) fn advance(
+        &mut self,
+        chunks: &mut Chunks,
+        mesher: &mut Mesher,
+        config: &WorldConfig,
+    ) -> bool {
+        let (mut chunk, extra_changes) = match self.results() {
+            Some(result) => result,
+            None => return false,
+        };
+
+        // Apply the extra changes from processing these chunks to the other chunks.
+        extra_changes.into_iter().for_each(|(voxel, id)| {
+            let coords =
+                ChunkUtils::map_voxel_to_chunk(voxel.0, voxel.1, voxel.2, config.chunk_size);
+
+            if chunks.is_chunk_ready(&coords) {
+                chunks.update_voxel(&voxel, id);
+                return;
+            }
+
+            let mut already = self.leftovers.remove(&coords).unwrap_or_default();
+            already.push((voxel, id));
+            self.leftovers.insert(coords, already);
+        });
+
+        // Advance the chunk to the next stage.
+        if let ChunkStatus::Generating(curr_stage) = chunk.status {
+            let next_stage = curr_stage + 1;
+
+            // This chunk is done with the last stage.
+            // Can be pushed to the mesher.
+            if next_stage >= self.stages.len() {
+                // At this point, this chunk has nothing to do with the pipeline.
+                chunk.status = ChunkStatus::Meshing;
+                mesher.add_chunk(&chunk.coords, false);
+                self.remove_chunk(&chunk.coords);
+            } else {
+                // Otherwise, advance the chunk to the next stage.
+                chunk.status = ChunkStatus::Generating(next_stage);
+                self.add_chunk(&chunk.coords, false);
+            }
 
-        let mut current_meta: Option<MetaStage> = None;
+            // Notify neighbors that this chunk is ready.
+            self.notify_listeners(chunks, mesher, &chunk.coords);
 
-        for stage in self.stages.to_owned().into_iter() {
-            if stage.needs_space().is_some() {
-                if let Some(current_stage) = current_meta {
-                    new_stages.push(Arc::new(current_stage));
+            // Renew the chunk to the world map.
+            chunks.renew(chunk);
+        }
+
+        true
+    }
+
+    /// Put the chunks that were waiting on the chunk at `coords` back into the pipeline, or into the mesher if
+    /// they are done generating.
+    pub(crate) fn notify_listeners(
+        &mut self,
+        chunks: &mut Chunks,
+        mesher: &mut Mesher,
+        coords: &Vec2<i32>,
+    ) {
+        let listeners = match chunks.listeners.remove(coords) {
+            Some(listeners) => listeners,
+            None => return,
+        };
+
+        listeners.into_iter().for_each(|n_coords| {
+            // If this chunk is DNE or if this chunk is still in the pipeline, we re-add it to the pipeline.
+            if !chunks.map.contains_key(&n_coords)
+                || matches!(
+                    chunks.raw(&n_coords).unwrap().status,
+                    ChunkStatus::Generating(_)
+                )
+            {
+                self.add_chunk(&n_coords, true);
+            }
+            // If this chunk is in the meshing stage, we re-add it to the mesher.
+            else if let Some(chunk) = chunks.raw(&n_coords) {
+                if matches!(chunk.status, ChunkStatus::Meshing) {
+                    mesher.add_chunk(&n_coords, true);
                 }
-                current_meta = None;
-                new_stages.push(stage);
+            }
+        })
+    }
+
+    /// Send the queued chunks whose neighbors are far enough along to their next stage. Chunks that do not exist
+    /// yet are loaded or created, and chunks that have to wait for a neighbor listen to it instead.
+    pub(crate) fn dispatch(
+        &mut self,
+        chunks: &mut Chunks,
+        mesher: &mut Mesher,
+        interests: &ChunkInterests,
+        registry: &Registry,
+        config: &WorldConfig,
+    ) {
+        let chunk_size = config.chunk_size;
+
+        let mut processes = vec![];
+
+        if !self.queue.is_empty() {
+            let mut queue: Vec<Vec2<i32>> = self.queue.to_owned().into();
+            queue.sort_by(|a, b| interests.compare(a, b));
+            self.queue = VecDeque::from(queue);
+        }
+
+        while !self.queue.is_empty() && !self.stages.is_empty() {
+            let coords = self.get().unwrap();
+            let chunk = chunks.raw(&coords);
+
+            // Check if this chunk DNE. If DNE, try loading or make one.
+            if chunk.is_none() {
+                // Try loading the chunk from disk.
+                if let Some(chunk) = chunks.try_load(&coords) {
+                    self.remove_chunk(&coords);
+                    mesher.add_chunk(&coords, false);
+                    chunks.renew(chunk);
+
+                    continue;
+                }
+
+                let new_chunk = Chunk::new(
+                    &nanoid!(),
+                    coords.0,
+                    coords.1,
+                    &ChunkOptions {
+                        max_height: config.max_height,
+                        sub_chunks: config.sub_chunks,
+                        size: config.chunk_size,
+                    },
+                );
+
+                chunks.renew(new_chunk);
+            }
+
+            // Retrieve the chunk again from the world map.
+            let chunk = chunks.raw(&coords).unwrap();
+
+            // Means the chunk shouldn't be in the pipeline. Not sure why this would ever happen.
+            if !matches!(chunk.status, ChunkStatus::Generating(_)) {
+                self.remove_chunk(&coords);
                 continue;
             }
 
-            if let Some(mut meta) = current_meta {
-                meta.add_stage(stage);
-                current_meta = Some(meta);
+            // Take ownership of the chunk.
+            let chunk = chunk.clone();
+
+            let index = if let ChunkStatus::Generating(index) = chunk.status {
+                index
+            } else {
+                unreachable!()
+            };
+            let stage = &self.stages[index];
+
+            // Calculate the radius that this stage requires to be processed.
+            let margin = stage.neighbors(config);
+
+            if margin > 0 {
+                let r = (margin as f32 / chunk_size as f32).ceil() as i32;
+
+                // Loop through the neighbors to see if they are ready.
+                let mut ready = true;
+
+                for x in -r..=r {
+                    for z in -r..=r {
+                        if (x == 0 && z == 0) || (x * x + z * z > r * r) {
+                            continue;
+                        }
+
+                        // OK cases are:
+                        // 1. The neighbor is ready.
+                        // 2. The neighbor's stage >= chunk's stage.
+                        let n_coords = Vec2(coords.0 + x, coords.1 + z);
+
+                        // If the chunk isn't within the world borders or its ready, then we skip.
+                        if !chunks.is_within_world(&n_coords) || chunks.is_chunk_ready(&n_coords) {
+                            continue;
+                        }
+
+                        // See if the neighbor's stage is >= chunk's stage.
+                        if let Some(neighbor) = chunks.raw(&n_coords) {
+                            if let ChunkStatus::Generating(n_stage) = neighbor.status {
+                                if n_stage >= index {
+                                    continue;
+                                }
+                            }
+                        }
+
+                        // Till this point, the neighbor is not ready. We can add a listener to it.
+                        chunks.add_listener(&n_coords, &coords);
+
+                        ready = false;
+
+                        break;
+                    }
+
+                    if !ready {
+                        break;
+                    }
+                }
+
+                // If this chunk cannot be processed yet, we ignore it until the listeners notify us.
+                if !ready {
+                    continue;
+                }
+            }
+
+            // To this point, we know that this chunk is ready to be processed by the stage.
+            if let Some(data) = stage.needs_space() {
+                let mut space = chunks.make_space(&chunk.coords, margin);
+
+                if data.needs_voxels {
+                    space = space.needs_voxels();
+                }
+
+                if data.needs_lights {
+                    space = space.needs_lights();
+                }
+
+                if data.needs_height_maps {
+                    space = space.needs_height_maps();
+                }
+
+                let space = space.build();
+
+                processes.push((chunk, Some(space)));
             } else {
-                let mut meta = MetaStage::default();
-                meta.add_stage(stage);
-                current_meta = Some(meta);
+                processes.push((chunk, None));
+            }
+        }
+
+        if !processes.is_empty() {
+            self.process(processes, registry, config);
+        }
+    }
+
+    /// Apply the changes that spilled into the chunk at `coords` while it was still generating, keeping the
+    /// height map up to date. The leftovers are kept, as the chunks around it apply them too, until the chunk
+    /// itself is meshed.
+    pub(crate) fn apply_leftovers(
+        &self,
+        chunks: &mut Chunks,
+        registry: &Registry,
+        coords: &Vec2<i32>,
+    ) {
+        let blocks = match self.leftovers.get(coords) {
+            Some(blocks) => blocks,
+            None => return,
+        };
+
+        blocks.iter().for_each(|(voxel, val)| {
+            let Vec3(vx, vy, vz) = *voxel;
+
+            chunks.set_raw_voxel(vx, vy, vz, *val);
+
+            let height = chunks.get_max_height(vx, vz);
+            let id = BlockUtils::extract_id(*val);
+
+            // Change the max height if necessary.
+            if registry.is_air(id) {
+                if vy == height as i32 {
+                    // on max height, should set max height to lower
+                    for y in (0..vy - 1).rev() {
+                        if y == 0 || registry.check_height(chunks.get_voxel(vx, y, vz)) {
+                            chunks.set_max_height(vx, vz, y as u32);
+                            break;
+                        }
+                    }
+                }
+            } else if height < vy as u32 {
+                chunks.set_max_height(vx, vz, vy as u32);
             }
+        });
+    }
+
+    /// Merge consecutive chunk stages that don't require spaces together into meta stages.
+    pub(crate) fn merge_stages(&mut self) {
+        self.stages = merge_stages(&self.stages);
+    }
+}
+
+/// Merge consecutive chunk stages that don't require spaces together into meta stages.
+pub(crate) fn merge_stages(
+    stages: &[Arc<dyn ChunkStage + Send + Sync>],
+) -> Vec<Arc<dyn ChunkStage + Send + Sync>> {
+    let mut new_stages: Vec<Arc<dyn ChunkStage + Send + Sync>> = vec![];
+
+    let mut current_meta: Option<MetaStage> = None;
+
+    for stage in stages.iter().cloned() {
+        if stage.needs_space().is_some() {
+            if let Some(current_stage) = current_meta {
+                new_stages.push(Arc::new(current_stage));
+            }
+            current_meta = None;
+            new_stages.push(stage);
+            continue;
         }
 
-        if let Some(meta) = current_meta {
-            new_stages.push(Arc::new(meta));
+        if let Some(mut meta) = current_meta {
+            meta.add_stage(stage);
+            current_meta = Some(meta);
+        } else {
+            let mut meta = MetaStage::default();
+            meta.add_stage(stage);
+            current_meta = Some(meta);
         }
+    }
 
-        self.stages = new_stages;
+    if let Some(meta) = current_meta {
+        new_stages.push(Arc::new(meta));
     }
+
+    new_stages
 }
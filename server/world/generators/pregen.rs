@@ -0,0 +1,250 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use crate::{
+    ChunkStatus, Chunks, MessageType, Registry, Vec2, Vec3, World, WorldConfig, WorldGenConfig,
+};
+
+use super::{
+    exporter::{MeshExporter, MeshFormat},
+    harness::WorldGenHarness,
+    mesher::Mesher,
+};
+
+/// Generates a region of chunks offline and writes them through the world's save backend, so that
+/// large maps can be pregenerated and shipped instead of generated on startup.
+///
+/// The region is processed in square tiles of chunks, each generated by a `WorldGenHarness` with
+/// enough padding for neighbor reads and spilled changes. With meshing turned on, every tile is also
+/// lit and meshed by the `Mesher`, and the meshes can be exported as preview files. The chunks
+/// around a tile that light reaches into are kept for the tiles next to it, instead of being
+/// generated again. The save backend
+/// only stores voxels and height maps, so lights and meshes are rebuilt when the chunks are loaded.
+pub struct Pregenerator {
+    /// The harness running the chunk stages.
+    harness: WorldGenHarness,
+
+    /// Number of chunks on each side of a tile.
+    tile_size: usize,
+
+    /// Whether to light and mesh the chunks after generating them.
+    meshing: bool,
+
+    /// Folder and format to export the meshes of each tile to.
+    export: Option<(PathBuf, MeshFormat)>,
+}
+
+impl Pregenerator {
+    /// Create a pregenerator from a harness with the stages to run.
+    pub fn new(harness: WorldGenHarness) -> Self {
+        Self {
+            harness,
+            tile_size: 8,
+            meshing: false,
+            export: None,
+        }
+    }
+
+    /// Create a pregenerator that runs the stages of a world's pipeline.
+    pub fn from_world(world: &World, registry: &Registry) -> Self {
+        Self::new(WorldGenHarness::from_pipeline(
+            &world.pipeline(),
+            &world.config(),
+            registry,
+        ))
+    }
+
+    /// Create a pregenerator that runs the stages described by a world generation config.
+    pub fn from_worldgen(
+        worldgen: &WorldGenConfig,
+        config: &WorldConfig,
+        registry: &Registry,
+    ) -> io::Result<Self> {
+        let mut harness = WorldGenHarness::new(config, registry);
+        harness.stages = worldgen.build_stages(config, registry)?;
+        Ok(Self::new(harness))
+    }
+
+    /// Set the number of threads to generate chunks with.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.harness.set_threads(threads);
+    }
+
+    /// Set the number of chunks on each side of a tile. Larger tiles waste less work on padding but
+    /// keep more chunks in memory.
+    pub fn set_tile_size(&mut self, tile_size: usize) {
+        self.tile_size = tile_size.max(1);
+    }
+
+    /// Set whether to light and mesh the chunks after generating them.
+    pub fn set_meshing(&mut self, meshing: bool) {
+        self.meshing = meshing;
+    }
+
+    /// Export the meshes of each tile into a folder, one file per tile. Turns on meshing.
+    pub fn set_mesh_export<P: AsRef<Path>>(&mut self, folder: P, format: MeshFormat) {
+        self.meshing = true;
+        self.export = Some((folder.as_ref().to_owned(), format));
+    }
+
+    /// Generate and save every chunk from `min` to `max` inclusive, calling `progress` with the
+    /// number of chunks done and the total after each tile. Returns the number of chunks saved.
+    pub fn run<F>(&self, min: &Vec2<i32>, max: &Vec2<i32>, mut progress: F) -> io::Result<usize>
+    where
+        F: FnMut(usize, usize),
+    {
+        let config = &self.harness.config;
+        let registry = &self.harness.registry;
+
+        if !config.saving {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Pregenerating requires a world config with saving turned on.",
+            ));
+        }
+
+        if let Some((folder, _)) = &self.export {
+            std::fs::create_dir_all(folder)?;
+        }
+
+        let mut chunks = Chunks::new(config);
+        let mut mesher = Mesher::new();
+
+        let light_padding = if self.meshing {
+            (config.max_light_level as f32 / config.chunk_size as f32).ceil() as i32
+        } else {
+            0
+        };
+
+        let total = ((max.0 - min.0 + 1).max(0) * (max.1 - min.1 + 1).max(0)) as usize;
+        let mut done = 0;
+
+        for tx in (min.0..=max.0).step_by(self.tile_size) {
+            for tz in (min.1..=max.1).step_by(self.tile_size) {
+                let tile_min = Vec2(tx, tz);
+                let tile_max = Vec2(
+                    (tx + self.tile_size as i32 - 1).min(max.0),
+                    (tz + self.tile_size as i32 - 1).min(max.1),
+                );
+
+                let is_in_tile = |coords: &Vec2<i32>| {
+                    coords.0 >= tile_min.0
+                        && coords.0 <= tile_max.0
+                        && coords.1 >= tile_min.1
+                        && coords.1 <= tile_max.1
+                };
+
+                // Only generate the chunks that the previous tiles did not leave behind as padding.
+                let mut missing_min = Vec2(i32::MAX, i32::MAX);
+                let mut missing_max = Vec2(i32::MIN, i32::MIN);
+
+                for cx in (tile_min.0 - light_padding)..=(tile_max.0 + light_padding) {
+                    for cz in (tile_min.1 - light_padding)..=(tile_max.1 + light_padding) {
+                        if !chunks.map.contains_key(&Vec2(cx, cz)) {
+                            missing_min = Vec2(missing_min.0.min(cx), missing_min.1.min(cz));
+                            missing_max = Vec2(missing_max.0.max(cx), missing_max.1.max(cz));
+                        }
+                    }
+                }
+
+                if missing_min.0 <= missing_max.0 {
+                    for mut chunk in self.harness.generate(&missing_min, &missing_max) {
+                        if !chunks.map.contains_key(&chunk.coords) {
+                            chunk.status = ChunkStatus::Ready;
+                            chunks.renew(chunk);
+                        }
+                    }
+                }
+
+                let mut coords_list = chunks
+                    .map
+                    .keys()
+                    .filter(|coords| is_in_tile(coords))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                coords_list.sort_by_key(|coords| (coords.0, coords.1));
+
+                if self.meshing {
+                    let processes = coords_list
+                        .iter()
+                        .map(|coords| {
+                            let space = chunks
+                                .make_space(coords, config.max_light_level as usize)
+                                .needs_height_maps()
+                                .needs_voxels()
+                                .build();
+
+                            (chunks.raw(coords).unwrap().to_owned(), space)
+                        })
+                        .collect::<Vec<_>>();
+
+                    mesher.process(processes, &MessageType::Load, registry, config);
+
+                    let mut exporter = MeshExporter::new();
+                    let mut meshed = 0;
+
+                    while meshed < coords_list.len() {
+                        if let Some((mut chunk, _)) = mesher.results() {
+                            if self.export.is_some() {
+                                let blocks_per_sub_chunk =
+                                    (config.max_height / config.sub_chunks) as i32;
+                                let Vec3(min_x, _, min_z) = chunk.min;
+
+                                chunk
+                                    .meshes
+                                    .take()
+                                    .unwrap_or_default()
+                                    .into_values()
+                                    .for_each(|mesh| {
+                                        exporter.add_geometries(
+                                            &Vec3(min_x, mesh.level * blocks_per_sub_chunk, min_z),
+                                            mesh.geometries,
+                                            registry,
+                                        );
+                                    });
+                            }
+
+                            chunks.renew(chunk);
+                            meshed += 1;
+                        } else {
+                            thread::yield_now();
+                        }
+                    }
+
+                    if let Some((folder, format)) = &self.export {
+                        let extension = match format {
+                            MeshFormat::Gltf => "gltf",
+                            MeshFormat::Obj => "obj",
+                        };
+
+                        exporter.write(
+                            folder.join(format!("{}_{}.{}", tile_min.0, tile_min.1, extension)),
+                            format,
+                        )?;
+                    }
+                }
+
+                for coords in coords_list.iter() {
+                    chunks.try_save(coords)?;
+                }
+
+                // Keep the chunks that later tiles read as padding: the rest of this column of tiles, and the
+                // columns after it.
+                let next_x = tile_max.0 + 1 - light_padding;
+                let next_z = tile_max.1 + 1 - light_padding;
+                let is_column_done = tile_max.1 == max.1;
+                chunks.map.retain(|coords, _| {
+                    coords.0 >= next_x || (!is_column_done && coords.1 >= next_z)
+                });
+
+                done += coords_list.len();
+                progress(done, total);
+            }
+        }
+
+        Ok(done)
+    }
+}
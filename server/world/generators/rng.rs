@@ -0,0 +1,12 @@
+use fastrand::Rng;
+
+/// Get a deterministic random number generator from a seed and a list of keys, such as chunk or voxel
+/// coordinates. The same seed and keys always give the same sequence, regardless of the order chunks
+/// are generated in.
+pub(crate) fn seeded_rng(seed: u32, keys: &[u64]) -> Rng {
+    let hash = keys.iter().fold(seed as u64, |hash, key| {
+        hash.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ key
+    });
+
+    Rng::with_seed(hash.wrapping_mul(0xBF58_476D_1CE4_E5B9))
+}
@@ -0,0 +1,405 @@
+use std::{io, sync::Arc};
+
+use fastrand::Rng;
+
+use crate::{
+    Chunk, ChunkStage, NoiseOptions, Registry, Resources, Schematic, SchematicTransform,
+    SeededNoise, Space, Terrain, Vec3, VoxelAccess, VoxelUpdate,
+};
+
+use super::rng::seeded_rng;
+
+/// Key mixed into the random number generators of grid cells, so that a cell doesn't share its sequence
+/// with the voxel column of the same coordinates.
+const CELL_KEY: u64 = 0x6365_6c6c;
+
+/// A function that generates the voxel updates of a structure placed at a voxel coordinate.
+pub type StructureGenerator = Arc<dyn Fn(&Vec3<i32>, &Registry) -> Vec<VoxelUpdate> + Send + Sync>;
+
+/// Where the voxels of a structure come from.
+#[derive(Clone)]
+pub enum StructureSource {
+    /// A schematic, pasted with its bottom center at the placement position.
+    Schematic(Schematic),
+
+    /// A function that generates the structure in code, such as a tree from `Trees::generate`.
+    Generator(StructureGenerator),
+}
+
+/// How the positions of structures are decided in a `StructureStage`.
+#[derive(Clone)]
+pub enum StructurePlacement {
+    /// Place a structure on every column where the normalized noise value is above the threshold,
+    /// similar to `Trees::should_plant`.
+    Noise {
+        options: NoiseOptions,
+        threshold: f64,
+    },
+
+    /// Place at most one structure in each `spacing` by `spacing` cell of the world, offset randomly
+    /// by up to `jitter` voxels from the cell center and kept with a probability of `chance`.
+    Grid {
+        spacing: i32,
+        jitter: i32,
+        chance: f64,
+    },
+}
+
+/// A prefab structure that can be placed by a `StructureStage`.
+#[derive(Clone)]
+pub struct Structure {
+    /// Name of the structure.
+    pub name: String,
+
+    /// Where the voxels of this structure come from.
+    pub source: StructureSource,
+
+    /// The offset from the ground voxel to where the structure is placed. Defaults to `(0, 1, 0)`.
+    pub offset: Vec3<i32>,
+
+    /// Relative weight of this structure being chosen over the others in the same stage.
+    pub weight: u32,
+
+    /// Names of biomes this structure can be placed in. Empty means any biome.
+    pub biomes: Vec<String>,
+
+    /// Names of blocks this structure can be placed on. Empty means any non-air block.
+    pub ground: Vec<String>,
+
+    /// Whether schematic structures are randomly rotated around the y-axis.
+    pub random_rotation: bool,
+}
+
+impl Structure {
+    /// Create a structure builder with a name.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(name: &str) -> StructureBuilder {
+        StructureBuilder::new(name)
+    }
+
+    /// Check if this structure can be placed on top of a block in a biome.
+    fn can_place(&self, ground: &str, biome: Option<&str>) -> bool {
+        if !self.ground.is_empty()
+            && !self
+                .ground
+                .iter()
+                .any(|name| name.to_lowercase() == ground.to_lowercase())
+        {
+            return false;
+        }
+
+        if let Some(biome) = biome {
+            if !self.biomes.is_empty() && !self.biomes.iter().any(|name| name == biome) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Generate the voxel updates of this structure with its ground at `at`.
+    fn generate(&self, at: &Vec3<i32>, rng: &Rng, registry: &Registry) -> Vec<VoxelUpdate> {
+        let base = at.add(&self.offset);
+
+        match &self.source {
+            StructureSource::Generator(generator) => generator(&base, registry),
+            StructureSource::Schematic(schematic) => {
+                let transform = if self.random_rotation {
+                    SchematicTransform::new().rotate(rng.u32(0..4))
+                } else {
+                    SchematicTransform::new()
+                };
+
+                let Vec3(width, _, depth) = schematic.transformed_size(&transform);
+                let corner = Vec3(base.0 - width as i32 / 2, base.1, base.2 - depth as i32 / 2);
+
+                schematic.to_updates(&corner, &transform, registry)
+            }
+        }
+    }
+}
+
+/// Builder for a structure.
+pub struct StructureBuilder {
+    name: String,
+    source: Option<StructureSource>,
+    offset: Vec3<i32>,
+    weight: u32,
+    biomes: Vec<String>,
+    ground: Vec<String>,
+    random_rotation: bool,
+}
+
+impl StructureBuilder {
+    /// Create a structure builder with default values.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            source: None,
+            offset: Vec3(0, 1, 0),
+            weight: 1,
+            biomes: vec![],
+            ground: vec![],
+            random_rotation: false,
+        }
+    }
+
+    /// Configure the structure to be pasted from a schematic.
+    pub fn schematic(mut self, schematic: Schematic) -> Self {
+        self.source = Some(StructureSource::Schematic(schematic));
+        self
+    }
+
+    /// Configure the structure to be generated by a function.
+    pub fn generator<F: Fn(&Vec3<i32>, &Registry) -> Vec<VoxelUpdate> + Send + Sync + 'static>(
+        mut self,
+        generator: F,
+    ) -> Self {
+        self.source = Some(StructureSource::Generator(Arc::new(generator)));
+        self
+    }
+
+    /// Configure the offset from the ground voxel. Defaults to `(0, 1, 0)`.
+    pub fn offset(mut self, offset: Vec3<i32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Configure the relative weight of this structure being chosen. Defaults to 1.
+    pub fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Configure the biomes this structure can be placed in. Defaults to any biome.
+    pub fn biomes(mut self, biomes: &[&str]) -> Self {
+        self.biomes = biomes.iter().map(|name| (*name).to_owned()).collect();
+        self
+    }
+
+    /// Configure the blocks this structure can be placed on. Defaults to any non-air block.
+    pub fn ground(mut self, ground: &[&str]) -> Self {
+        self.ground = ground.iter().map(|name| (*name).to_owned()).collect();
+        self
+    }
+
+    /// Configure whether schematic structures are randomly rotated. Defaults to false.
+    pub fn random_rotation(mut self, random_rotation: bool) -> Self {
+        self.random_rotation = random_rotation;
+        self
+    }
+
+    /// Create a structure instance.
+    pub fn build(self) -> Structure {
+        Structure {
+            source: self.source.unwrap_or_else(|| {
+                panic!(
+                    "Structure {} does not have a schematic or generator.",
+                    self.name
+                )
+            }),
+            name: self.name,
+            offset: self.offset,
+            weight: self.weight,
+            biomes: self.biomes,
+            ground: self.ground,
+            random_rotation: self.random_rotation,
+        }
+    }
+}
+
+/// A generic chunk stage to place prefab structures. Structures are only placed at positions within
+/// the processed chunk, and the voxels that spill over the chunk border are added to
+/// `chunk.extra_changes` to be applied to the neighboring chunks through the pipeline's leftovers.
+pub struct StructureStage {
+    name: String,
+    seed: u32,
+    placement: StructurePlacement,
+    noise: Option<SeededNoise>,
+    terrain: Option<Terrain>,
+    structures: Vec<Structure>,
+}
+
+impl StructureStage {
+    /// Create a new structure stage with a seed and a placement strategy.
+    pub fn new(seed: u32, placement: StructurePlacement) -> Self {
+        let noise = if let StructurePlacement::Noise { options, .. } = &placement {
+            Some(SeededNoise::new(seed, options))
+        } else {
+            None
+        };
+
+        Self {
+            name: "Structures".to_owned(),
+            seed,
+            placement,
+            noise,
+            terrain: None,
+            structures: vec![],
+        }
+    }
+
+    /// Set the name of this stage. Defaults to "Structures".
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// Set the terrain used to filter structures by their biomes.
+    pub fn terrain(mut self, terrain: Terrain) -> Self {
+        self.terrain = Some(terrain);
+        self
+    }
+
+    /// Add a structure to be placed by this stage. Errors if the structure has biomes but the stage has no
+    /// terrain to look them up in.
+    pub fn add_structure(mut self, structure: Structure) -> io::Result<Self> {
+        if !structure.biomes.is_empty() && self.terrain.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Structure {} has biomes but the structure stage has no terrain to look them up in.",
+                    structure.name
+                ),
+            ));
+        }
+
+        self.structures.push(structure);
+        Ok(self)
+    }
+
+    /// Get a deterministic random number generator for a voxel column.
+    fn rng(&self, vx: i32, vz: i32) -> Rng {
+        seeded_rng(self.seed, &[vx as u32 as u64, vz as u32 as u64])
+    }
+
+    /// Get a deterministic random number generator for a grid cell.
+    fn cell_rng(&self, cx: i32, cz: i32) -> Rng {
+        seeded_rng(self.seed, &[CELL_KEY, cx as u32 as u64, cz as u32 as u64])
+    }
+
+    /// Get the voxel columns within the chunk where structures should be attempted.
+    fn positions(&self, chunk: &Chunk) -> Vec<(i32, i32)> {
+        let Vec3(min_x, _, min_z) = chunk.min;
+        let Vec3(max_x, _, max_z) = chunk.max;
+
+        let mut positions = vec![];
+
+        match &self.placement {
+            StructurePlacement::Noise { threshold, .. } => {
+                let noise = self.noise.as_ref().unwrap();
+
+                for vx in min_x..max_x {
+                    for vz in min_z..max_z {
+                        let height = chunk.get_max_height(vx, vz) as i32;
+                        let value =
+                            ((noise.get3d(vx, height, vz) + 1.0) / 2.0 * 10000.0).floor() / 10000.0;
+
+                        if value > *threshold {
+                            positions.push((vx, vz));
+                        }
+                    }
+                }
+            }
+            StructurePlacement::Grid {
+                spacing,
+                jitter,
+                chance,
+            } => {
+                let spacing = (*spacing).max(1);
+
+                for cx in min_x.div_euclid(spacing) - 1..=(max_x - 1).div_euclid(spacing) + 1 {
+                    for cz in min_z.div_euclid(spacing) - 1..=(max_z - 1).div_euclid(spacing) + 1 {
+                        let rng = self.cell_rng(cx, cz);
+
+                        let vx = cx * spacing + spacing / 2 + rng.i32(-*jitter..=*jitter);
+                        let vz = cz * spacing + spacing / 2 + rng.i32(-*jitter..=*jitter);
+
+                        if rng.f64() >= *chance {
+                            continue;
+                        }
+
+                        if vx >= min_x && vx < max_x && vz >= min_z && vz < max_z {
+                            positions.push((vx, vz));
+                        }
+                    }
+                }
+            }
+        }
+
+        positions
+    }
+}
+
+impl ChunkStage for StructureStage {
+    fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    fn process(&self, mut chunk: Chunk, resources: Resources, _: Option<Space>) -> Chunk {
+        if self.structures.is_empty() {
+            return chunk;
+        }
+
+        let registry = resources.registry;
+        let needs_biome = self
+            .structures
+            .iter()
+            .any(|structure| !structure.biomes.is_empty());
+
+        for (vx, vz) in self.positions(&chunk) {
+            let height = chunk.get_max_height(vx, vz) as i32;
+            let id = chunk.get_voxel(vx, height, vz);
+
+            if registry.is_air(id) || !registry.has_type(id) {
+                continue;
+            }
+
+            let ground = &registry.get_block_by_id(id).name;
+            let biome = self
+                .terrain
+                .as_ref()
+                .filter(|_| needs_biome)
+                .map(|terrain| terrain.get_biome_at(vx, vz).name.as_str());
+
+            let candidates = self
+                .structures
+                .iter()
+                .filter(|structure| structure.weight > 0 && structure.can_place(ground, biome))
+                .collect::<Vec<_>>();
+
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let rng = self.rng(vx, vz);
+
+            let total = candidates
+                .iter()
+                .map(|structure| structure.weight)
+                .sum::<u32>();
+            let mut pick = rng.u32(0..total);
+
+            let structure = candidates
+                .into_iter()
+                .find(|structure| {
+                    if pick < structure.weight {
+                        return true;
+                    }
+
+                    pick -= structure.weight;
+                    false
+                })
+                .unwrap();
+
+            structure
+                .generate(&Vec3(vx, height, vz), &rng, registry)
+                .into_iter()
+                .for_each(|(Vec3(ux, uy, uz), voxel)| {
+                    chunk.set_raw_voxel(ux, uy, uz, voxel);
+                });
+        }
+
+        chunk
+    }
+}
@@ -7,13 +7,38 @@ use crate::WorldConfig;
 
 use super::{
     noise::{NoiseOptions, SeededNoise},
+    noise_graph::{NoiseGraph, NoiseNode},
     spline::SplineMap,
 };
 
-#[derive(PartialEq, Clone)]
+/// A biome of the terrain, placed at a point in the space of terrain layer noise values.
+#[derive(Clone, Debug)]
 pub struct Biome {
     pub name: String,
     pub test_block: String,
+
+    /// Name of the block placed on the top voxel of each column in this biome.
+    pub surface_block: Option<String>,
+
+    /// Name of the block placed under the surface block in this biome.
+    pub subsurface_block: Option<String>,
+
+    /// Number of voxels under the surface block that are replaced with the subsurface block.
+    pub subsurface_depth: u32,
+
+    /// Overrides the height bias of the terrain layers in this biome. Sampled with the weighted average
+    /// of the terrain layer noise values.
+    pub height_bias_spline: Option<SplineMap>,
+
+    /// Overrides the height offset of the terrain layers in this biome. Sampled with the weighted average
+    /// of the terrain layer noise values.
+    pub height_offset_spline: Option<SplineMap>,
+}
+
+impl PartialEq for Biome {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
 }
 
 impl Biome {
@@ -21,8 +46,48 @@ impl Biome {
         Self {
             name: name.to_owned(),
             test_block: test_block.to_owned(),
+            surface_block: None,
+            subsurface_block: None,
+            subsurface_depth: 3,
+            height_bias_spline: None,
+            height_offset_spline: None,
         }
     }
+
+    /// Set the block placed on the top voxel of each column in this biome.
+    pub fn surface(mut self, block: &str) -> Self {
+        self.surface_block = Some(block.to_owned());
+        self
+    }
+
+    /// Set the block placed under the surface, and how many voxels deep it goes. Defaults to 3 voxels.
+    pub fn subsurface(mut self, block: &str, depth: u32) -> Self {
+        self.subsurface_block = Some(block.to_owned());
+        self.subsurface_depth = depth;
+        self
+    }
+
+    /// Add a set of points to this biome's height bias override spline graph.
+    pub fn add_bias_points(mut self, points: &[[f64; 2]]) -> Self {
+        let spline = self
+            .height_bias_spline
+            .get_or_insert_with(SplineMap::default);
+        points.iter().for_each(|point| {
+            spline.add(point[0], point[1]);
+        });
+        self
+    }
+
+    /// Add a set of points to this biome's height offset override spline graph.
+    pub fn add_offset_points(mut self, points: &[[f64; 2]]) -> Self {
+        let spline = self
+            .height_offset_spline
+            .get_or_insert_with(SplineMap::default);
+        points.iter().for_each(|point| {
+            spline.add(point[0], point[1]);
+        });
+        self
+    }
 }
 
 /// A seeded layered terrain for Voxelize world generation.
@@ -31,8 +96,13 @@ pub struct Terrain {
     config: WorldConfig,
     noise: SeededNoise,
     biome_tree: KdTree<f64, Biome, Vec<f64>>,
+    biomes: Vec<Biome>,
+    biome_blend: usize,
+    biome_falloff: f64,
+    has_biome_overrides: bool,
     pub layers: Vec<(TerrainLayer, f64)>,
     pub noise_layers: Vec<(TerrainLayer, f64)>,
+    pub density_layers: Vec<(NoiseGraph, f64)>,
 }
 
 impl Terrain {
@@ -42,8 +112,13 @@ impl Terrain {
             config: config.to_owned(),
             noise: SeededNoise::new(config.seed, &config.terrain),
             biome_tree: KdTree::new(2),
+            biomes: vec![],
+            biome_blend: 1,
+            biome_falloff: 8.0,
+            has_biome_overrides: false,
             layers: vec![],
             noise_layers: vec![],
+            density_layers: vec![],
         }
     }
 
@@ -79,6 +154,14 @@ impl Terrain {
         self
     }
 
+    /// Add a 3D density layer to the voxelize terrain, seeded with the world's seed. Its weighted 3D noise is added
+    /// on top of the 2D bias and offset density, which allows overhangs, arches and floating islands.
+    pub fn add_density_layer(&mut self, node: &NoiseNode, weight: f64) -> &mut Self {
+        self.density_layers
+            .push((NoiseGraph::new(self.config.seed, node), weight));
+        self
+    }
+
     pub fn add_biome(&mut self, point: &[f64], biome: Biome) -> &mut Self {
         let point_vec = point.to_vec();
         let point_vec = point_vec[..self.layers.len()]
@@ -88,10 +171,28 @@ impl Terrain {
             .collect::<Vec<f64>>()
             .to_owned();
 
+        if biome.height_bias_spline.is_some() || biome.height_offset_spline.is_some() {
+            self.has_biome_overrides = true;
+        }
+
+        self.biomes.push(biome.clone());
         self.biome_tree.add(point_vec, biome).unwrap();
         self
     }
 
+    /// Get every biome added to this terrain, in the order they were added.
+    pub fn biomes(&self) -> &[Biome] {
+        &self.biomes
+    }
+
+    /// Configure how many of the nearest biomes are blended together, and how fast a biome's weight
+    /// falls off as it gets further than the nearest biome. Defaults to 1 biome, which is no blending.
+    pub fn set_biome_blend(&mut self, neighbors: usize, falloff: f64) -> &mut Self {
+        self.biome_blend = neighbors.max(1);
+        self.biome_falloff = falloff;
+        self
+    }
+
     /// Get the voxel density at a voxel coordinate, which does the following:
     /// 1. Calculate the height bias and height offset of each terrain layer.
     /// 2. Obtain the average height bias and height offset at this specific voxel column.
@@ -107,6 +208,21 @@ impl Terrain {
         }
     }
 
+    /// Get the weighted 3D density of the density layers at a voxel coordinate. Returns 0.0 if there are none.
+    pub fn get_3d_density(&self, vx: i32, vy: i32, vz: i32) -> f64 {
+        self.density_layers
+            .iter()
+            .map(|(graph, weight)| graph.get3d(vx, vy, vz) * weight)
+            .sum()
+    }
+
+    /// Get the voxel density at a voxel coordinate, combining the 2D bias and offset density with the 3D density
+    /// layers. Stages that loop over columns should cache `get_bias_offset` instead of calling this for every voxel.
+    pub fn get_density_at(&self, vx: i32, vy: i32, vz: i32) -> f64 {
+        let (bias, offset) = self.get_bias_offset(vx, vz);
+        self.get_density_from_bias_offset(bias, offset, vy) + self.get_3d_density(vx, vy, vz)
+    }
+
     /// Get the height bias and height offset values at a voxel column. What it does is that it samples the bias and offset
     /// of all noise layers and take the average of them all.
     pub fn get_bias_offset(&self, vx: i32, vz: i32) -> (f64, f64) {
@@ -128,15 +244,40 @@ impl Terrain {
             total_weight += weight;
         });
 
-        (bias / total_weight, offset / total_weight)
+        let bias = bias / total_weight;
+        let offset = offset / total_weight;
+
+        if !self.has_biome_overrides {
+            return (bias, offset);
+        }
+
+        // Blend the biome overrides, sampled with the weighted average of the layer noise values.
+        let value = self.get_layer_average(vx, vz);
+
+        let mut blended_bias = 0.0;
+        let mut blended_offset = 0.0;
+
+        self.get_biome_weights_at(vx, vz)
+            .into_iter()
+            .for_each(|(biome, weight)| {
+                blended_bias += weight
+                    * biome
+                        .height_bias_spline
+                        .as_ref()
+                        .map_or(bias, |spline| spline.sample(value));
+                blended_offset += weight
+                    * biome
+                        .height_offset_spline
+                        .as_ref()
+                        .map_or(offset, |spline| spline.sample(value));
+            });
+
+        (blended_bias, blended_offset)
     }
 
+    /// Get the nearest biome at a voxel column.
     pub fn get_biome_at(&self, vx: i32, vz: i32) -> &Biome {
-        let values = self
-            .layers
-            .iter()
-            .map(|(layer, weight)| layer.noise.get2d(vx, vz) * weight)
-            .collect::<Vec<f64>>();
+        let values = self.get_layer_values(vx, vz);
 
         let result = self
             .biome_tree
@@ -145,6 +286,81 @@ impl Terrain {
 
         result.1
     }
+
+    /// Get the blended biomes at a voxel column and their weights, which add up to 1. Up to the configured
+    /// number of nearest biomes are blended, and each biome's weight falls off exponentially by how much
+    /// further it is than the nearest biome, so biomes are evenly mixed right at their borders.
+    pub fn get_biome_weights_at(&self, vx: i32, vz: i32) -> Vec<(&Biome, f64)> {
+        let values = self.get_layer_values(vx, vz);
+
+        let nearest = self
+            .biome_tree
+            .nearest(&values, self.biome_blend, &squared_euclidean)
+            .unwrap();
+
+        let closest = nearest[0].0.sqrt();
+
+        let mut weights = nearest
+            .into_iter()
+            .map(|(distance, biome)| {
+                (
+                    biome,
+                    (-(distance.sqrt() - closest) * self.biome_falloff).exp(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let total = weights.iter().map(|(_, weight)| weight).sum::<f64>();
+        weights.iter_mut().for_each(|(_, weight)| *weight /= total);
+
+        weights
+    }
+
+    /// Get the biome whose surface rules apply at a voxel column. Near biome borders, the blended biomes
+    /// are picked by their weights with a seeded hash of the column, dithering the surface materials.
+    pub fn get_surface_biome_at(&self, vx: i32, vz: i32) -> &Biome {
+        let weights = self.get_biome_weights_at(vx, vz);
+
+        if weights.len() == 1 {
+            return weights[0].0;
+        }
+
+        let mut hash = (self.config.seed as u64) ^ 0x9E37_79B9_7F4A_7C15;
+        hash = (hash ^ vx as u32 as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ vz as u32 as u64).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+
+        let mut pick = (hash >> 11) as f64 / (1u64 << 53) as f64;
+
+        for (biome, weight) in weights.iter() {
+            if pick < *weight {
+                return biome;
+            }
+
+            pick -= weight;
+        }
+
+        weights.last().unwrap().0
+    }
+
+    /// Get the weighted noise values of each terrain layer at a voxel column.
+    fn get_layer_values(&self, vx: i32, vz: i32) -> Vec<f64> {
+        self.layers
+            .iter()
+            .map(|(layer, weight)| layer.noise.get2d(vx, vz) * weight)
+            .collect()
+    }
+
+    /// Get the weighted average of the terrain layer noise values at a voxel column.
+    fn get_layer_average(&self, vx: i32, vz: i32) -> f64 {
+        let total_weight = self.layers.iter().map(|(_, weight)| weight).sum::<f64>();
+
+        if total_weight == 0.0 {
+            return 0.0;
+        }
+
+        self.get_layer_values(vx, vz).iter().sum::<f64>() / total_weight
+    }
 }
 
 /// A layer to the terrain. Consists of two spline graphs: height bias and height offset graphs.
@@ -155,7 +371,7 @@ impl Terrain {
 pub struct TerrainLayer {
     pub name: String,
     #[serde(skip_serializing)]
-    pub noise: SeededNoise,
+    pub noise: NoiseGraph,
     pub options: NoiseOptions,
     pub height_bias_spline: SplineMap,
     pub height_offset_spline: SplineMap,
@@ -167,13 +383,25 @@ impl TerrainLayer {
     pub fn new(name: &str, options: &NoiseOptions) -> Self {
         TerrainLayer {
             name: name.to_owned(),
-            noise: SeededNoise::new(0, options),
+            noise: NoiseGraph::new(0, &NoiseNode::fractal(options)),
             options: options.to_owned(),
             height_bias_spline: SplineMap::default(),
             height_offset_spline: SplineMap::default(),
         }
     }
 
+    /// Create a new terrain layer from any noise graph node, such as warped or cellular noise, instead of just
+    /// the multi-fractal noise of `NoiseOptions`.
+    pub fn from_node(name: &str, node: &NoiseNode) -> Self {
+        TerrainLayer {
+            name: name.to_owned(),
+            noise: NoiseGraph::new(0, node),
+            options: NoiseOptions::default(),
+            height_bias_spline: SplineMap::default(),
+            height_offset_spline: SplineMap::default(),
+        }
+    }
+
     /// Add a point to the bias spline graph.
     pub fn add_bias_point(mut self, point: [f64; 2]) -> Self {
         self.height_bias_spline.add(point[0], point[1]);
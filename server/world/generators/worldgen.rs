@@ -0,0 +1,768 @@
+use std::{fs, io, path::Path, sync::Arc};
+
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{Registry, Schematic, Vec3, WorldConfig};
+
+use super::{
+    caves::CaveStage,
+    lsystem::LSystem,
+    noise::NoiseOptions,
+    noise_graph::NoiseNode,
+    ores::Ore,
+    ores::OreStage,
+    pipeline::{BaseTerrainStage, BiomeSurfaceStage, ChunkStage, FlatlandStage, Pipeline},
+    structures::{Structure, StructurePlacement, StructureStage},
+    terrain::{Biome, Terrain, TerrainLayer},
+    trees::{Tree, Trees},
+};
+
+/// A serializable description of a world's generation, from the terrain layers and biomes to the
+/// trees and the order of the chunk stages. Blocks are referenced by name, so the same description
+/// works across registries. Descriptions are written in JSON, and loaded with `from_json` or `load`. Other
+/// formats such as TOML are not supported.
+///
+/// ```json
+/// {
+///   "terrain": {
+///     "layers": [{ "name": "continentalness", "noise": { "frequency": 0.0035 }, "weight": 1.0,
+///                  "bias": [[-1.0, 3.0], [1.0, 3.0]], "offset": [[-1.0, 0.2], [1.0, 0.6]] }],
+///     "biomes": [{ "name": "Plains", "point": [0.0], "surface": "Grass Block" }]
+///   },
+///   "stages": [
+///     { "type": "baseTerrain", "base": "Stone" },
+///     { "type": "biomeSurface" }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WorldGenConfig {
+    /// The terrain shared by the terrain and biome related stages.
+    pub terrain: TerrainConfig,
+
+    /// The trees that can be referenced by the tree stages.
+    pub trees: Vec<TreeConfig>,
+
+    /// The chunk stages, in the order they are added to the pipeline.
+    pub stages: Vec<StageConfig>,
+}
+
+/// Description of a `Terrain`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TerrainConfig {
+    /// Terrain layers that decide the biomes and the terrain shape.
+    pub layers: Vec<TerrainLayerConfig>,
+
+    /// Terrain layers that only affect the terrain shape.
+    pub noise_layers: Vec<TerrainLayerConfig>,
+
+    /// 3D noise layers added on top of the 2D terrain density.
+    pub density_layers: Vec<DensityLayerConfig>,
+
+    /// Biomes placed at points in the space of the terrain layer values.
+    pub biomes: Vec<BiomeConfig>,
+
+    /// Number of nearest biomes to blend together. Defaults to 1, which is no blending.
+    pub biome_blend: Option<usize>,
+
+    /// How fast a blended biome's weight falls off. Defaults to 8.0.
+    pub biome_falloff: Option<f64>,
+}
+
+/// Description of a `TerrainLayer`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TerrainLayerConfig {
+    pub name: String,
+
+    #[serde(default = "default_noise")]
+    pub noise: NoiseOptions,
+
+    /// A noise graph to use instead of the multi-fractal `noise` options.
+    #[serde(default)]
+    pub source: Option<NoiseNode>,
+
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+
+    #[serde(default)]
+    pub bias: Vec<[f64; 2]>,
+
+    #[serde(default)]
+    pub offset: Vec<[f64; 2]>,
+}
+
+/// Description of a 3D density layer of a `Terrain`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DensityLayerConfig {
+    pub source: NoiseNode,
+
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+/// Description of a `Biome`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BiomeConfig {
+    pub name: String,
+
+    /// The point of this biome, one value per terrain layer.
+    pub point: Vec<f64>,
+
+    #[serde(default)]
+    pub test_block: String,
+
+    #[serde(default)]
+    pub surface: Option<String>,
+
+    #[serde(default)]
+    pub subsurface: Option<String>,
+
+    #[serde(default = "default_subsurface_depth")]
+    pub subsurface_depth: u32,
+
+    #[serde(default)]
+    pub bias: Vec<[f64; 2]>,
+
+    #[serde(default)]
+    pub offset: Vec<[f64; 2]>,
+}
+
+/// Description of a `Tree` and its `LSystem`. Unset fields use the `TreeBuilder` defaults.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TreeConfig {
+    pub name: String,
+    pub leaf: String,
+    pub trunk: String,
+    pub leaf_radius: Option<i32>,
+    pub leaf_height: Option<i32>,
+    pub branch_initial_radius: Option<i32>,
+    pub branch_min_radius: Option<i32>,
+    pub branch_radius_factor: Option<f64>,
+    pub branch_initial_length: Option<i32>,
+    pub branch_min_length: Option<i32>,
+    pub branch_length_factor: Option<f64>,
+    pub branch_dy_angle: Option<f64>,
+    pub branch_drot_angle: Option<f64>,
+    pub system: Option<LSystemConfig>,
+}
+
+/// Description of an `LSystem`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LSystemConfig {
+    pub axiom: String,
+    pub rules: HashMap<char, String>,
+    pub iterations: u32,
+}
+
+/// Description of a chunk stage, tagged by its `type`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StageConfig {
+    /// A `FlatlandStage`.
+    Flatland(FlatlandConfig),
+
+    /// A `BaseTerrainStage` filled with the base block.
+    BaseTerrain(BaseTerrainConfig),
+
+    /// A `BiomeSurfaceStage` applying the biome surface rules.
+    BiomeSurface,
+
+    /// A `StructureStage` planting the trees.
+    Trees(TreesConfig),
+
+    /// A `StructureStage` pasting schematics.
+    Structures(StructuresConfig),
+
+    /// A `CaveStage`.
+    Caves(CavesConfig),
+
+    /// An `OreStage`.
+    Ores(OresConfig),
+}
+
+/// Description of a `FlatlandStage`, with the soiling layers from the bottom up.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FlatlandConfig {
+    pub soiling: Vec<SoilingConfig>,
+}
+
+/// A layer of a `FlatlandStage`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SoilingConfig {
+    pub block: String,
+    pub height: usize,
+}
+
+/// Description of a `BaseTerrainStage`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BaseTerrainConfig {
+    pub base: String,
+
+    #[serde(default)]
+    pub threshold: f64,
+}
+
+/// Description of how structures are placed. See `StructurePlacement`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PlacementConfig {
+    Noise {
+        #[serde(default = "default_noise")]
+        noise: NoiseOptions,
+        threshold: f64,
+    },
+    Grid {
+        spacing: i32,
+        #[serde(default)]
+        jitter: i32,
+        #[serde(default = "default_chance")]
+        chance: f64,
+    },
+}
+
+/// Description of a tree planting stage.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TreesConfig {
+    pub placement: PlacementConfig,
+
+    /// Names of the trees to plant, from `WorldGenConfig::trees`.
+    pub trees: Vec<String>,
+
+    #[serde(default)]
+    pub ground: Vec<String>,
+
+    #[serde(default)]
+    pub biomes: Vec<String>,
+}
+
+/// Description of a schematic pasting stage.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuresConfig {
+    pub placement: PlacementConfig,
+    pub structures: Vec<StructureConfig>,
+}
+
+/// Description of a schematic `Structure`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureConfig {
+    pub name: String,
+
+    /// Path to the schematic file, relative to the working directory.
+    pub schematic: String,
+
+    #[serde(default = "default_structure_weight")]
+    pub weight: u32,
+
+    #[serde(default = "default_structure_offset")]
+    pub offset: [i32; 3],
+
+    #[serde(default)]
+    pub ground: Vec<String>,
+
+    #[serde(default)]
+    pub biomes: Vec<String>,
+
+    #[serde(default)]
+    pub random_rotation: bool,
+}
+
+/// Description of a `CaveStage`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CavesConfig {
+    pub cheese: Option<CheeseCavesConfig>,
+    pub worms: Option<WormCavesConfig>,
+    pub min_height: i32,
+    pub max_height: i32,
+    pub surface_margin: i32,
+    pub fluid: Option<String>,
+}
+
+impl Default for CavesConfig {
+    fn default() -> Self {
+        Self {
+            cheese: None,
+            worms: None,
+            min_height: 1,
+            max_height: i32::MAX,
+            surface_margin: 0,
+            fluid: None,
+        }
+    }
+}
+
+/// Description of the cheese caves of a `CaveStage`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CheeseCavesConfig {
+    #[serde(default = "default_noise")]
+    pub noise: NoiseOptions,
+    pub threshold: f64,
+}
+
+/// Description of the worm caves of a `CaveStage`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WormCavesConfig {
+    #[serde(default = "default_noise")]
+    pub noise: NoiseOptions,
+    pub thickness: f64,
+}
+
+/// Description of an `OreStage`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OresConfig {
+    pub ores: Vec<OreConfig>,
+}
+
+/// Description of an `Ore`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OreConfig {
+    pub block: String,
+    pub min_height: i32,
+    pub max_height: i32,
+    pub size: usize,
+    pub frequency: f64,
+    pub replaceable: Vec<String>,
+    pub biomes: Vec<String>,
+}
+
+impl Default for OreConfig {
+    fn default() -> Self {
+        Self {
+            block: String::new(),
+            min_height: 0,
+            max_height: i32::MAX,
+            size: 8,
+            frequency: 1.0,
+            replaceable: vec![],
+            biomes: vec![],
+        }
+    }
+}
+
+fn default_noise() -> NoiseOptions {
+    NoiseOptions::new().build()
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_subsurface_depth() -> u32 {
+    3
+}
+
+fn default_chance() -> f64 {
+    1.0
+}
+
+fn default_structure_weight() -> u32 {
+    1
+}
+
+fn default_structure_offset() -> [i32; 3] {
+    [0, 1, 0]
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Get the ID of a block by name, erroring instead of panicking if the block does not exist.
+fn block_id(registry: &Registry, name: &str) -> io::Result<u32> {
+    if !registry.blocks_by_name.contains_key(&name.to_lowercase()) {
+        return Err(invalid(format!(
+            "Unknown block in world generation: {}",
+            name
+        )));
+    }
+
+    Ok(registry.get_id_by_name(name))
+}
+
+fn names(names: &[String]) -> Vec<&str> {
+    names.iter().map(|name| name.as_str()).collect()
+}
+
+impl WorldGenConfig {
+    /// Parse a world generation description from a JSON string.
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a world generation description from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Build the terrain described by this configuration.
+    pub fn build_terrain(&self, config: &WorldConfig) -> Terrain {
+        let mut terrain = Terrain::new(config);
+
+        let to_layer = |layer: &TerrainLayerConfig| {
+            if let Some(source) = &layer.source {
+                TerrainLayer::from_node(&layer.name, source)
+            } else {
+                TerrainLayer::new(&layer.name, &layer.noise)
+            }
+            .add_bias_points(&layer.bias)
+            .add_offset_points(&layer.offset)
+        };
+
+        self.terrain.layers.iter().for_each(|layer| {
+            terrain.add_layer(&to_layer(layer), layer.weight);
+        });
+
+        self.terrain.noise_layers.iter().for_each(|layer| {
+            terrain.add_noise_layer(&to_layer(layer), layer.weight);
+        });
+
+        self.terrain.density_layers.iter().for_each(|layer| {
+            terrain.add_density_layer(&layer.source, layer.weight);
+        });
+
+        self.terrain.biomes.iter().for_each(|biome| {
+            let mut instance = Biome::new(&biome.name, &biome.test_block);
+
+            if let Some(surface) = &biome.surface {
+                instance = instance.surface(surface);
+            }
+
+            if let Some(subsurface) = &biome.subsurface {
+                instance = instance.subsurface(subsurface, biome.subsurface_depth);
+            }
+
+            if !biome.bias.is_empty() {
+                instance = instance.add_bias_points(&biome.bias);
+            }
+
+            if !biome.offset.is_empty() {
+                instance = instance.add_offset_points(&biome.offset);
+            }
+
+            terrain.add_biome(&biome.point, instance);
+        });
+
+        if self.terrain.biome_blend.is_some() || self.terrain.biome_falloff.is_some() {
+            terrain.set_biome_blend(
+                self.terrain.biome_blend.unwrap_or(1),
+                self.terrain.biome_falloff.unwrap_or(8.0),
+            );
+        }
+
+        terrain
+    }
+
+    /// Build the trees described by this configuration, registered under their names.
+    pub fn build_trees(&self, config: &WorldConfig, registry: &Registry) -> io::Result<Trees> {
+        let mut trees = Trees::new(config.seed, &NoiseOptions::new().build());
+
+        for tree in self.trees.iter() {
+            let mut builder = Tree::new(
+                block_id(registry, &tree.leaf)?,
+                block_id(registry, &tree.trunk)?,
+            );
+
+            if let Some(value) = tree.leaf_radius {
+                builder = builder.leaf_radius(value);
+            }
+            if let Some(value) = tree.leaf_height {
+                builder = builder.leaf_height(value);
+            }
+            if let Some(value) = tree.branch_initial_radius {
+                builder = builder.branch_initial_radius(value);
+            }
+            if let Some(value) = tree.branch_min_radius {
+                builder = builder.branch_min_radius(value);
+            }
+            if let Some(value) = tree.branch_radius_factor {
+                builder = builder.branch_radius_factor(value);
+            }
+            if let Some(value) = tree.branch_initial_length {
+                builder = builder.branch_initial_length(value);
+            }
+            if let Some(value) = tree.branch_min_length {
+                builder = builder.branch_min_length(value);
+            }
+            if let Some(value) = tree.branch_length_factor {
+                builder = builder.branch_length_factor(value);
+            }
+            if let Some(value) = tree.branch_dy_angle {
+                builder = builder.branch_dy_angle(value);
+            }
+            if let Some(value) = tree.branch_drot_angle {
+                builder = builder.branch_drot_angle(value);
+            }
+
+            if let Some(system) = &tree.system {
+                let mut lsystem = LSystem::new()
+                    .axiom(&system.axiom)
+                    .iterations(system.iterations);
+
+                for (key, value) in system.rules.iter() {
+                    lsystem = lsystem.rule(*key, value);
+                }
+
+                builder = builder.system(lsystem.build());
+            }
+
+            trees.register(&tree.name, builder.build());
+        }
+
+        Ok(trees)
+    }
+
+    /// Check that every block referenced by the biomes and structure grounds is in the registry. Blocks
+    /// that are resolved while building their stage, such as ores, are checked there.
+    fn validate_blocks(&self, registry: &Registry) -> io::Result<()> {
+        for biome in self.terrain.biomes.iter() {
+            if !biome.test_block.is_empty() {
+                block_id(registry, &biome.test_block)?;
+            }
+
+            for name in biome.surface.iter().chain(biome.subsurface.iter()) {
+                block_id(registry, name)?;
+            }
+        }
+
+        for stage in self.stages.iter() {
+            let names = match stage {
+                StageConfig::Trees(trees) => trees.ground.iter().collect::<Vec<_>>(),
+                StageConfig::Structures(structures) => structures
+                    .structures
+                    .iter()
+                    .flat_map(|structure| structure.ground.iter())
+                    .collect(),
+                _ => vec![],
+            };
+
+            for name in names {
+                block_id(registry, name)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that every biome has a point with one value per terrain layer, as the biomes are looked up by
+    /// the layers' values.
+    fn validate_biomes(&self) -> io::Result<()> {
+        let layers = self.terrain.layers.len();
+
+        if layers == 0 && !self.terrain.biomes.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Biomes need at least one terrain layer to be looked up by",
+            ));
+        }
+
+        for biome in self.terrain.biomes.iter() {
+            if biome.point.len() != layers {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Biome {} has a point of {} values, but there are {} terrain layers",
+                        biome.name,
+                        biome.point.len(),
+                        layers
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the chunk stages described by this configuration, in order. Errors if any referenced block
+    /// is not in the registry, if a biome's point does not match the terrain layers, or if trees or structures
+    /// are limited to biomes but the terrain has none.
+    pub fn build_stages(
+        &self,
+        config: &WorldConfig,
+        registry: &Registry,
+    ) -> io::Result<Vec<Arc<dyn ChunkStage + Send + Sync>>> {
+        self.validate_blocks(registry)?;
+        self.validate_biomes()?;
+
+        let terrain = self.build_terrain(config);
+
+        // Biome filters are only available when the terrain has biomes to look up.
+        let biome_terrain = if self.terrain.biomes.is_empty() {
+            None
+        } else {
+            Some(terrain.clone())
+        };
+        let trees = Arc::new(self.build_trees(config, registry)?);
+
+        let placement = |placement: &PlacementConfig| match placement {
+            PlacementConfig::Noise { noise, threshold } => StructurePlacement::Noise {
+                options: noise.to_owned(),
+                threshold: *threshold,
+            },
+            PlacementConfig::Grid {
+                spacing,
+                jitter,
+                chance,
+            } => StructurePlacement::Grid {
+                spacing: *spacing,
+                jitter: *jitter,
+                chance: *chance,
+            },
+        };
+
+        let mut stages: Vec<Arc<dyn ChunkStage + Send + Sync>> = vec![];
+
+        for stage in self.stages.iter() {
+            match stage {
+                StageConfig::Flatland(flatland) => {
+                    let mut stage = FlatlandStage::new();
+
+                    for soiling in flatland.soiling.iter() {
+                        stage =
+                            stage.add_soiling(block_id(registry, &soiling.block)?, soiling.height);
+                    }
+
+                    stages.push(Arc::new(stage));
+                }
+                StageConfig::BaseTerrain(base_terrain) => {
+                    let mut stage = BaseTerrainStage::new(terrain.clone());
+                    stage.set_base(block_id(registry, &base_terrain.base)?);
+                    stage.set_threshold(base_terrain.threshold);
+
+                    stages.push(Arc::new(stage));
+                }
+                StageConfig::BiomeSurface => {
+                    stages.push(Arc::new(BiomeSurfaceStage::new(terrain.clone(), registry)?));
+                }
+                StageConfig::Trees(trees_config) => {
+                    let mut stage =
+                        StructureStage::new(config.seed, placement(&trees_config.placement))
+                            .name("Trees");
+
+                    if let Some(terrain) = &biome_terrain {
+                        stage = stage.terrain(terrain.clone());
+                    }
+
+                    for name in trees_config.trees.iter() {
+                        if !self.trees.iter().any(|tree| &tree.name == name) {
+                            return Err(invalid(format!(
+                                "Unknown tree in world generation: {}",
+                                name
+                            )));
+                        }
+
+                        let trees = trees.clone();
+                        let tree = name.to_owned();
+
+                        stage = stage.add_structure(
+                            Structure::new(name)
+                                .generator(move |at, _| trees.generate(&tree, at))
+                                .offset(Vec3(0, 0, 0))
+                                .ground(&names(&trees_config.ground))
+                                .biomes(&names(&trees_config.biomes))
+                                .build(),
+                        )?;
+                    }
+
+                    stages.push(Arc::new(stage));
+                }
+                StageConfig::Structures(structures) => {
+                    let mut stage =
+                        StructureStage::new(config.seed, placement(&structures.placement));
+
+                    if let Some(terrain) = &biome_terrain {
+                        stage = stage.terrain(terrain.clone());
+                    }
+
+                    for structure in structures.structures.iter() {
+                        let [x, y, z] = structure.offset;
+
+                        stage = stage.add_structure(
+                            Structure::new(&structure.name)
+                                .schematic(Schematic::load(&structure.schematic)?)
+                                .weight(structure.weight)
+                                .offset(Vec3(x, y, z))
+                                .ground(&names(&structure.ground))
+                                .biomes(&names(&structure.biomes))
+                                .random_rotation(structure.random_rotation)
+                                .build(),
+                        )?;
+                    }
+
+                    stages.push(Arc::new(stage));
+                }
+                StageConfig::Caves(caves) => {
+                    let mut stage = CaveStage::new()
+                        .depth(caves.min_height, caves.max_height)
+                        .surface_margin(caves.surface_margin);
+
+                    if let Some(cheese) = &caves.cheese {
+                        stage = stage.cheese(config.seed, &cheese.noise, cheese.threshold);
+                    }
+
+                    if let Some(worms) = &caves.worms {
+                        stage = stage.worms(config.seed, &worms.noise, worms.thickness);
+                    }
+
+                    if let Some(fluid) = &caves.fluid {
+                        stage = stage.fluid(block_id(registry, fluid)?);
+                    }
+
+                    stages.push(Arc::new(stage));
+                }
+                StageConfig::Ores(ores) => {
+                    let mut stage = OreStage::new();
+
+                    if let Some(terrain) = &biome_terrain {
+                        stage = stage.terrain(terrain.clone());
+                    }
+
+                    for ore in ores.ores.iter() {
+                        stage = stage.add_ore(
+                            Ore::new(&ore.block)
+                                .height_range(ore.min_height, ore.max_height)
+                                .size(ore.size)
+                                .frequency(ore.frequency)
+                                .replaceable(&names(&ore.replaceable))
+                                .biomes(&names(&ore.biomes))
+                                .build(),
+                            registry,
+                        )?;
+                    }
+
+                    stages.push(Arc::new(stage));
+                }
+            }
+        }
+
+        Ok(stages)
+    }
+
+    /// Add the chunk stages described by this configuration to the end of a pipeline.
+    pub fn apply(
+        &self,
+        pipeline: &mut Pipeline,
+        config: &WorldConfig,
+        registry: &Registry,
+    ) -> io::Result<()> {
+        pipeline.stages.extend(self.build_stages(config, registry)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use specs::Entity;
+
+use crate::{Vec2, Vec3};
+
+/// Something that happened in the world, for game logic to react to through `World::add_world_event_handle`,
+/// or for systems to read from the `WorldEvents` resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldEvent {
+    /// A non-client entity was added to the world, spawned or revived.
+    EntitySpawned {
+        entity: Entity,
+        id: String,
+        etype: String,
+    },
+
+    /// A non-client entity was removed from the world. The entity is already deleted.
+    EntityDespawned { entity: Entity, id: String },
+
+    /// An entity moved into another chunk. `from` is `None` for entities that were just added.
+    ChunkChanged {
+        entity: Entity,
+        from: Option<Vec2<i32>>,
+        to: Vec2<i32>,
+    },
+
+    /// A client joined the world.
+    ClientJoined { entity: Entity, id: String },
+
+    /// A client left the world. The entity is already deleted.
+    ClientLeft { entity: Entity, id: String },
+
+    /// A voxel was changed by a voxel update, from one block ID to another.
+    VoxelChanged {
+        voxel: Vec3<i32>,
+        from: u32,
+        to: u32,
+    },
+}
+
+/// The world events of the current tick, handed to the world event handles at the end of the tick.
+#[derive(Default)]
+pub struct WorldEvents {
+    queue: Vec<WorldEvent>,
+}
+
+impl WorldEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an event, to be handled at the end of the tick.
+    pub fn push(&mut self, event: WorldEvent) {
+        self.queue.push(event);
+    }
+
+    /// The events recorded so far this tick.
+    pub fn iter(&self) -> impl Iterator<Item = &WorldEvent> {
+        self.queue.iter()
+    }
+
+    /// Take the events recorded so far.
+    pub fn drain(&mut self) -> Vec<WorldEvent> {
+        std::mem::take(&mut self.queue)
+    }
+}
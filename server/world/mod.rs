@@ -1,4 +1,5 @@
 mod bookkeeping;
+mod brains;
 mod clients;
 mod components;
 mod config;
@@ -6,7 +7,9 @@ mod entities;
 mod events;
 mod generators;
 mod interests;
+mod lifecycle;
 mod messages;
+mod pathfinding;
 mod physics;
 mod registry;
 mod search;
@@ -30,7 +33,7 @@ use specs::{
 };
 use std::fs::{self, File};
 use std::path::PathBuf;
-use std::{env, sync::Arc};
+use std::{env, sync::Arc, time::Instant};
 
 use crate::{
     encode_message,
@@ -42,6 +45,7 @@ use crate::{
 use super::common::ClientFilter;
 
 pub use bookkeeping::*;
+pub use brains::*;
 pub use clients::*;
 pub use components::*;
 pub use config::*;
@@ -49,7 +53,9 @@ pub use entities::*;
 pub use events::*;
 pub use generators::*;
 pub use interests::*;
+pub use lifecycle::*;
 pub use messages::*;
+pub use pathfinding::*;
 pub use physics::*;
 pub use registry::*;
 pub use search::*;
@@ -61,23 +67,34 @@ pub use voxels::*;
 
 pub type Transports = HashMap<String, Recipient<EncodedMessage>>;
 
+/// A handler for the `WorldEvent`s, added with `World::add_world_event_handle`.
+type WorldEventHandle = Arc<dyn Fn(&mut World, &WorldEvent)>;
+
 /// The default client metadata parser, parses PositionComp and DirectionComp, and updates RigidBodyComp.
 pub fn default_client_parser(world: &mut World, metadata: &str, client_ent: Entity) {
     let metadata: PeerUpdate =
         serde_json::from_str(metadata).expect("Could not parse peer update.");
 
     if let Some(position) = metadata.position {
-        {
-            let mut positions = world.write_component::<PositionComp>();
-            if let Some(p) = positions.get_mut(client_ent) {
-                p.0.set(position.0, position.1, position.2);
+        // Under authoritative movement, the position is only a target for the server to validate.
+        if world.config().authoritative_movement {
+            let mut movements = world.write_component::<ClientMovementComp>();
+            if let Some(m) = movements.get_mut(client_ent) {
+                m.target = Some(position);
+            }
+        } else {
+            {
+                let mut positions = world.write_component::<PositionComp>();
+                if let Some(p) = positions.get_mut(client_ent) {
+                    p.0.set(position.0, position.1, position.2);
+                }
             }
-        }
 
-        {
-            let mut bodies = world.write_component::<RigidBodyComp>();
-            if let Some(b) = bodies.get_mut(client_ent) {
-                b.0.set_position(position.0, position.1, position.2);
+            {
+                let mut bodies = world.write_component::<RigidBodyComp>();
+                if let Some(b) = bodies.get_mut(client_ent) {
+                    b.0.set_position(position.0, position.1, position.2);
+                }
             }
         }
     }
@@ -113,6 +130,15 @@ pub struct World {
     /// The progress of preloading.
     pub preload_progress: f32,
 
+    /// The preload chunks that are not ready yet.
+    preload_remaining: Vec<Vec2<i32>>,
+
+    /// The total number of preload chunks.
+    preload_total: usize,
+
+    /// When the preloading started.
+    preload_started: Instant,
+
     /// Entity component system world.
     ecs: ECSWorld,
 
@@ -139,15 +165,26 @@ pub struct World {
 
     /// A map to spawn and create entities.
     entity_loaders: HashMap<String, Arc<dyn Fn(&mut World, MetadataComp) -> EntityBuilder>>,
+
+    /// The handlers for `WorldEvent`s.
+    world_event_handles: Vec<WorldEventHandle>,
 }
 
+/// How far past the ray the search for entities to raycast against reaches, to account for their sizes.
+const RAYCAST_ENTITY_PADDING: f32 = 4.0;
+
 fn dispatcher() -> DispatcherBuilder<'static, 'static> {
     DispatcherBuilder::new()
         .with(UpdateStatsSystem, "update-stats", &[])
         .with(EntitiesMetaSystem, "entities-meta", &[])
         .with(PeersMetaSystem, "peers-meta", &[])
         .with(CurrentChunkSystem, "current-chunk", &[])
-        .with(ChunkUpdatingSystem, "chunk-updating", &["current-chunk"])
+        .with(RandomTickSystem, "random-tick", &["current-chunk"])
+        .with(
+            ChunkUpdatingSystem,
+            "chunk-updating",
+            &["current-chunk", "random-tick"],
+        )
         .with(ChunkRequestsSystem, "chunk-requests", &["current-chunk"])
         .with(
             ChunkGeneratingSystem,
@@ -156,7 +193,30 @@ fn dispatcher() -> DispatcherBuilder<'static, 'static> {
         )
         .with(ChunkSendingSystem, "chunk-sending", &["chunk-generation"])
         .with(ChunkSavingSystem, "chunk-saving", &["chunk-generation"])
-        .with(PhysicsSystem, "physics", &["current-chunk", "update-stats"])
+        .with(
+            ClientMovementSystem,
+            "client-movement",
+            &["current-chunk", "update-stats"],
+        )
+        .with(BrainSystem, "brains", &["current-chunk"])
+        .with(
+            PathfindingSystem,
+            "pathfinding",
+            &["current-chunk", "brains"],
+        )
+        .with(
+            PhysicsSystem,
+            "physics",
+            &[
+                "current-chunk",
+                "update-stats",
+                "client-movement",
+                "chunk-updating",
+                "pathfinding",
+            ],
+        )
+        .with(AttachmentSystem, "attachments", &["physics"])
+        .with(SearchSystem, "search", &["physics", "attachments"])
         .with(DataSavingSystem, "entities-saving", &["entities-meta"])
         .with(
             EntitiesSendingSystem,
@@ -226,6 +286,10 @@ impl World {
         ecs.register::<AddrComp>();
         ecs.register::<InteractorComp>();
         ecs.register::<CollisionsComp>();
+        ecs.register::<ClientMovementComp>();
+        ecs.register::<AttachmentComp>();
+        ecs.register::<PathComp>();
+        ecs.register::<BrainComp>();
 
         ecs.insert(name.to_owned());
         ecs.insert(config.clone());
@@ -240,7 +304,10 @@ impl World {
         ecs.insert(Clients::new());
         ecs.insert(MessageQueue::new());
         ecs.insert(Physics::new());
+        ecs.insert(Pathfinder::new());
+        ecs.insert(Spawner::new());
         ecs.insert(Events::new());
+        ecs.insert(WorldEvents::new());
         ecs.insert(Transports::new());
         ecs.insert(ChunkInterests::new());
         ecs.insert(Bookkeeping::new());
@@ -251,6 +318,9 @@ impl World {
             started: false,
             preloading: false,
             preload_progress: 0.0,
+            preload_remaining: vec![],
+            preload_total: 0,
+            preload_started: Instant::now(),
 
             ecs,
 
@@ -258,6 +328,7 @@ impl World {
             method_handles: HashMap::default(),
             event_handles: HashMap::default(),
             entity_loaders: HashMap::default(),
+            world_event_handles: vec![],
             client_parser: Arc::new(default_client_parser),
             client_modifier: None,
             transport_handle: None,
@@ -370,6 +441,7 @@ impl World {
             .with(RigidBodyComp::new(&body))
             .with(InteractorComp::new(&interactor))
             .with(CollisionsComp::new())
+            .with(ClientMovementComp::new())
             .build();
 
         if let Some(modifier) = self.client_modifier.to_owned() {
@@ -391,6 +463,12 @@ impl World {
         let join_message = Message::new(&MessageType::Join).text(id).build();
         self.broadcast(join_message, ClientFilter::All);
 
+        self.write_resource::<WorldEvents>()
+            .push(WorldEvent::ClientJoined {
+                entity: ent,
+                id: id.to_owned(),
+            });
+
         info!("Client at {} joined the server to world: {}", id, self.name);
     }
 
@@ -414,6 +492,13 @@ impl World {
 
             let leave_message = Message::new(&MessageType::Leave).text(&client.id).build();
             self.broadcast(leave_message, ClientFilter::All);
+
+            self.write_resource::<WorldEvents>()
+                .push(WorldEvent::ClientLeft {
+                    entity: client.entity,
+                    id: client.id.to_owned(),
+                });
+
             info!("Client at {} left the world: {}", id, self.name);
         }
     }
@@ -459,6 +544,12 @@ impl World {
         self.command_handle = Some(Arc::new(handle));
     }
 
+    /// Add a handler for the `WorldEvent`s, called with each event at the end of the tick it happened in. Clients
+    /// join and leave between ticks, so their events are handled at the end of the next tick.
+    pub fn add_world_event_handle<F: Fn(&mut World, &WorldEvent) + 'static>(&mut self, handle: F) {
+        self.world_event_handles.push(Arc::new(handle));
+    }
+
     pub fn set_entity_loader<F: Fn(&mut World, MetadataComp) -> EntityBuilder + 'static>(
         &mut self,
         etype: &str,
@@ -599,6 +690,44 @@ impl World {
         self.write_resource::<Pipeline>()
     }
 
+    /// Replace the chunk stages with the ones from a world generation description. This only happens
+    /// if no chunks are loaded or being generated, and returns whether the stages were replaced.
+    pub fn reload_worldgen(&mut self, worldgen: &WorldGenConfig) -> std::io::Result<bool> {
+        let is_idle = {
+            let pipeline = self.pipeline();
+            self.chunks().map.is_empty() && pipeline.queue.is_empty() && pipeline.chunks.is_empty()
+        };
+
+        if !is_idle {
+            warn!("World generation not reloaded, chunks are already loaded.");
+            return Ok(false);
+        }
+
+        let stages = worldgen.build_stages(&self.config(), &self.registry())?;
+        let started = self.started;
+
+        let mut pipeline = self.pipeline_mut();
+        pipeline.stages = stages;
+        pipeline.leftovers.clear();
+
+        // Stages are merged when the world is prepared, so merge again if that has already happened.
+        if started {
+            pipeline.merge_stages();
+        }
+
+        Ok(true)
+    }
+
+    /// Access the natural entity spawner in the ECS world.
+    pub fn spawner(&self) -> Fetch<Spawner> {
+        self.read_resource::<Spawner>()
+    }
+
+    /// Access a mutable natural entity spawner in the ECS world.
+    pub fn spawner_mut(&mut self) -> FetchMut<Spawner> {
+        self.write_resource::<Spawner>()
+    }
+
     /// Access the mesher in the ECS world.
     pub fn mesher(&self) -> Fetch<Mesher> {
         self.read_resource::<Mesher>()
@@ -609,6 +738,133 @@ impl World {
         self.write_resource::<Mesher>()
     }
 
+    /// Find a path through the loaded voxels of the world right away, for an entity with its feet at `start`.
+    /// Use `PathComp` to find paths in the background instead.
+    pub fn find_path(
+        &self,
+        start: &Vec3<i32>,
+        goal: &Vec3<i32>,
+        options: &PathOptions,
+    ) -> Option<Path> {
+        find_path(&*self.chunks(), &self.registry(), start, goal, options)
+    }
+
+    /// Cast a ray through the loaded voxels of the world, returning the first voxel hit.
+    pub fn raycast_voxels(
+        &self,
+        origin: &Vec3<f32>,
+        direction: &Vec3<f32>,
+        max_distance: f32,
+    ) -> Option<VoxelRaycastHit> {
+        raycast(
+            &*self.chunks(),
+            &self.registry(),
+            origin,
+            direction,
+            max_distance,
+        )
+    }
+
+    /// Cast a ray against the bounding boxes of the clients and entities in the search tree, returning the
+    /// closest hit other than `exclude`. The search tree is rebuilt by the `SearchSystem` every tick.
+    pub fn raycast_entities(
+        &self,
+        origin: &Vec3<f32>,
+        direction: &Vec3<f32>,
+        max_distance: f32,
+        exclude: Option<Entity>,
+    ) -> Option<EntityRaycastHit> {
+        let &Vec3(dx, dy, dz) = direction;
+        let ds = (dx * dx + dy * dy + dz * dz).sqrt();
+
+        if ds == 0.0 {
+            return None;
+        }
+
+        let direction = Vec3(dx / ds, dy / ds, dz / ds);
+        let half = max_distance / 2.0;
+        let center = Vec3(
+            origin.0 + direction.0 * half,
+            origin.1 + direction.1 * half,
+            origin.2 + direction.2 * half,
+        );
+
+        // Entity positions are the centers of their bodies, so widen the search to reach their edges.
+        let radius = half + RAYCAST_ENTITY_PADDING;
+
+        let search = self.search();
+        let bodies = self.read_component::<RigidBodyComp>();
+
+        search
+            .within(&center, radius * radius)
+            .into_iter()
+            .filter(|(_, ent)| Some(**ent) != exclude)
+            .filter_map(|(_, ent)| {
+                let body = bodies.get(*ent)?;
+                let (axis, distance) =
+                    raycast_aabb(origin, &direction, &body.0.aabb, max_distance)?;
+                let sign = |d: f32| if d > 0.0 { -1 } else { 1 };
+
+                Some(EntityRaycastHit {
+                    entity: *ent,
+                    point: Vec3(
+                        origin.0 + distance * direction.0,
+                        origin.1 + distance * direction.1,
+                        origin.2 + distance * direction.2,
+                    ),
+                    normal: Vec3(
+                        if axis == 0 { sign(direction.0) } else { 0 },
+                        if axis == 1 { sign(direction.1) } else { 0 },
+                        if axis == 2 { sign(direction.2) } else { 0 },
+                    ),
+                    distance,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Cast a ray through both the voxels and the entities of the world, returning the closest hit.
+    pub fn raycast(
+        &self,
+        origin: &Vec3<f32>,
+        direction: &Vec3<f32>,
+        max_distance: f32,
+        exclude: Option<Entity>,
+    ) -> Option<RaycastHit> {
+        let voxel = self
+            .raycast_voxels(origin, direction, max_distance)
+            .map(RaycastHit::Voxel);
+        let entity = self
+            .raycast_entities(origin, direction, max_distance, exclude)
+            .map(RaycastHit::Entity);
+
+        match (voxel, entity) {
+            (Some(voxel), Some(entity)) => Some(if entity.distance() < voxel.distance() {
+                entity
+            } else {
+                voxel
+            }),
+            (voxel, entity) => voxel.or(entity),
+        }
+    }
+
+    /// Cast a ray from the position of an entity towards the direction it is looking at, such as finding
+    /// what a client is looking at. The entity itself is never hit.
+    pub fn raycast_from(&self, entity: Entity, max_distance: f32) -> Option<RaycastHit> {
+        let origin = self
+            .read_component::<PositionComp>()
+            .get(entity)?
+            .0
+            .to_owned();
+        let direction = self
+            .read_component::<DirectionComp>()
+            .get(entity)?
+            .0
+            .to_owned();
+
+        self.raycast(&origin, &direction, max_distance, Some(entity))
+    }
+
     /// Create a basic entity ready to be added more.
     pub fn create_entity(&mut self, id: &str, etype: &str) -> EntityBuilder {
         self.ecs_mut()
@@ -621,6 +877,39 @@ impl World {
             .with(CollisionsComp::new())
     }
 
+    /// Attach an entity to the parent in the attachment, moving it along with the parent from then on. Replaces
+    /// the entity's previous attachment, if any.
+    pub fn attach_entity(&mut self, entity: Entity, attachment: AttachmentComp) {
+        self.detach_entity(entity);
+
+        self.ecs_mut()
+            .write_storage::<AttachmentComp>()
+            .insert(entity, attachment)
+            .expect("Failed to insert attachment component");
+    }
+
+    /// Detach an entity from its parent, returning the attachment it had.
+    pub fn detach_entity(&mut self, entity: Entity) -> Option<AttachmentComp> {
+        let attachment = self
+            .ecs_mut()
+            .write_storage::<AttachmentComp>()
+            .remove(entity)?;
+
+        if let Some(handle) = &attachment.handle {
+            self.physics_mut().detach(handle);
+        }
+
+        if let Some(metadata) = self
+            .ecs_mut()
+            .write_storage::<MetadataComp>()
+            .get_mut(entity)
+        {
+            metadata.map.remove("attachment");
+        }
+
+        Some(attachment)
+    }
+
     /// Spawn an entity of type at a location.
     pub fn spawn_entity_at(&mut self, etype: &str, position: &Vec3<f32>) -> Option<Entity> {
         if !self.entity_loaders.contains_key(&etype.to_lowercase()) {
@@ -691,6 +980,14 @@ impl World {
             .insert(ent, CollisionsComp::new())
             .expect("Failed to insert collisions component");
 
+        // Restore the attachment to the parent, which joins the bodies again once the parent is loaded too.
+        if let Some(attachment) = metadata.get::<AttachmentComp>("attachment") {
+            self.ecs_mut()
+                .write_storage::<AttachmentComp>()
+                .insert(ent, attachment)
+                .expect("Failed to insert attachment component");
+        }
+
         self.ecs_mut()
             .write_storage::<MetadataComp>()
             .insert(ent, metadata)
@@ -719,32 +1016,32 @@ impl World {
         }
     }
 
-    /// Preload the chunks in the world.
+    /// Preload the chunks in the world, enqueuing every preload chunk and the chunks its light can reach.
     pub(crate) fn preload(&mut self) {
-        let radius = self.config().preload_radius as i32;
+        let targets = self.config().preload_chunks();
+
+        let neighbors = {
+            let chunks = self.chunks();
+
+            targets
+                .iter()
+                .flat_map(|coords| chunks.light_traversed_chunks(coords))
+                .collect::<Vec<_>>()
+        };
 
         {
-            for x in -radius..=radius {
-                for z in -radius..=radius {
-                    let coords = Vec2(x, z);
-                    let neighbors = self.chunks().light_traversed_chunks(&coords);
-
-                    neighbors.into_iter().for_each(|coords| {
-                        let is_within = {
-                            let chunks = self.chunks();
-                            chunks.is_within_world(&coords)
-                        };
-
-                        let mut pipeline = self.pipeline_mut();
-                        if is_within {
-                            pipeline.add_chunk(&coords, false);
-                        }
-                    });
-                }
-            }
+            let mut pipeline = self.pipeline_mut();
+
+            neighbors
+                .iter()
+                .for_each(|coords| pipeline.add_chunk(coords, false));
         }
 
+        self.preload_total = targets.len();
+        self.preload_remaining = targets;
+        self.preload_progress = 0.0;
         self.preloading = true;
+        self.preload_started = Instant::now();
     }
 
     /// Tick of the world, run every 16ms.
@@ -754,60 +1051,131 @@ impl World {
         }
 
         if self.preloading {
-            let light_padding = (self.config().max_light_level as f32
-                / self.config().chunk_size as f32)
-                .ceil() as usize;
-            let check_radius = (self.config().preload_radius - light_padding) as i32;
-
-            let mut total = 0;
-            let supposed = (check_radius * 2).pow(2);
-
-            for x in -check_radius..=check_radius {
-                for z in -check_radius..=check_radius {
-                    let chunks = self.chunks();
-                    let coords = Vec2(x, z);
-
-                    if chunks.is_chunk_ready(&coords) {
-                        total += 1;
-                    } else {
-                        if let Some(chunk) = chunks.raw(&coords) {
-                            if chunk.status == ChunkStatus::Meshing
-                                && !self.mesher().map.contains(&coords)
-                            {
-                                // Add the chunk back to meshing queue.
-                                drop(chunks);
-                                self.mesher_mut().add_chunk(&coords, false);
-                            }
-                        }
-
-                        // drop(chunks);
-
-                        // let is_in_pipeline = self.pipeline().has_chunk(&coords);
-                        // let is_in_mesher = self.mesher().map.contains(&coords);
-
-                        // info!(
-                        //     "Chunk {:?} is not ready. In pipeline: {}, in mesher: {}, status: {:?}",
-                        //     coords, is_in_pipeline, is_in_mesher, status
-                        // );
+            let mut remaining = std::mem::take(&mut self.preload_remaining);
+
+            remaining.retain(|coords| {
+                let chunks = self.chunks();
+
+                if chunks.is_chunk_ready(coords) {
+                    return false;
+                }
+
+                if let Some(chunk) = chunks.raw(coords) {
+                    if chunk.status == ChunkStatus::Meshing && !self.mesher().map.contains(coords) {
+                        // Add the chunk back to meshing queue.
+                        drop(chunks);
+                        self.mesher_mut().add_chunk(coords, false);
                     }
                 }
-            }
 
-            self.preload_progress = (total as f32 / supposed as f32).min(1.0);
+                true
+            });
 
-            if total >= supposed {
+            self.preload_progress = if self.preload_total == 0 {
+                1.0
+            } else {
+                1.0 - remaining.len() as f32 / self.preload_total as f32
+            };
+
+            if remaining.is_empty() {
                 self.preloading = false;
+
+                if self.config().preload_in_background {
+                    info!(
+                        "✅ World \"{}\" preloaded {} chunks in the background in {}s",
+                        self.name,
+                        self.preload_total,
+                        self.preload_started.elapsed().as_millis() as f64 / 1000.0
+                    );
+                }
             }
+
+            self.preload_remaining = remaining;
         }
 
         if !self.preloading && self.is_empty() {
+            // The last client leaving still has to be handled.
+            self.dispatch_world_events();
             return;
         }
 
+        self.spawn_entities();
+
         let mut dispatcher = (self.dispatcher)().build();
         dispatcher.dispatch(&self.ecs);
 
         self.ecs.maintain();
+
+        self.dispatch_world_events();
+    }
+
+    /// Hand the world events recorded so far to the world event handles.
+    fn dispatch_world_events(&mut self) {
+        let events = self.write_resource::<WorldEvents>().drain();
+
+        if self.world_event_handles.is_empty() {
+            return;
+        }
+
+        let handles = self.world_event_handles.to_owned();
+
+        for event in &events {
+            for handle in &handles {
+                handle(self, event);
+            }
+        }
+    }
+
+    /// Run a round of the natural entity spawner if due, spawning entities around the clients and despawning the
+    /// ones far from every client.
+    fn spawn_entities(&mut self) {
+        if !self.spawner().is_due(self.stats().tick) {
+            return;
+        }
+
+        let (spawns, despawns) = {
+            let clients = (
+                &self.ecs.read_storage::<ClientFlag>(),
+                &self.ecs.read_storage::<RigidBodyComp>(),
+            )
+                .join()
+                .map(|(_, body)| body.0.get_position())
+                .collect::<Vec<_>>();
+
+            let population = (
+                &self.ecs.entities(),
+                &self.ecs.read_storage::<EntityFlag>(),
+                &self.ecs.read_storage::<ETypeComp>(),
+                &self.ecs.read_storage::<RigidBodyComp>(),
+            )
+                .join()
+                .map(|(entity, _, etype, body)| (entity, etype.0.to_owned(), body.0.get_position()))
+                .collect::<Vec<_>>();
+
+            let spawner = self.spawner();
+
+            (
+                spawner.spawns(
+                    &self.chunks(),
+                    &self.registry(),
+                    &self.config(),
+                    &clients,
+                    &population,
+                ),
+                spawner.despawns(&clients, &population),
+            )
+        };
+
+        if !despawns.is_empty() {
+            let entities = self.ecs.entities();
+            despawns.into_iter().for_each(|entity| {
+                let _ = entities.delete(entity);
+            });
+        }
+
+        for (etype, position) in spawns {
+            self.spawn_entity_at(&etype, &position);
+        }
     }
 
     /// Handler for `Peer` type messages.
@@ -908,9 +1276,14 @@ impl World {
 
             drop(interests);
 
+            let tick = self.stats().tick;
+
             to_remove.into_iter().for_each(|coords| {
                 self.pipeline_mut().remove_chunk(coords);
                 self.mesher_mut().remove_chunk(coords);
+
+                // Nobody sees the chunk anymore, so its timers wait until it loads again.
+                self.chunks_mut().pause_chunk_ticks(coords, tick);
             })
         }
     }
@@ -0,0 +1,280 @@
+use std::{cmp::Reverse, collections::BinaryHeap, f32::consts::SQRT_2};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::{Registry, Vec3, VoxelAccess};
+
+use super::options::PathOptions;
+
+/// A path found through the voxels, as the voxels the entity's feet pass through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    /// The voxels along the path, from the start to the end.
+    pub nodes: Vec<Vec3<i32>>,
+
+    /// Whether the path reaches the goal. Bounded searches that run out of nodes end at the voxel closest to
+    /// the goal instead.
+    pub complete: bool,
+}
+
+/// Answers where an entity can stand and move to, following the path options.
+struct Walker<'a> {
+    space: &'a dyn VoxelAccess,
+    registry: &'a Registry,
+    options: &'a PathOptions,
+}
+
+impl Walker<'_> {
+    /// Whether the entity can pass through a voxel.
+    fn is_open(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        if vy < 0 || !self.space.contains(vx, vy, vz) {
+            return false;
+        }
+
+        let block = self
+            .registry
+            .get_block_by_id(self.space.get_voxel(vx, vy, vz));
+
+        if block.is_fluid {
+            return self.options.can_swim;
+        }
+
+        block.is_empty
+            || block.is_passable
+            || block
+                .get_aabbs(&Vec3(vx, vy, vz), self.space, self.registry)
+                .is_empty()
+    }
+
+    /// Whether the entity can stand on top of a voxel.
+    fn is_solid(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        if vy < 0 || !self.space.contains(vx, vy, vz) {
+            return false;
+        }
+
+        let block = self
+            .registry
+            .get_block_by_id(self.space.get_voxel(vx, vy, vz));
+
+        !block.is_empty
+            && !block.is_fluid
+            && !block.is_passable
+            && !block
+                .get_aabbs(&Vec3(vx, vy, vz), self.space, self.registry)
+                .is_empty()
+    }
+
+    fn is_fluid(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        self.space.contains(vx, vy, vz)
+            && self
+                .registry
+                .get_block_by_id(self.space.get_voxel(vx, vy, vz))
+                .is_fluid
+    }
+
+    /// Whether the entity fits in a column of voxels, starting at its feet.
+    fn fits(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        (0..self.options.height as i32).all(|dy| self.is_open(vx, vy + dy, vz))
+    }
+
+    /// Whether the entity can stand with its feet in a voxel, on solid ground or swimming.
+    fn can_stand(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        self.fits(vx, vy, vz)
+            && (self.is_solid(vx, vy - 1, vz)
+                || (self.options.can_swim && self.is_fluid(vx, vy, vz)))
+    }
+
+    /// Find the voxel an entity around `voxel` would be standing in, looking a little up and down.
+    fn ground(&self, voxel: &Vec3<i32>) -> Option<Vec3<i32>> {
+        let &Vec3(vx, vy, vz) = voxel;
+
+        (0..=self.options.max_drop as i32 + 1)
+            .map(|dy| vy - dy)
+            .chain(std::iter::once(vy + 1))
+            .find(|&y| self.can_stand(vx, y, vz))
+            .map(|y| Vec3(vx, y, vz))
+    }
+
+    /// Move one voxel horizontally, stepping up or dropping down as needed.
+    fn walk(&self, node: &Vec3<i32>, dx: i32, dz: i32) -> Option<(Vec3<i32>, f32)> {
+        let &Vec3(vx, vy, vz) = node;
+        let (nx, nz) = (vx + dx, vz + dz);
+        let height = self.options.height as i32;
+
+        if self.can_stand(nx, vy, nz) {
+            return Some((Vec3(nx, vy, nz), 1.0));
+        }
+
+        for k in 1..=self.options.step_height as i32 {
+            // Stepping up needs room above the head first.
+            if !self.is_open(vx, vy + height - 1 + k, vz) {
+                break;
+            }
+
+            if self.can_stand(nx, vy + k, nz) {
+                return Some((Vec3(nx, vy + k, nz), 1.0 + 0.5 * k as f32));
+            }
+        }
+
+        if !self.fits(nx, vy, nz) {
+            return None;
+        }
+
+        for k in 1..=self.options.max_drop as i32 {
+            if !self.is_open(nx, vy - k, nz) {
+                break;
+            }
+
+            if self.can_stand(nx, vy - k, nz) {
+                return Some((Vec3(nx, vy - k, nz), 1.0 + 0.5 * k as f32));
+            }
+        }
+
+        None
+    }
+
+    fn neighbors(&self, node: &Vec3<i32>) -> Vec<(Vec3<i32>, f32)> {
+        let &Vec3(vx, vy, vz) = node;
+        let mut neighbors = vec![];
+
+        for [dx, dz] in [[1, 0], [-1, 0], [0, 1], [0, -1]] {
+            if let Some(neighbor) = self.walk(node, dx, dz) {
+                neighbors.push(neighbor);
+            }
+        }
+
+        // Diagonals stay level, and never cut corners.
+        for [dx, dz] in [[1, 1], [1, -1], [-1, 1], [-1, -1]] {
+            if self.fits(vx + dx, vy, vz)
+                && self.fits(vx, vy, vz + dz)
+                && self.can_stand(vx + dx, vy, vz + dz)
+            {
+                neighbors.push((Vec3(vx + dx, vy, vz + dz), SQRT_2));
+            }
+        }
+
+        if self.options.can_swim && self.is_fluid(vx, vy, vz) {
+            for dy in [1, -1] {
+                if self.can_stand(vx, vy + dy, vz) {
+                    neighbors.push((Vec3(vx, vy + dy, vz), 1.0));
+                }
+            }
+        }
+
+        neighbors
+    }
+}
+
+/// Estimate the cost between two voxels, never overestimating it.
+fn heuristic(a: &Vec3<i32>, b: &Vec3<i32>) -> f32 {
+    let dx = (a.0 - b.0).abs() as f32;
+    let dy = (a.1 - b.1).abs() as f32;
+    let dz = (a.2 - b.2).abs() as f32;
+
+    dx.max(dz) - dx.min(dz) + SQRT_2 * dx.min(dz) + 0.5 * dy
+}
+
+/// Find a path for an entity from the voxel its feet are in to the goal, with A*. The search is bounded by
+/// `options.max_nodes`, after which the path to the voxel closest to the goal is returned, marked incomplete.
+/// Returns `None` if the entity cannot stand at the start, or cannot move anywhere closer to the goal.
+pub fn find_path(
+    space: &dyn VoxelAccess,
+    registry: &Registry,
+    start: &Vec3<i32>,
+    goal: &Vec3<i32>,
+    options: &PathOptions,
+) -> Option<Path> {
+    let walker = Walker {
+        space,
+        registry,
+        options,
+    };
+
+    let start = walker.ground(start)?;
+    let goal = walker.ground(goal).unwrap_or_else(|| goal.to_owned());
+
+    let horizontal = (start.0 - goal.0).abs().max((start.2 - goal.2).abs());
+    if horizontal as usize > options.max_distance {
+        return None;
+    }
+
+    let mut queue = BinaryHeap::new();
+    let mut costs = HashMap::new();
+    let mut came_from = HashMap::new();
+    let mut closed = HashSet::new();
+
+    let mut closest = (heuristic(&start, &goal), start.clone());
+
+    costs.insert(start.clone(), 0.0);
+    queue.push(Reverse((
+        (closest.0 * 1000.0) as u32,
+        start.0,
+        start.1,
+        start.2,
+    )));
+
+    let reconstruct = |came_from: &HashMap<Vec3<i32>, Vec3<i32>>, end: &Vec3<i32>| {
+        let mut nodes = vec![end.to_owned()];
+
+        while let Some(prev) = came_from.get(nodes.last().unwrap()) {
+            nodes.push(prev.to_owned());
+        }
+
+        nodes.reverse();
+        nodes
+    };
+
+    while let Some(Reverse((_, vx, vy, vz))) = queue.pop() {
+        let node = Vec3(vx, vy, vz);
+
+        if node == goal {
+            return Some(Path {
+                nodes: reconstruct(&came_from, &node),
+                complete: true,
+            });
+        }
+
+        if !closed.insert(node.clone()) {
+            continue;
+        }
+
+        if closed.len() > options.max_nodes {
+            break;
+        }
+
+        let estimate = heuristic(&node, &goal);
+        if estimate < closest.0 {
+            closest = (estimate, node.clone());
+        }
+
+        let cost = costs[&node];
+
+        for (neighbor, step) in walker.neighbors(&node) {
+            let next_cost = cost + step;
+
+            if costs.get(&neighbor).is_some_and(|&c| c <= next_cost) {
+                continue;
+            }
+
+            costs.insert(neighbor.clone(), next_cost);
+            came_from.insert(neighbor.clone(), node.clone());
+
+            let score = next_cost + heuristic(&neighbor, &goal);
+            queue.push(Reverse((
+                (score * 1000.0) as u32,
+                neighbor.0,
+                neighbor.1,
+                neighbor.2,
+            )));
+        }
+    }
+
+    if closest.1 == start {
+        return None;
+    }
+
+    Some(Path {
+        nodes: reconstruct(&came_from, &closest.1),
+        complete: false,
+    })
+}
@@ -0,0 +1,11 @@
+mod astar;
+mod options;
+mod pathfinder;
+mod region;
+mod steering;
+
+pub use astar::*;
+pub use options::*;
+pub use pathfinder::*;
+pub use region::*;
+pub use steering::*;
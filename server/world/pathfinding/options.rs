@@ -0,0 +1,124 @@
+use crate::RigidBody;
+
+/// Parameters of a path search, describing the entity walking the path.
+#[derive(Debug, Clone)]
+pub struct PathOptions {
+    /// How many blocks tall the entity is. Default is 2.
+    pub height: usize,
+
+    /// How many blocks the entity can step up at once. Default is 1, matching `RigidBody::auto_step`.
+    pub step_height: usize,
+
+    /// How many blocks the entity can drop down at once. Default is 3.
+    pub max_drop: usize,
+
+    /// Whether the entity can swim through fluids. Default is false.
+    pub can_swim: bool,
+
+    /// Maximum number of voxels searched before giving up with the closest path found. Default is 2048.
+    pub max_nodes: usize,
+
+    /// Maximum horizontal distance between the start and the goal, in blocks. Default is 64.
+    pub max_distance: usize,
+}
+
+impl Default for PathOptions {
+    fn default() -> Self {
+        Self::new().build()
+    }
+}
+
+impl PathOptions {
+    /// Create path options using the builder pattern.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> PathOptionsBuilder {
+        PathOptionsBuilder::default()
+    }
+}
+
+const DEFAULT_HEIGHT: usize = 2;
+const DEFAULT_STEP_HEIGHT: usize = 1;
+const DEFAULT_MAX_DROP: usize = 3;
+const DEFAULT_CAN_SWIM: bool = false;
+const DEFAULT_MAX_NODES: usize = 2048;
+const DEFAULT_MAX_DISTANCE: usize = 64;
+
+/// Builder for the parameters of a path search.
+pub struct PathOptionsBuilder {
+    height: usize,
+    step_height: usize,
+    max_drop: usize,
+    can_swim: bool,
+    max_nodes: usize,
+    max_distance: usize,
+}
+
+impl Default for PathOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            height: DEFAULT_HEIGHT,
+            step_height: DEFAULT_STEP_HEIGHT,
+            max_drop: DEFAULT_MAX_DROP,
+            can_swim: DEFAULT_CAN_SWIM,
+            max_nodes: DEFAULT_MAX_NODES,
+            max_distance: DEFAULT_MAX_DISTANCE,
+        }
+    }
+}
+
+impl PathOptionsBuilder {
+    /// Configure the height and the step height to match a rigid body.
+    pub fn body(mut self, body: &RigidBody) -> Self {
+        self.height = (body.aabb.height().ceil() as usize).max(1);
+        self.step_height = if body.auto_step { 1 } else { 0 };
+        self
+    }
+
+    /// Configure how many blocks tall the entity is. Defaults to `2`.
+    pub fn height(mut self, height: usize) -> Self {
+        self.height = height.max(1);
+        self
+    }
+
+    /// Configure how many blocks the entity can step up. Defaults to `1`.
+    pub fn step_height(mut self, step_height: usize) -> Self {
+        self.step_height = step_height;
+        self
+    }
+
+    /// Configure how many blocks the entity can drop down. Defaults to `3`.
+    pub fn max_drop(mut self, max_drop: usize) -> Self {
+        self.max_drop = max_drop;
+        self
+    }
+
+    /// Configure whether the entity can swim through fluids. Defaults to `false`.
+    pub fn can_swim(mut self, can_swim: bool) -> Self {
+        self.can_swim = can_swim;
+        self
+    }
+
+    /// Configure the maximum number of voxels searched. Defaults to `2048`.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Configure the maximum distance to the goal. Defaults to `64` blocks.
+    pub fn max_distance(mut self, max_distance: usize) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Create the path options.
+    pub fn build(self) -> PathOptions {
+        PathOptions {
+            height: self.height,
+            step_height: self.step_height,
+            max_drop: self.max_drop,
+            can_swim: self.can_swim,
+            max_nodes: self.max_nodes,
+            max_distance: self.max_distance,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use hashbrown::HashMap;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use specs::Entity;
+
+use crate::{Registry, Vec3, VoxelAccess};
+
+use super::{
+    astar::{find_path, Path},
+    options::PathOptions,
+    region::VoxelRegion,
+};
+
+/// How far around the start and the goal the voxels are copied for a search, in blocks. Paths that detour
+/// further than that are not found. The margin grows with the distance, to leave room for longer detours.
+const REGION_MARGIN: i32 = 16;
+
+/// How far above and below the start and the goal the voxels are copied for a search, in blocks.
+const REGION_VERTICAL_MARGIN: i32 = 8;
+
+/// A pool of workers finding paths for entities, away from the game loop.
+pub struct Pathfinder {
+    /// The latest request of each entity waiting for a path. Results of older requests are dropped.
+    pending: HashMap<Entity, u64>,
+
+    /// The ID of the next request.
+    next_request: u64,
+
+    /// Sender of found paths from the workers to the main thread.
+    sender: Arc<Sender<(Entity, u64, Option<Path>)>>,
+
+    /// Receiver of found paths from the workers to the main thread.
+    receiver: Arc<Receiver<(Entity, u64, Option<Path>)>>,
+
+    /// The thread pool for pathfinding.
+    pool: ThreadPool,
+}
+
+impl Default for Pathfinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pathfinder {
+    /// Create a new pool of pathfinding workers.
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded();
+
+        Self {
+            pending: HashMap::new(),
+            next_request: 0,
+            sender: Arc::new(sender),
+            receiver: Arc::new(receiver),
+            pool: ThreadPoolBuilder::new()
+                .thread_name(|index| format!("pathfinding-{index}"))
+                .num_threads(4)
+                .build()
+                .unwrap(),
+        }
+    }
+
+    /// Find a path for an entity in the background, replacing its previous request. The voxels around the
+    /// start and the goal are copied out of `space` first.
+    pub fn request(
+        &mut self,
+        entity: Entity,
+        start: &Vec3<i32>,
+        goal: &Vec3<i32>,
+        options: &PathOptions,
+        space: &dyn VoxelAccess,
+        registry: &Registry,
+    ) {
+        let request = self.next_request;
+        self.next_request += 1;
+        self.pending.insert(entity, request);
+
+        // Too far to search, so skip copying the voxels in between.
+        let horizontal = (start.0 - goal.0).abs().max((start.2 - goal.2).abs());
+        if horizontal as usize > options.max_distance {
+            let _ = self.sender.send((entity, request, None));
+            return;
+        }
+
+        let margin = REGION_MARGIN.max(horizontal / 2);
+        let vertical_margin =
+            REGION_VERTICAL_MARGIN.max((options.height + options.max_drop) as i32 + 1);
+        let min = Vec3(
+            start.0.min(goal.0) - margin,
+            (start.1.min(goal.1) - vertical_margin).max(0),
+            start.2.min(goal.2) - margin,
+        );
+        let shape = Vec3(
+            ((start.0 - goal.0).abs() + margin * 2 + 1) as usize,
+            (start.1.max(goal.1) + vertical_margin + 1 - min.1) as usize,
+            ((start.2 - goal.2).abs() + margin * 2 + 1) as usize,
+        );
+
+        let sender = Arc::clone(&self.sender);
+        let region = VoxelRegion::new(space, &min, &shape);
+        let registry = registry.to_owned();
+        let start = start.to_owned();
+        let goal = goal.to_owned();
+        let options = options.to_owned();
+
+        self.pool.spawn(move || {
+            let path = find_path(&region, &registry, &start, &goal, &options);
+
+            // The pathfinder is gone if this fails, and nobody is waiting for the path anymore.
+            let _ = sender.send((entity, request, path));
+        });
+    }
+
+    /// Drop the pending request of an entity.
+    pub fn cancel(&mut self, entity: Entity) {
+        self.pending.remove(&entity);
+    }
+
+    /// Whether an entity is waiting for a path.
+    pub fn is_pending(&self, entity: Entity) -> bool {
+        self.pending.contains_key(&entity)
+    }
+
+    /// Whether no entity is waiting for a path.
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Collect the paths found since the last call, for the latest request of each entity.
+    pub fn results(&mut self) -> Vec<(Entity, Option<Path>)> {
+        let mut results = vec![];
+
+        while let Ok((entity, request, path)) = self.receiver.try_recv() {
+            if self.pending.get(&entity) != Some(&request) {
+                continue;
+            }
+
+            self.pending.remove(&entity);
+            results.push((entity, path));
+        }
+
+        results
+    }
+}
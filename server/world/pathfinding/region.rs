@@ -0,0 +1,78 @@
+use crate::{Vec3, VoxelAccess};
+
+/// A copy of the voxels within a box of the world, so that they can be searched on another thread. Voxels
+/// outside of the box are not contained.
+#[derive(Debug, Default, Clone)]
+pub struct VoxelRegion {
+    /// Minimum voxel coordinate of the region.
+    pub min: Vec3<i32>,
+
+    /// Shape of the region.
+    pub shape: Vec3<usize>,
+
+    voxels: Vec<u32>,
+}
+
+impl VoxelRegion {
+    /// Copy the raw voxels of a box out of a voxel access.
+    pub fn new(space: &dyn VoxelAccess, min: &Vec3<i32>, shape: &Vec3<usize>) -> Self {
+        let &Vec3(min_x, min_y, min_z) = min;
+        let &Vec3(width, height, depth) = shape;
+
+        let mut voxels = Vec::with_capacity(width * height * depth);
+
+        for x in 0..width as i32 {
+            for y in 0..height as i32 {
+                for z in 0..depth as i32 {
+                    let (vx, vy, vz) = (min_x + x, min_y + y, min_z + z);
+
+                    voxels.push(if space.contains(vx, vy, vz) {
+                        space.get_raw_voxel(vx, vy, vz)
+                    } else {
+                        0
+                    });
+                }
+            }
+        }
+
+        Self {
+            min: min.to_owned(),
+            shape: shape.to_owned(),
+            voxels,
+        }
+    }
+
+    fn index(&self, vx: i32, vy: i32, vz: i32) -> usize {
+        let Vec3(_, height, depth) = self.shape;
+        let (x, y, z) = (
+            (vx - self.min.0) as usize,
+            (vy - self.min.1) as usize,
+            (vz - self.min.2) as usize,
+        );
+
+        (x * height + y) * depth + z
+    }
+}
+
+impl VoxelAccess for VoxelRegion {
+    /// Get the raw voxel data at the voxel position. Zero is returned outside of the region.
+    fn get_raw_voxel(&self, vx: i32, vy: i32, vz: i32) -> u32 {
+        if !self.contains(vx, vy, vz) {
+            return 0;
+        }
+
+        self.voxels[self.index(vx, vy, vz)]
+    }
+
+    /// Whether the voxel is within the region.
+    fn contains(&self, vx: i32, vy: i32, vz: i32) -> bool {
+        let Vec3(width, height, depth) = self.shape;
+
+        vx >= self.min.0
+            && vy >= self.min.1
+            && vz >= self.min.2
+            && vx < self.min.0 + width as i32
+            && vy < self.min.1 + height as i32
+            && vz < self.min.2 + depth as i32
+    }
+}
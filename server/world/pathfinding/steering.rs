@@ -0,0 +1,40 @@
+use crate::{RigidBody, Vec3};
+
+use super::astar::Path;
+
+/// How close horizontally a body has to get to a node of the path to move on to the next one, at least, in
+/// blocks. Narrow bodies have to be fully inside the column of the node, so they don't clip corners.
+const NODE_REACHED_DISTANCE: f32 = 0.1;
+
+/// Steer a body along a path, applying a horizontal impulse that turns it towards the node at `index` at
+/// `speed` blocks per second. `index` moves on as the nodes are reached. Returns whether the end of the path
+/// has been reached, in which case no impulse is applied.
+pub fn follow_path(body: &mut RigidBody, path: &Path, index: &mut usize, speed: f32) -> bool {
+    let Vec3(px, _, pz) = body.get_position();
+    let feet = body.aabb.min_y;
+    let reached = (0.5 - body.aabb.width().max(body.aabb.depth()) / 2.0).max(NODE_REACHED_DISTANCE);
+
+    while let Some(Vec3(nx, ny, nz)) = path.nodes.get(*index) {
+        let dx = *nx as f32 + 0.5 - px;
+        let dz = *nz as f32 + 0.5 - pz;
+        let distance = (dx * dx + dz * dz).sqrt();
+
+        if distance < reached && (feet - *ny as f32).abs() < 1.5 {
+            *index += 1;
+            continue;
+        }
+
+        let Vec3(vx, _, vz) = body.velocity;
+        let (target_x, target_z) = (dx / distance * speed, dz / distance * speed);
+
+        body.apply_impulse(
+            (target_x - vx) * body.mass,
+            0.0,
+            (target_z - vz) * body.mass,
+        );
+
+        return false;
+    }
+
+    true
+}
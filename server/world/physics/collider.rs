@@ -0,0 +1,106 @@
+use rapier3d::prelude::{Group, InteractionGroups, Isometry, SharedShape};
+
+use super::aabb::AABB;
+
+/// The shape a rigid body collides with other bodies as, centered on the body's position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColliderShape {
+    /// A box with the given half extents.
+    Cuboid { half_extents: [f32; 3] },
+
+    /// An upright capsule, a cylinder of `half_height` with half spheres of `radius` on both ends.
+    Capsule { half_height: f32, radius: f32 },
+
+    /// A sphere of `radius`.
+    Ball { radius: f32 },
+
+    /// Several shapes, each offset from the body's position.
+    Compound(Vec<([f32; 3], ColliderShape)>),
+}
+
+impl ColliderShape {
+    /// The capsule fitted to an AABB that bodies collide as by default.
+    pub fn capsule_from(aabb: &AABB) -> Self {
+        ColliderShape::Capsule {
+            half_height: aabb.height() / 2.0,
+            radius: (aabb.width() / 2.0).min(aabb.depth() / 2.0),
+        }
+    }
+
+    /// A box the size of an AABB.
+    pub fn cuboid_from(aabb: &AABB) -> Self {
+        ColliderShape::Cuboid {
+            half_extents: [aabb.width() / 2.0, aabb.height() / 2.0, aabb.depth() / 2.0],
+        }
+    }
+
+    /// Convert into a rapier shape.
+    pub fn to_shared_shape(&self) -> SharedShape {
+        match self {
+            ColliderShape::Cuboid {
+                half_extents: [hx, hy, hz],
+            } => SharedShape::cuboid(*hx, *hy, *hz),
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => SharedShape::capsule_y(*half_height, *radius),
+            ColliderShape::Ball { radius } => SharedShape::ball(*radius),
+            ColliderShape::Compound(shapes) => SharedShape::compound(
+                shapes
+                    .iter()
+                    .map(|([ox, oy, oz], shape)| {
+                        (
+                            Isometry::translation(*ox, *oy, *oz),
+                            shape.to_shared_shape(),
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Which groups a body is part of, and which groups it collides with. Two bodies only collide, and only
+/// report collisions, if each one is part of a group the other one collides with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionGroups {
+    /// Bit mask of the groups this body is part of.
+    pub memberships: u32,
+
+    /// Bit mask of the groups this body collides with.
+    pub filter: u32,
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl CollisionGroups {
+    /// Create collision groups from the bit masks of memberships and filter.
+    pub fn new(memberships: u32, filter: u32) -> Self {
+        Self {
+            memberships,
+            filter,
+        }
+    }
+
+    /// Part of every group and colliding with every group.
+    pub fn all() -> Self {
+        Self::new(u32::MAX, u32::MAX)
+    }
+
+    /// Whether bodies with these two groups collide.
+    pub fn test(&self, other: &CollisionGroups) -> bool {
+        self.memberships & other.filter != 0 && other.memberships & self.filter != 0
+    }
+
+    /// Convert into rapier interaction groups.
+    pub fn to_interaction_groups(&self) -> InteractionGroups {
+        InteractionGroups::new(
+            Group::from_bits_truncate(self.memberships),
+            Group::from_bits_truncate(self.filter),
+        )
+    }
+}
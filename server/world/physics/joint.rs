@@ -0,0 +1,123 @@
+use rapier3d::prelude::{
+    point, vector, FixedJointBuilder, GenericJoint, GenericJointBuilder, JointAxesMask,
+    RevoluteJointBuilder, UnitVector,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::Vec3;
+
+/// How an attached entity is held to its parent, at the anchor points of both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JointKind {
+    /// The anchors are held together, so the child moves along with the parent. Riders and held items.
+    Fixed,
+
+    /// The anchors are held together, but the child can swing around `axis` through them.
+    Revolute { axis: [f32; 3] },
+
+    /// The anchors are kept within `length` of each other. Carts following one another.
+    Rope { length: f32 },
+}
+
+impl JointKind {
+    /// Convert into a rapier joint between the anchors. Contacts between the two bodies are disabled, so that
+    /// attached entities do not push each other apart. Rapier has no rope joint, so a rope is an empty joint
+    /// that only disables the contacts, and its length is kept by `constrain`.
+    pub fn to_generic_joint(
+        &self,
+        parent_anchor: &Vec3<f32>,
+        child_anchor: &Vec3<f32>,
+    ) -> GenericJoint {
+        let &Vec3(px, py, pz) = parent_anchor;
+        let &Vec3(cx, cy, cz) = child_anchor;
+
+        match self {
+            JointKind::Fixed => FixedJointBuilder::new()
+                .local_anchor1(point![px, py, pz])
+                .local_anchor2(point![cx, cy, cz])
+                .contacts_enabled(false)
+                .build()
+                .into(),
+            JointKind::Revolute { axis: [ax, ay, az] } => {
+                RevoluteJointBuilder::new(UnitVector::new_normalize(vector![*ax, *ay, *az]))
+                    .local_anchor1(point![px, py, pz])
+                    .local_anchor2(point![cx, cy, cz])
+                    .contacts_enabled(false)
+                    .build()
+                    .into()
+            }
+            JointKind::Rope { .. } => GenericJointBuilder::new(JointAxesMask::empty())
+                .contacts_enabled(false)
+                .build(),
+        }
+    }
+
+    /// Solve the position of a child body, given the world position of the parent's anchor and the child's
+    /// anchor relative to its own position. Returns the position the child is moved to, if it has to move.
+    pub fn constrain(
+        &self,
+        anchor: &Vec3<f32>,
+        child: &Vec3<f32>,
+        child_anchor: &Vec3<f32>,
+    ) -> Option<Vec3<f32>> {
+        let &Vec3(ax, ay, az) = anchor;
+        let &Vec3(cx, cy, cz) = child_anchor;
+
+        match self {
+            JointKind::Fixed => Some(Vec3(ax - cx, ay - cy, az - cz)),
+            JointKind::Revolute { axis } => {
+                let [nx, ny, nz] = normalize(axis)?;
+                let dot = |[x, y, z]: [f32; 3]| x * nx + y * ny + z * nz;
+
+                // The child circles the axis through the anchor, at the radius and height its anchor sets.
+                let along = -dot([cx, cy, cz]);
+                let [rx, ry, rz] = [-cx - along * nx, -cy - along * ny, -cz - along * nz];
+                let radius = (rx * rx + ry * ry + rz * rz).sqrt();
+
+                let offset = [child.0 - ax, child.1 - ay, child.2 - az];
+                let offset_along = dot(offset);
+                let radial = [
+                    offset[0] - offset_along * nx,
+                    offset[1] - offset_along * ny,
+                    offset[2] - offset_along * nz,
+                ];
+                let [ux, uy, uz] = normalize(&radial)
+                    .or_else(|| normalize(&[rx, ry, rz]))
+                    .unwrap_or_default();
+
+                Some(Vec3(
+                    ax + along * nx + ux * radius,
+                    ay + along * ny + uy * radius,
+                    az + along * nz + uz * radius,
+                ))
+            }
+            JointKind::Rope { length } => {
+                let [dx, dy, dz] = [child.0 + cx - ax, child.1 + cy - ay, child.2 + cz - az];
+                let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+
+                if distance <= *length {
+                    return None;
+                }
+
+                let scale = length / distance;
+
+                Some(Vec3(
+                    ax + dx * scale - cx,
+                    ay + dy * scale - cy,
+                    az + dz * scale - cz,
+                ))
+            }
+        }
+    }
+}
+
+fn normalize(&[x, y, z]: &[f32; 3]) -> Option<[f32; 3]> {
+    let len = (x * x + y * y + z * z).sqrt();
+
+    if len <= f32::EPSILON {
+        return None;
+    }
+
+    Some([x / len, y / len, z / len])
+}
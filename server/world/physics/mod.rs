@@ -2,10 +2,10 @@ use crossbeam_channel::Receiver;
 use nalgebra::Vector3;
 use rapier3d::prelude::{
     vector, ActiveEvents, BroadPhase, CCDSolver, ChannelEventCollector, ColliderBuilder,
-    ColliderHandle, ColliderSet, CollisionEvent, ImpulseJointSet, IntegrationParameters,
-    IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline, RigidBody as RapierBody,
-    RigidBodyBuilder as RapierBodyBuilder, RigidBodyHandle as RapierBodyHandle,
-    RigidBodySet as RapierBodySet,
+    ColliderHandle, ColliderSet, CollisionEvent, ImpulseJointHandle, ImpulseJointSet,
+    IntegrationParameters, IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline,
+    RigidBody as RapierBody, RigidBodyBuilder as RapierBodyBuilder,
+    RigidBodyHandle as RapierBodyHandle, RigidBodySet as RapierBodySet,
 };
 
 use crate::{approx_equals, BlockRotation, Vec3, VoxelAccess};
@@ -13,10 +13,16 @@ use crate::{approx_equals, BlockRotation, Vec3, VoxelAccess};
 use super::{registry::Registry, WorldConfig};
 
 mod aabb;
+mod collider;
+mod joint;
+mod raycast;
 mod rigidbody;
 mod sweep;
 
 pub use aabb::*;
+pub use collider::*;
+pub use joint::*;
+pub use raycast::*;
 pub use rigidbody::*;
 pub use sweep::*;
 
@@ -34,6 +40,7 @@ pub struct Physics {
     collision_recv: Receiver<CollisionEvent>,
     event_handler: ChannelEventCollector,
     gravity: Vector3<f32>,
+    accumulator: f32,
 }
 
 impl Physics {
@@ -56,9 +63,29 @@ impl Physics {
             pipeline: PhysicsPipeline::default(),
             event_handler,
             gravity: vector![0.0, 0.0, 0.0],
+            accumulator: 0.0,
         }
     }
 
+    /// Add `delta` seconds to the fixed timestep accumulator. Returns how many steps of `step` seconds are
+    /// due, at most `max_substeps`, and how far the leftover time is into the next step, from 0 to 1. Time
+    /// beyond `max_substeps` steps is dropped.
+    pub fn advance(&mut self, delta: f32, step: f32, max_substeps: usize) -> (usize, f32) {
+        self.accumulator += delta.max(0.0);
+
+        // Tolerate rounding so that deltas of exactly one step always run that step.
+        let due = ((self.accumulator + step * 1e-4) / step).floor() as usize;
+        let steps = due.min(max_substeps);
+
+        self.accumulator = (self.accumulator - steps as f32 * step).max(0.0);
+
+        if due > steps {
+            self.accumulator %= step;
+        }
+
+        (steps, (self.accumulator / step).min(1.0))
+    }
+
     pub fn step(&mut self, dt: f32) -> Vec<CollisionEvent> {
         self.integration_options.dt = dt;
 
@@ -96,11 +123,16 @@ impl Physics {
             .gravity_scale(0.0)
             .lock_rotations()
             .build();
-        let mut collider = ColliderBuilder::capsule_y(
-            body.aabb.height() / 2.0,
-            (body.aabb.width() / 2.0).min(body.aabb.depth() / 2.0),
-        )
-        .build();
+        let shape = body
+            .collider
+            .clone()
+            .unwrap_or_else(|| collider::ColliderShape::capsule_from(&body.aabb));
+
+        // Compound shapes stay a single collider, so that each body has one collider handle to report
+        // collisions for.
+        let mut collider = ColliderBuilder::new(shape.to_shared_shape())
+            .collision_groups(body.collision_groups.to_interaction_groups())
+            .build();
 
         collider.set_active_events(ActiveEvents::COLLISION_EVENTS);
 
@@ -112,6 +144,38 @@ impl Physics {
         (body_handle, collider_handle)
     }
 
+    /// Join two rapier bodies at their anchors, relative to each body's position. Voxelize bodies are moved by
+    /// their own physics and copied into rapier, so the joint mostly keeps rapier from pushing the two bodies
+    /// apart; `AttachmentSystem` is what holds the child to its parent. Ropes constrain nothing in rapier, and
+    /// only turn the contacts off.
+    pub fn attach(
+        &mut self,
+        parent: &RapierBodyHandle,
+        child: &RapierBodyHandle,
+        joint: &JointKind,
+        parent_anchor: &Vec3<f32>,
+        child_anchor: &Vec3<f32>,
+    ) -> ImpulseJointHandle {
+        self.impulse_joint_set.insert(
+            parent.to_owned(),
+            child.to_owned(),
+            joint.to_generic_joint(parent_anchor, child_anchor),
+            true,
+        )
+    }
+
+    /// Remove a joint created by `attach`.
+    pub fn detach(&mut self, joint_handle: &ImpulseJointHandle) {
+        self.impulse_joint_set.remove(joint_handle.to_owned(), true);
+    }
+
+    /// Whether a joint created by `attach` is still in place.
+    pub fn is_attached(&self, joint_handle: &ImpulseJointHandle) -> bool {
+        self.impulse_joint_set
+            .get(joint_handle.to_owned())
+            .is_some()
+    }
+
     pub fn get(&self, body_handle: &RapierBodyHandle) -> &RapierBody {
         &self.body_set[body_handle.to_owned()]
     }
@@ -163,6 +227,7 @@ impl Physics {
         let is_body_asleep =
             Physics::is_body_asleep(space, registry, config, body, dt, local_no_grav);
         if is_body_asleep {
+            body.sleeping = true;
             return;
         }
         body.sleep_frame_count -= 1;
@@ -406,7 +471,7 @@ impl Physics {
         body.velocity[(axis + 2) % 3] *= scalar;
     }
 
-    fn process_collisions(
+    pub(crate) fn process_collisions(
         space: &dyn VoxelAccess,
         registry: &Registry,
         aabb: &mut AABB,
@@ -0,0 +1,244 @@
+use specs::Entity;
+
+use crate::{Block, Registry, Vec3, VoxelAccess};
+
+use super::aabb::AABB;
+
+/// The result of a ray hitting a voxel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelRaycastHit {
+    /// The voxel that was hit.
+    pub voxel: Vec3<i32>,
+
+    /// The point where the ray entered the voxel's bounding boxes.
+    pub point: Vec3<f32>,
+
+    /// The normal of the face that was hit, pointing back towards the ray.
+    pub normal: Vec3<i32>,
+
+    /// The distance traveled by the ray.
+    pub distance: f32,
+}
+
+/// The result of a ray hitting an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityRaycastHit {
+    /// The entity that was hit.
+    pub entity: Entity,
+
+    /// The point where the ray entered the entity's bounding box.
+    pub point: Vec3<f32>,
+
+    /// The normal of the face that was hit, pointing back towards the ray.
+    pub normal: Vec3<i32>,
+
+    /// The distance traveled by the ray.
+    pub distance: f32,
+}
+
+/// The closest thing a ray hit in the world.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RaycastHit {
+    Voxel(VoxelRaycastHit),
+    Entity(EntityRaycastHit),
+}
+
+impl RaycastHit {
+    /// The distance traveled by the ray.
+    pub fn distance(&self) -> f32 {
+        match self {
+            RaycastHit::Voxel(hit) => hit.distance,
+            RaycastHit::Entity(hit) => hit.distance,
+        }
+    }
+}
+
+/// Intersect a ray with an AABB. `direction` must be normalized. Returns the axis of the face that was hit
+/// and the distance to it, or the exit face if the ray starts inside the box.
+pub fn raycast_aabb(
+    origin: &Vec3<f32>,
+    direction: &Vec3<f32>,
+    aabb: &AABB,
+    max_distance: f32,
+) -> Option<(usize, f32)> {
+    let origin = [origin.0, origin.1, origin.2];
+    let direction = [direction.0, direction.1, direction.2];
+    let min = [aabb.min_x, aabb.min_y, aabb.min_z];
+    let max = [aabb.max_x, aabb.max_y, aabb.max_z];
+
+    let mut t_min = (f32::NEG_INFINITY, 0);
+    let mut t_max = (f32::INFINITY, 0);
+
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            // Parallel to this slab, so the ray has to start within it.
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t1 = (min[axis] - origin[axis]) / direction[axis];
+        let t2 = (max[axis] - origin[axis]) / direction[axis];
+        let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+        if near > t_min.0 {
+            t_min = (near, axis);
+        }
+
+        if far < t_max.0 {
+            t_max = (far, axis);
+        }
+    }
+
+    // The box is behind the ray, or the ray misses it.
+    if t_max.0 < 0.0 || t_min.0 > t_max.0 {
+        return None;
+    }
+
+    let (distance, axis) = if t_min.0 < 0.0 { t_max } else { t_min };
+
+    if distance > max_distance {
+        return None;
+    }
+
+    Some((axis, distance))
+}
+
+/// Cast a ray through the voxels of `space`, returning the first voxel whose bounding boxes are hit. Empty
+/// blocks and fluids are ignored.
+pub fn raycast(
+    space: &dyn VoxelAccess,
+    registry: &Registry,
+    origin: &Vec3<f32>,
+    direction: &Vec3<f32>,
+    max_distance: f32,
+) -> Option<VoxelRaycastHit> {
+    raycast_filter(space, registry, origin, direction, max_distance, &|block| {
+        !block.is_fluid
+    })
+}
+
+/// Cast a ray through the voxels of `space`, only hitting the blocks that pass `filter`. Empty blocks are
+/// always ignored. The voxels are traversed one by one, and the rotated `aabbs` of each block are tested.
+pub fn raycast_filter(
+    space: &dyn VoxelAccess,
+    registry: &Registry,
+    origin: &Vec3<f32>,
+    direction: &Vec3<f32>,
+    max_distance: f32,
+    filter: &dyn Fn(&Block) -> bool,
+) -> Option<VoxelRaycastHit> {
+    let &Vec3(dx, dy, dz) = direction;
+    let ds = (dx * dx + dy * dy + dz * dz).sqrt();
+
+    if ds == 0.0 {
+        return None;
+    }
+
+    let direction = Vec3(dx / ds, dy / ds, dz / ds);
+    let Vec3(dx, dy, dz) = direction;
+    let &Vec3(ox, oy, oz) = origin;
+
+    let mut ix = ox.floor() as i32;
+    let mut iy = oy.floor() as i32;
+    let mut iz = oz.floor() as i32;
+
+    let step_x = if dx > 0.0 { 1 } else { -1 };
+    let step_y = if dy > 0.0 { 1 } else { -1 };
+    let step_z = if dz > 0.0 { 1 } else { -1 };
+
+    let tx_delta = (1.0 / dx).abs();
+    let ty_delta = (1.0 / dy).abs();
+    let tz_delta = (1.0 / dz).abs();
+
+    let x_dist = if step_x > 0 {
+        ix as f32 + 1.0 - ox
+    } else {
+        ox - ix as f32
+    };
+    let y_dist = if step_y > 0 {
+        iy as f32 + 1.0 - oy
+    } else {
+        oy - iy as f32
+    };
+    let z_dist = if step_z > 0 {
+        iz as f32 + 1.0 - oz
+    } else {
+        oz - iz as f32
+    };
+
+    let mut tx_max = if tx_delta.is_finite() {
+        tx_delta * x_dist
+    } else {
+        f32::INFINITY
+    };
+    let mut ty_max = if ty_delta.is_finite() {
+        ty_delta * y_dist
+    } else {
+        f32::INFINITY
+    };
+    let mut tz_max = if tz_delta.is_finite() {
+        tz_delta * z_dist
+    } else {
+        f32::INFINITY
+    };
+
+    let mut t = 0.0;
+
+    while t <= max_distance {
+        let block = registry.get_block_by_id(space.get_voxel(ix, iy, iz));
+
+        if !block.is_empty && filter(block) {
+            let voxel = Vec3(ix, iy, iz);
+            let rotation = space.get_voxel_rotation(ix, iy, iz);
+
+            let closest = block
+                .get_aabbs(&voxel, space, registry)
+                .iter()
+                .filter_map(|aabb| {
+                    let mut aabb = rotation.rotate_aabb(aabb, true, true);
+                    aabb.translate(ix as f32, iy as f32, iz as f32);
+                    raycast_aabb(origin, &direction, &aabb, max_distance)
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            if let Some((axis, distance)) = closest {
+                return Some(VoxelRaycastHit {
+                    voxel,
+                    point: Vec3(ox + distance * dx, oy + distance * dy, oz + distance * dz),
+                    normal: Vec3(
+                        if axis == 0 { -step_x } else { 0 },
+                        if axis == 1 { -step_y } else { 0 },
+                        if axis == 2 { -step_z } else { 0 },
+                    ),
+                    distance,
+                });
+            }
+        }
+
+        // Advance to the next nearest voxel boundary.
+        if tx_max < ty_max {
+            if tx_max < tz_max {
+                ix += step_x;
+                t = tx_max;
+                tx_max += tx_delta;
+            } else {
+                iz += step_z;
+                t = tz_max;
+                tz_max += tz_delta;
+            }
+        } else if ty_max < tz_max {
+            iy += step_y;
+            t = ty_max;
+            ty_max += ty_delta;
+        } else {
+            iz += step_z;
+            t = tz_max;
+            tz_max += tz_delta;
+        }
+    }
+
+    None
+}
@@ -1,5 +1,7 @@
 use crate::{Vec3, AABB};
 
+use super::collider::{ColliderShape, CollisionGroups};
+
 /// A physical body in the Voxelize world.
 #[derive(Default, Clone)]
 pub struct RigidBody {
@@ -35,6 +37,10 @@ pub struct RigidBody {
     /// Counts how many frames this rigid body is static.
     pub sleep_frame_count: i32,
 
+    /// Whether this rigid body is asleep. Sleeping bodies are skipped by the physics until a force, an impulse
+    /// or a nearby voxel change wakes them up.
+    pub sleeping: bool,
+
     /// AABB of this rigid body, describing its collision box.
     pub aabb: AABB,
 
@@ -52,6 +58,15 @@ pub struct RigidBody {
 
     /// Whether or not this rigid body auto-steps up blocks.
     pub auto_step: bool,
+
+    /// The shape this rigid body collides with other bodies as. Defaults to a capsule fitted to the AABB.
+    pub collider: Option<ColliderShape>,
+
+    /// The collision groups of this rigid body against other bodies. Defaults to colliding with everything.
+    pub collision_groups: CollisionGroups,
+
+    /// Position of the rigid body before its last physics step, used to interpolate between steps.
+    pub previous_position: Option<Vec3<f32>>,
 }
 
 impl RigidBody {
@@ -66,9 +81,25 @@ impl RigidBody {
 
         self.aabb
             .set_position(px - offset_w, py - offset_h, pz - offset_d);
+        self.previous_position = None;
         self.mark_active()
     }
 
+    /// Get the position of the rigid body `alpha` of the way from its previous physics step to its last one.
+    pub fn get_interpolated_position(&self, alpha: f32) -> Vec3<f32> {
+        let Vec3(cx, cy, cz) = self.get_position();
+
+        if let Some(Vec3(px, py, pz)) = self.previous_position {
+            Vec3(
+                px + (cx - px) * alpha,
+                py + (cy - py) * alpha,
+                pz + (cz - pz) * alpha,
+            )
+        } else {
+            Vec3(cx, cy, cz)
+        }
+    }
+
     /// Get the position of the rigid body, which is the bottom center of the rigid body.
     pub fn get_position(&self) -> Vec3<f32> {
         let [offset_w, offset_h, offset_d] = self.aabb_offset();
@@ -116,6 +147,7 @@ impl RigidBody {
     /// Mark rigid body as active in the physical world.
     pub fn mark_active(&mut self) {
         self.sleep_frame_count = 10;
+        self.sleeping = false;
     }
 
     /// Compute the offset from the minimum coordinates to the bottom center.
@@ -137,6 +169,8 @@ pub struct RigidBodyBuilder {
     restitution: f32,
     gravity_multiplier: f32,
     auto_step: bool,
+    collider: Option<ColliderShape>,
+    collision_groups: CollisionGroups,
 }
 
 impl RigidBodyBuilder {
@@ -181,6 +215,19 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Configure the shape this rigid body collides with other bodies as. Default is a capsule fitted to the AABB.
+    pub fn collider(mut self, collider: ColliderShape) -> Self {
+        self.collider = Some(collider);
+        self
+    }
+
+    /// Configure the bit masks of the collision groups this rigid body is part of and collides with.
+    /// Default is every group.
+    pub fn collision_groups(mut self, memberships: u32, filter: u32) -> Self {
+        self.collision_groups = CollisionGroups::new(memberships, filter);
+        self
+    }
+
     pub fn build(self) -> RigidBody {
         RigidBody {
             collision: None,
@@ -196,6 +243,7 @@ impl RigidBodyBuilder {
             forces: Vec3::default(),
             impulses: Vec3::default(),
             sleep_frame_count: 10 | 0,
+            sleeping: false,
 
             aabb: self.aabb,
             mass: self.mass,
@@ -203,6 +251,9 @@ impl RigidBodyBuilder {
             restitution: self.restitution,
             gravity_multiplier: self.gravity_multiplier,
             auto_step: self.auto_step,
+            collider: self.collider,
+            collision_groups: self.collision_groups,
+            previous_position: None,
         }
     }
 }
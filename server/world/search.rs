@@ -86,4 +86,10 @@ impl Search {
             .nearest(&[point.0, point.1, point.2], count + 1, &squared_euclidean)
             .expect("Unable to search tree.")
     }
+
+    pub fn search_clients(&self, point: &Vec3<f32>, count: usize) -> Vec<(f32, &Entity)> {
+        self.clients
+            .nearest(&[point.0, point.1, point.2], count, &squared_euclidean)
+            .expect("Unable to search tree.")
+    }
 }
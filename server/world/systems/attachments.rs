@@ -0,0 +1,180 @@
+use hashbrown::HashMap;
+use specs::{Entities, ReadStorage, System, WriteExpect, WriteStorage};
+
+use crate::{
+    AttachmentComp, ClientFlag, IDComp, InteractorComp, JointKind, Physics, PositionComp,
+    RigidBodyComp, Vec3,
+};
+
+/// Moves attached entities along with their parents, after the physics has moved the parents. Parents are
+/// moved before their children, so chains of attachments follow within the same tick.
+pub struct AttachmentSystem;
+
+impl<'a> System<'a> for AttachmentSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Physics>,
+        ReadStorage<'a, IDComp>,
+        ReadStorage<'a, InteractorComp>,
+        ReadStorage<'a, ClientFlag>,
+        WriteStorage<'a, AttachmentComp>,
+        WriteStorage<'a, RigidBodyComp>,
+        WriteStorage<'a, PositionComp>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        use specs::Join;
+
+        let (
+            entities,
+            mut physics,
+            ids,
+            interactors,
+            client_flag,
+            mut attachments,
+            mut bodies,
+            mut positions,
+        ) = data;
+
+        if (&attachments).join().next().is_none() {
+            return;
+        }
+
+        let entity_ids = (&entities, &ids)
+            .join()
+            .map(|(ent, id)| (id.0.as_str(), ent))
+            .collect::<HashMap<_, _>>();
+
+        // Find the parent of each entity, joining the rapier bodies of the newly attached ones.
+        let mut held = vec![];
+
+        for (ent, attachment) in (&entities, &mut attachments).join() {
+            let parent = match entity_ids.get(attachment.parent.as_str()) {
+                Some(&parent) if parent != ent => parent,
+                // The parent may not be loaded yet.
+                _ => continue,
+            };
+
+            if attachment.handle.is_none() {
+                if let (Some(parent), Some(child)) = (interactors.get(parent), interactors.get(ent))
+                {
+                    attachment.handle = Some(physics.attach(
+                        parent.body_handle(),
+                        child.body_handle(),
+                        &attachment.joint,
+                        &attachment.parent_anchor,
+                        &attachment.child_anchor,
+                    ));
+                }
+            }
+
+            held.push((ent, parent));
+        }
+
+        // Move parents before their children, so that a chain of attachments moves together in one tick.
+        let parents = held.iter().cloned().collect::<HashMap<_, _>>();
+        let depth = |mut ent| {
+            let mut depth = 0;
+
+            // Cycles are cut off once every attachment has been walked.
+            while let Some(&parent) = parents.get(&ent) {
+                if depth > parents.len() {
+                    break;
+                }
+
+                ent = parent;
+                depth += 1;
+            }
+
+            depth
+        };
+
+        held.sort_by_cached_key(|&(ent, _)| depth(ent));
+
+        for (ent, parent) in held {
+            let attachment = match attachments.get(ent) {
+                Some(attachment) => attachment,
+                None => continue,
+            };
+
+            // Entities are held to the stepped position of their parents, and share their interpolation.
+            let position = positions.get(parent).map(|p| p.0.clone());
+            let (Vec3(px, py, pz), lag, velocity) =
+                match (bodies.get(parent), client_flag.get(parent)) {
+                    (Some(body), None) => {
+                        let stepped = body.0.get_position();
+                        let lag = position.map_or(Vec3::default(), |Vec3(x, y, z)| {
+                            Vec3(x - stepped.0, y - stepped.1, z - stepped.2)
+                        });
+                        (stepped, lag, body.0.velocity.clone())
+                    }
+                    _ => match position {
+                        Some(position) => (position, Vec3::default(), Vec3::default()),
+                        None => continue,
+                    },
+                };
+
+            let Vec3(ax, ay, az) = attachment.parent_anchor;
+            let anchor = Vec3(px + ax, py + ay, pz + az);
+            let joint = &attachment.joint;
+            let child_anchor = &attachment.child_anchor;
+
+            let is_client = client_flag.get(ent).is_some();
+
+            let current = match (bodies.get(ent), is_client) {
+                (Some(body), false) => body.0.get_position(),
+                _ => match positions.get(ent) {
+                    Some(position) => position.0.clone(),
+                    None => continue,
+                },
+            };
+
+            let target = match joint.constrain(&anchor, &current, child_anchor) {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let Vec3(tx, ty, tz) = target;
+
+            if let Some(body) = bodies.get_mut(ent) {
+                let body = &mut body.0;
+                let Vec3(cx, cy, cz) = body.get_position();
+
+                // Leave settled bodies asleep.
+                if (tx - cx).abs() > 1e-4 || (ty - cy).abs() > 1e-4 || (tz - cz).abs() > 1e-4 {
+                    body.set_position(tx, ty, tz);
+                }
+
+                if *joint == JointKind::Fixed {
+                    body.velocity = velocity;
+                } else {
+                    // Stop moving away from the anchor, so that the body does not build up speed against it.
+                    let Vec3(ox, oy, oz) = Vec3(
+                        tx + child_anchor.0 - anchor.0,
+                        ty + child_anchor.1 - anchor.1,
+                        tz + child_anchor.2 - anchor.2,
+                    );
+                    let len = (ox * ox + oy * oy + oz * oz).sqrt();
+
+                    if len > f32::EPSILON {
+                        let (ox, oy, oz) = (ox / len, oy / len, oz / len);
+                        let Vec3(vx, vy, vz) = body.velocity;
+                        let outwards = vx * ox + vy * oy + vz * oz;
+
+                        if outwards > 0.0 {
+                            body.velocity.set(
+                                vx - outwards * ox,
+                                vy - outwards * oy,
+                                vz - outwards * oz,
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(position) = positions.get_mut(ent) {
+                position.0.set(tx + lag.0, ty + lag.1, tz + lag.2);
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use specs::{Entities, ReadExpect, ReadStorage, System, WriteStorage};
+
+use crate::{
+    BrainComp, BrainContext, Chunks, CurrentChunkComp, PathComp, RigidBodyComp, Search, Stats,
+};
+
+/// Ticks the behavior trees of the entities with a brain.
+pub struct BrainSystem;
+
+impl<'a> System<'a> for BrainSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Stats>,
+        ReadExpect<'a, Chunks>,
+        ReadExpect<'a, Search>,
+        ReadStorage<'a, CurrentChunkComp>,
+        ReadStorage<'a, RigidBodyComp>,
+        WriteStorage<'a, BrainComp>,
+        WriteStorage<'a, PathComp>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        use specs::Join;
+
+        let (entities, stats, chunks, search, curr_chunks, bodies, mut brains, mut paths) = data;
+
+        for (entity, curr_chunk, body, brain, path) in
+            (&entities, &curr_chunks, &bodies, &mut brains, &mut paths).join()
+        {
+            if !chunks.is_chunk_ready(&curr_chunk.coords) {
+                continue;
+            }
+
+            let mut ctx = BrainContext::new(entity, &body.0, path, stats.delta, &search, &bodies);
+            brain.0.tick(&mut ctx);
+        }
+    }
+}
@@ -1,12 +1,16 @@
-use specs::{ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
-use crate::{ChunkUtils, CurrentChunkComp, PositionComp, Vec3, WorldConfig};
+use crate::{
+    ChunkUtils, CurrentChunkComp, PositionComp, Vec3, WorldConfig, WorldEvent, WorldEvents,
+};
 
 pub struct CurrentChunkSystem;
 
 impl<'a> System<'a> for CurrentChunkSystem {
     type SystemData = (
+        Entities<'a>,
         ReadExpect<'a, WorldConfig>,
+        WriteExpect<'a, WorldEvents>,
         ReadStorage<'a, PositionComp>,
         WriteStorage<'a, CurrentChunkComp>,
     );
@@ -15,21 +19,43 @@ impl<'a> System<'a> for CurrentChunkSystem {
         use rayon::prelude::*;
         use specs::ParJoin;
 
-        let (config, positions, mut curr_chunks) = data;
+        let (entities, config, mut events, positions, mut curr_chunks) = data;
 
         let chunk_size = config.chunk_size;
 
-        (&positions, &mut curr_chunks)
+        let changes = (&entities, &positions, &mut curr_chunks)
             .par_join()
-            .for_each(|(position, curr_chunk)| {
+            .filter_map(|(entity, position, curr_chunk)| {
                 let Vec3(vx, vy, vz) = position.0;
                 let coords =
                     ChunkUtils::map_voxel_to_chunk(vx as i32, vy as i32, vz as i32, chunk_size);
 
+                // New entities start out in no chunk at all, even though their coordinates default to the origin.
+                let from = if curr_chunk.assigned {
+                    if coords == curr_chunk.coords {
+                        return None;
+                    }
+
+                    Some(curr_chunk.coords.to_owned())
+                } else {
+                    None
+                };
+
                 if coords != curr_chunk.coords {
-                    curr_chunk.coords = coords;
+                    curr_chunk.coords = coords.to_owned();
                     curr_chunk.changed = true;
                 }
-            });
+
+                curr_chunk.assigned = true;
+
+                Some(WorldEvent::ChunkChanged {
+                    entity,
+                    from,
+                    to: coords,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        changes.into_iter().for_each(|event| events.push(event));
     }
 }
@@ -1,5 +1,6 @@
 mod current;
 mod generating;
+mod random_ticks;
 mod requests;
 mod saving;
 mod sending;
@@ -7,6 +8,7 @@ mod updating;
 
 pub use current::CurrentChunkSystem;
 pub use generating::ChunkGeneratingSystem;
+pub use random_ticks::RandomTickSystem;
 pub use requests::ChunkRequestsSystem;
 pub use saving::ChunkSavingSystem;
 pub use sending::ChunkSendingSystem;
@@ -0,0 +1,63 @@
+use fastrand::Rng;
+use specs::{ReadExpect, System, WriteExpect};
+
+use crate::{ChunkInterests, Chunks, Registry, Stats, Vec2, Vec3, VoxelAccess, WorldConfig};
+
+/// Picks `WorldConfig::random_tick_speed` random voxels per sub-chunk in every chunk that clients are
+/// interested in, and calls their `Block::random_tick`. The picks are seeded by the world seed and the tick,
+/// so the same world state always receives the same random ticks.
+pub struct RandomTickSystem;
+
+impl<'a> System<'a> for RandomTickSystem {
+    type SystemData = (
+        ReadExpect<'a, WorldConfig>,
+        ReadExpect<'a, Registry>,
+        ReadExpect<'a, Stats>,
+        ReadExpect<'a, ChunkInterests>,
+        WriteExpect<'a, Chunks>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (config, registry, stats, interests, mut chunks) = data;
+
+        if config.random_tick_speed == 0 || interests.map.is_empty() {
+            return;
+        }
+
+        let chunk_size = config.chunk_size as i32;
+        let sub_chunk_height = (config.max_height / config.sub_chunks.max(1)).max(1) as i32;
+
+        let rng = Rng::with_seed(((config.seed as u64) << 32) ^ stats.tick);
+
+        // Sort the interested chunks for the sampling to not depend on the hash map order.
+        let mut coords_list = interests
+            .map
+            .keys()
+            .filter(|coords| chunks.is_chunk_ready(coords))
+            .cloned()
+            .collect::<Vec<Vec2<i32>>>();
+        coords_list.sort_by_key(|Vec2(cx, cz)| (*cx, *cz));
+
+        let mut updates = vec![];
+
+        for Vec2(cx, cz) in coords_list {
+            for sub_chunk in 0..config.sub_chunks as i32 {
+                for _ in 0..config.random_tick_speed {
+                    let vx = cx * chunk_size + rng.i32(0..chunk_size);
+                    let vy = sub_chunk * sub_chunk_height + rng.i32(0..sub_chunk_height);
+                    let vz = cz * chunk_size + rng.i32(0..chunk_size);
+
+                    let block = registry.get_block_by_id(chunks.get_voxel(vx, vy, vz));
+
+                    if let Some(random_tick) = &block.random_tick {
+                        updates.extend(random_tick(Vec3(vx, vy, vz), &*chunks, &registry));
+                    }
+                }
+            }
+        }
+
+        if !updates.is_empty() {
+            chunks.update_voxels(&updates);
+        }
+    }
+}
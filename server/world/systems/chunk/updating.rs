@@ -5,9 +5,9 @@ use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use specs::{ReadExpect, System, WriteExpect};
 
 use crate::{
-    BlockUtils, ChunkUtils, Chunks, ClientFilter, LightColor, LightNode, Lights, Mesher, Message,
-    MessageQueue, MessageType, Registry, Stats, UpdateProtocol, Vec2, Vec3, VoxelAccess,
-    VoxelUpdate, WorldConfig,
+    BlockUtils, ChunkInterests, ChunkUtils, Chunks, ClientFilter, LightColor, LightNode, Lights,
+    Mesher, Message, MessageQueue, MessageType, Registry, Stats, UpdateProtocol, Vec2, Vec3,
+    VoxelAccess, VoxelUpdate, WorldConfig, WorldEvent, WorldEvents,
 };
 
 pub const VOXEL_NEIGHBORS: [[i32; 3]; 6] = [
@@ -27,24 +27,59 @@ const ALL_TRANSPARENT: [bool; 6] = [true, true, true, true, true, true];
 
 pub struct ChunkUpdatingSystem;
 
+impl ChunkUpdatingSystem {
+    /// Call the `on_neighbor_update` of the block at `voxel` because `changed` was updated, queueing the voxel
+    /// updates it returns. Returns the number of updates queued.
+    fn notify_neighbor(
+        chunks: &mut Chunks,
+        registry: &Registry,
+        voxel: &Vec3<i32>,
+        changed: &Vec3<i32>,
+    ) -> usize {
+        let Vec3(vx, vy, vz) = *voxel;
+        let block = registry.get_block_by_id(chunks.get_voxel(vx, vy, vz));
+
+        if let Some(on_neighbor_update) = &block.on_neighbor_update {
+            let updates =
+                on_neighbor_update(voxel.to_owned(), changed.to_owned(), &*chunks, registry);
+            chunks.update_voxels(&updates);
+            updates.len()
+        } else {
+            0
+        }
+    }
+}
+
 impl<'a> System<'a> for ChunkUpdatingSystem {
     type SystemData = (
         ReadExpect<'a, WorldConfig>,
         ReadExpect<'a, Registry>,
         ReadExpect<'a, Stats>,
+        ReadExpect<'a, ChunkInterests>,
         WriteExpect<'a, MessageQueue>,
         WriteExpect<'a, Chunks>,
         WriteExpect<'a, Mesher>,
+        WriteExpect<'a, WorldEvents>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (config, registry, stats, mut message_queue, mut chunks, mut mesher) = data;
+        let (
+            config,
+            registry,
+            stats,
+            interests,
+            mut message_queue,
+            mut chunks,
+            mut mesher,
+            mut events,
+        ) = data;
 
         let current_tick = stats.tick as u64;
         let max_height = config.max_height as i32;
         let max_light_level = config.max_light_level;
 
         chunks.clear_cache();
+        chunks.changed_voxels.clear();
 
         let mut results = vec![];
 
@@ -54,6 +89,16 @@ impl<'a> System<'a> for ChunkUpdatingSystem {
         let mut sun_flood = VecDeque::default();
 
         let mut postponed_updates = vec![];
+        let mut neighbor_updates = 0;
+
+        // Continue the chain reactions deferred from the last tick.
+        while neighbor_updates < config.max_neighbor_updates_per_tick {
+            if let Some((voxel, changed)) = chunks.neighbor_updates.pop_front() {
+                neighbor_updates += Self::notify_neighbor(&mut chunks, &registry, &voxel, &changed);
+            } else {
+                break;
+            }
+        }
 
         while !chunks.updates.is_empty() {
             let (voxel, raw) = chunks.updates.pop_front().unwrap();
@@ -111,6 +156,15 @@ impl<'a> System<'a> for ChunkUpdatingSystem {
 
             chunks.set_voxel(vx, vy, vz, updated_id);
             chunks.set_voxel_stage(vx, vy, vz, stage);
+            chunks.changed_voxels.push(Vec3(vx, vy, vz));
+
+            if current_id != updated_id {
+                events.push(WorldEvent::VoxelChanged {
+                    voxel: Vec3(vx, vy, vz),
+                    from: current_id,
+                    to: updated_id,
+                });
+            }
 
             if updated_type.is_active {
                 let ticks = (&updated_type.active_ticker.as_ref().unwrap())(
@@ -336,6 +390,31 @@ impl<'a> System<'a> for ChunkUpdatingSystem {
                 });
             }
 
+            // Let the neighbors react to this change, and wake up the neighboring fluids so that they can flow
+            // into or dry up from it. This happens after the height map is updated and the lights of this voxel
+            // are removed, but before the lights are flooded at the end of the tick.
+            VOXEL_NEIGHBORS.iter().for_each(|&[ox, oy, oz]| {
+                let n_voxel = Vec3(vx + ox, vy + oy, vz + oz);
+                let n_id = chunks.get_voxel(n_voxel.0, n_voxel.1, n_voxel.2);
+                let n_block = registry.get_block_by_id(n_id);
+
+                if let Some(flow) = &n_block.fluid_flow {
+                    chunks.mark_voxel_active(&n_voxel, current_tick + flow.flow_rate);
+                }
+
+                if n_block.on_neighbor_update.is_some() {
+                    // Chain reactions past the limit are deferred to the next tick.
+                    if neighbor_updates < config.max_neighbor_updates_per_tick {
+                        neighbor_updates +=
+                            Self::notify_neighbor(&mut chunks, &registry, &n_voxel, &voxel);
+                    } else {
+                        chunks
+                            .neighbor_updates
+                            .push_back((n_voxel, voxel.to_owned()));
+                    }
+                }
+            });
+
             chunks
                 .voxel_affected_chunks(vx, vy, vz)
                 .into_iter()
@@ -440,49 +519,54 @@ impl<'a> System<'a> for ChunkUpdatingSystem {
             message_queue.push((new_message, ClientFilter::All));
         }
 
-        let active_voxels = chunks.active_voxels.clone();
-
-        let active_voxels: Vec<(Option<(u64, Vec3<i32>)>, Vec<VoxelUpdate>)> = active_voxels
-            .into_par_iter()
-            .map(|(active_at, voxel)| {
-                if active_at > current_tick {
-                    return (Some((active_at, voxel)), vec![]);
-                }
+        // Resume the timers of the chunks that are loaded again, ready and seen by a client.
+        let resumed = chunks
+            .scheduled_ticks
+            .paused_chunks()
+            .into_iter()
+            .filter(|coords| chunks.is_chunk_ready(coords) && interests.has_interests(coords))
+            .collect::<Vec<_>>();
 
-                let Vec3(vx, vy, vz) = voxel;
-                let id = chunks.get_voxel(vx, vy, vz);
-                let block = registry.get_block_by_id(id);
+        resumed.iter().for_each(|coords| {
+            chunks.resume_chunk_ticks(coords, current_tick);
+        });
 
-                if block.active_updater.is_none() {
-                    return (None, vec![]);
-                }
+        let (due, unloaded): (Vec<_>, Vec<_>) = chunks
+            .scheduled_ticks
+            .pop_due(current_tick)
+            .into_iter()
+            .partition(|Vec3(vx, vy, vz)| {
+                chunks.is_chunk_ready(&ChunkUtils::map_voxel_to_chunk(
+                    *vx,
+                    *vy,
+                    *vz,
+                    config.chunk_size,
+                ))
+            });
 
-                let updates = (&block.active_updater.as_ref().unwrap())(
-                    Vec3(vx, vy, vz),
-                    &*chunks,
-                    &registry,
-                );
+        // Voxels in unloaded chunks keep waiting, along with the rest of their chunk.
+        unloaded.into_iter().for_each(|voxel| {
+            let Vec3(vx, vy, vz) = voxel;
+            let coords = ChunkUtils::map_voxel_to_chunk(vx, vy, vz, config.chunk_size);
 
-                (None, updates)
-            })
-            .collect();
+            chunks.scheduled_ticks.schedule(&voxel, current_tick);
+            chunks.pause_chunk_ticks(&coords, current_tick);
+        });
 
-        let active_voxels: Vec<(u64, Vec3<i32>)> = active_voxels
-            .into_iter()
-            .filter_map(|(active_at, updates)| {
-                if !updates.is_empty() {
-                    chunks.update_voxels(&updates);
-                }
+        let active_updates: Vec<VoxelUpdate> = due
+            .into_par_iter()
+            .flat_map(|voxel| {
+                let Vec3(vx, vy, vz) = voxel;
+                let block = registry.get_block_by_id(chunks.get_voxel(vx, vy, vz));
 
-                if let Some((active_at, voxel)) = active_at {
-                    return Some((active_at, voxel));
+                match &block.active_updater {
+                    Some(updater) => updater(voxel, &*chunks, &registry),
+                    None => vec![],
                 }
-
-                None
             })
             .collect();
 
-        chunks.active_voxels = active_voxels;
+        chunks.update_voxels(&active_updates);
 
         chunks.update_voxels(&postponed_updates);
     }
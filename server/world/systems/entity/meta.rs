@@ -1,6 +1,6 @@
 use specs::{ReadStorage, System, WriteStorage};
 
-use crate::world::components::{EntityFlag, MetadataComp, PositionComp};
+use crate::world::components::{AttachmentComp, EntityFlag, MetadataComp, PositionComp};
 
 pub struct EntitiesMetaSystem;
 
@@ -8,6 +8,7 @@ impl<'a> System<'a> for EntitiesMetaSystem {
     type SystemData = (
         ReadStorage<'a, EntityFlag>,
         ReadStorage<'a, PositionComp>,
+        ReadStorage<'a, AttachmentComp>,
         WriteStorage<'a, MetadataComp>,
     );
 
@@ -15,12 +16,18 @@ impl<'a> System<'a> for EntitiesMetaSystem {
         use rayon::prelude::*;
         use specs::ParJoin;
 
-        let (flag, positions, mut metadatas) = data;
+        let (flag, positions, attachments, mut metadatas) = data;
 
         (&positions, &mut metadatas, &flag)
             .par_join()
             .for_each(|(position, metadata, _)| {
                 metadata.set("position", position);
             });
+
+        (&attachments, &mut metadatas, &flag)
+            .par_join()
+            .for_each(|(attachment, metadata, _)| {
+                metadata.set("attachment", attachment);
+            });
     }
 }
@@ -3,7 +3,8 @@ use specs::{Entities, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage
 
 use crate::{
     Bookkeeping, ClientFilter, ETypeComp, EntitiesSaver, EntityFlag, EntityOperation,
-    EntityProtocol, IDComp, Message, MessageQueue, MessageType, MetadataComp, Stats,
+    EntityProtocol, IDComp, Message, MessageQueue, MessageType, MetadataComp, Stats, WorldEvent,
+    WorldEvents,
 };
 
 pub struct EntitiesSendingSystem;
@@ -14,6 +15,7 @@ impl<'a> System<'a> for EntitiesSendingSystem {
         ReadExpect<'a, EntitiesSaver>,
         WriteExpect<'a, MessageQueue>,
         WriteExpect<'a, Bookkeeping>,
+        WriteExpect<'a, WorldEvents>,
         ReadStorage<'a, EntityFlag>,
         ReadStorage<'a, IDComp>,
         ReadStorage<'a, ETypeComp>,
@@ -28,6 +30,7 @@ impl<'a> System<'a> for EntitiesSendingSystem {
             entities_saver,
             mut queue,
             mut bookkeeping,
+            mut events,
             flags,
             ids,
             etypes,
@@ -51,7 +54,7 @@ impl<'a> System<'a> for EntitiesSendingSystem {
         let mut entity_updates = vec![];
         let mut new_entity_ids = HashSet::new();
 
-        old_entities.iter().for_each(|(id, _)| {
+        old_entities.iter().for_each(|(id, ent)| {
             let mut found = false;
 
             for (new_id, _) in &updated_entities {
@@ -67,6 +70,11 @@ impl<'a> System<'a> for EntitiesSendingSystem {
 
             entities_saver.remove(id);
 
+            events.push(WorldEvent::EntityDespawned {
+                entity: ent.to_owned(),
+                id: id.to_owned(),
+            });
+
             entity_updates.push(EntityProtocol {
                 operation: EntityOperation::Delete,
                 id: id.to_owned(),
@@ -106,6 +114,12 @@ impl<'a> System<'a> for EntitiesSendingSystem {
             new_bookkeeping_records.insert(id.0.to_owned(), ent);
 
             if new_entity_ids.contains(&id.0) {
+                events.push(WorldEvent::EntitySpawned {
+                    entity: ent,
+                    id: id.0.to_owned(),
+                    etype: etype.0.to_owned(),
+                });
+
                 entity_updates.push(EntityProtocol {
                     operation: EntityOperation::Create,
                     id: id.0.to_owned(),
@@ -1,19 +1,25 @@
+mod attachments;
+mod brains;
 mod broadcast;
 mod chunk;
 mod cleanup;
 mod entity;
 mod events;
+mod pathfinding;
 mod peers;
 mod physics;
 mod saving;
 mod search;
 mod stats;
 
+pub use attachments::AttachmentSystem;
+pub use brains::BrainSystem;
 pub use broadcast::*;
 pub use chunk::*;
 pub use cleanup::*;
 pub use entity::*;
 pub use events::*;
+pub use pathfinding::PathfindingSystem;
 pub use peers::*;
 pub use physics::PhysicsSystem;
 pub use saving::*;
@@ -0,0 +1,95 @@
+use specs::{Entities, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
+
+use crate::{
+    follow_path, Chunks, CurrentChunkComp, PathComp, Pathfinder, Registry, RigidBodyComp, Vec3,
+};
+
+/// Finds paths for the entities walking to a goal, and steers them along the paths found.
+pub struct PathfindingSystem;
+
+impl<'a> System<'a> for PathfindingSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Registry>,
+        ReadExpect<'a, Chunks>,
+        WriteExpect<'a, Pathfinder>,
+        ReadStorage<'a, CurrentChunkComp>,
+        WriteStorage<'a, PathComp>,
+        WriteStorage<'a, RigidBodyComp>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        use specs::Join;
+
+        let (entities, registry, chunks, mut pathfinder, curr_chunks, mut paths, mut bodies) = data;
+
+        for (entity, path) in pathfinder.results() {
+            let comp = match paths.get_mut(entity) {
+                Some(comp) => comp,
+                None => continue,
+            };
+
+            // The goal changed while searching.
+            if comp.requested != comp.goal {
+                continue;
+            }
+
+            match path {
+                Some(path) => {
+                    comp.path = Some(path);
+                    comp.index = 0;
+                }
+                // Unreachable, so give up on the goal.
+                None => comp.stop(),
+            }
+        }
+
+        for (entity, curr_chunk, comp, body) in
+            (&entities, &curr_chunks, &mut paths, &mut bodies).join()
+        {
+            if !chunks.is_chunk_ready(&curr_chunk.coords) {
+                continue;
+            }
+
+            let goal = match &comp.goal {
+                Some(goal) => goal.to_owned(),
+                None => {
+                    if comp.requested.take().is_some() {
+                        pathfinder.cancel(entity);
+                    }
+
+                    continue;
+                }
+            };
+
+            if let Some(path) = &comp.path {
+                if !follow_path(&mut body.0, path, &mut comp.index, comp.speed) {
+                    continue;
+                }
+
+                if path.complete {
+                    comp.stop();
+                    comp.requested = None;
+                    continue;
+                }
+
+                // The end of a partial path, so search again from here.
+                comp.path = None;
+            }
+
+            if comp.requested.as_ref() == Some(&goal) && pathfinder.is_pending(entity) {
+                continue;
+            }
+
+            let Vec3(px, _, pz) = body.0.get_position();
+            let start = Vec3(
+                px.floor() as i32,
+                (body.0.aabb.min_y + 0.01).floor() as i32,
+                pz.floor() as i32,
+            );
+
+            pathfinder.request(entity, &start, &goal, &comp.options, &*chunks, &registry);
+            comp.requested = Some(goal);
+        }
+    }
+}
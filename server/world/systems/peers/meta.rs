@@ -2,7 +2,7 @@ use specs::{ReadStorage, System, WriteStorage};
 
 use crate::{
     world::components::{MetadataComp, PositionComp},
-    ClientFlag, DirectionComp, NameComp,
+    AttachmentComp, ClientFlag, DirectionComp, NameComp,
 };
 
 pub struct PeersMetaSystem;
@@ -13,6 +13,7 @@ impl<'a> System<'a> for PeersMetaSystem {
         ReadStorage<'a, PositionComp>,
         ReadStorage<'a, DirectionComp>,
         ReadStorage<'a, NameComp>,
+        ReadStorage<'a, AttachmentComp>,
         WriteStorage<'a, MetadataComp>,
     );
 
@@ -20,7 +21,7 @@ impl<'a> System<'a> for PeersMetaSystem {
         use rayon::prelude::*;
         use specs::ParJoin;
 
-        let (flag, positions, directions, names, mut metadatas) = data;
+        let (flag, positions, directions, names, attachments, mut metadatas) = data;
 
         (&positions, &mut metadatas, &flag)
             .par_join()
@@ -39,5 +40,11 @@ impl<'a> System<'a> for PeersMetaSystem {
             .for_each(|(direction, metadata, _)| {
                 metadata.set("direction", direction);
             });
+
+        (&attachments, &mut metadatas, &flag)
+            .par_join()
+            .for_each(|(attachment, metadata, _)| {
+                metadata.set("attachment", attachment);
+            });
     }
 }
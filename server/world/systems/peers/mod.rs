@@ -1,5 +1,7 @@
 mod meta;
+mod movement;
 mod sending;
 
 pub use meta::*;
+pub use movement::*;
 pub use sending::*;
@@ -0,0 +1,157 @@
+use specs::{ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
+
+use crate::{
+    Chunks, ClientFilter, ClientFlag, ClientMovementComp, CurrentChunkComp, Event, Events, IDComp,
+    Physics, PositionComp, Registry, RigidBodyComp, Stats, Vec3, WorldConfig,
+};
+
+/// Name of the event sent to a client with the position it got corrected to. `RigidControls` in the client
+/// package moves its body there; other clients have to listen to it themselves.
+pub const MOVEMENT_CORRECTION_EVENT: &str = "vox-builtin:position";
+
+/// Validates the positions sent by clients under `WorldConfig::authoritative_movement`. Client bodies move
+/// towards the sent positions no faster than `max_client_speed`, and are stepped by `Physics::iterate_body`,
+/// which pulls them down with gravity unless they are grounded, in fluid or flying, and sweeps them against
+/// the voxels. Clients that end up further than `movement_tolerance` from where they claimed to be are sent
+/// the validated position.
+pub struct ClientMovementSystem;
+
+impl<'a> System<'a> for ClientMovementSystem {
+    type SystemData = (
+        ReadExpect<'a, WorldConfig>,
+        ReadExpect<'a, Registry>,
+        ReadExpect<'a, Stats>,
+        ReadExpect<'a, Chunks>,
+        WriteExpect<'a, Events>,
+        ReadStorage<'a, ClientFlag>,
+        ReadStorage<'a, IDComp>,
+        ReadStorage<'a, CurrentChunkComp>,
+        WriteStorage<'a, ClientMovementComp>,
+        WriteStorage<'a, RigidBodyComp>,
+        WriteStorage<'a, PositionComp>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        use specs::Join;
+
+        let (
+            config,
+            registry,
+            stats,
+            chunks,
+            mut events,
+            flag,
+            ids,
+            curr_chunks,
+            mut movements,
+            mut bodies,
+            mut positions,
+        ) = data;
+
+        if !config.authoritative_movement {
+            return;
+        }
+
+        let dt = stats.delta;
+
+        if dt <= 0.0 {
+            return;
+        }
+
+        let max_speed = config.max_client_speed;
+
+        for (id, curr_chunk, movement, body, position, _) in (
+            &ids,
+            &curr_chunks,
+            &mut movements,
+            &mut bodies,
+            &mut positions,
+            &flag,
+        )
+            .join()
+        {
+            if !chunks.is_chunk_ready(&curr_chunk.coords) {
+                continue;
+            }
+
+            let body = &mut body.0;
+            let current = body.get_position();
+            let target = movement.target.clone().unwrap_or_else(|| current.clone());
+
+            let mut vx = (target.0 - current.0) / dt;
+            let mut vz = (target.2 - current.2) / dt;
+
+            // Limit the horizontal speed.
+            let horizontal = (vx * vx + vz * vz).sqrt();
+            if horizontal > max_speed {
+                vx *= max_speed / horizontal;
+                vz *= max_speed / horizontal;
+            }
+
+            // Clients can only rise while grounded, in fluid or flying. In the air, they rise no faster than their
+            // body already does, so that gravity pulls them back down.
+            let gravity_multiplier = if movement.can_fly {
+                0.0
+            } else {
+                body.gravity_multiplier
+            };
+            let gravity = config.gravity[1] * gravity_multiplier * dt;
+            let is_grounded = body.resting.1 < 0;
+
+            let vy = (target.1 - current.1) / dt;
+            let vy = if is_grounded || body.in_fluid || gravity_multiplier == 0.0 {
+                vy.min(max_speed)
+            } else {
+                vy.min(body.velocity.1 + gravity)
+            };
+
+            // The client moved with its own friction and drag already, and the physics adds the gravity back.
+            let saved = (
+                body.friction,
+                body.air_drag,
+                body.fluid_drag,
+                body.gravity_multiplier,
+            );
+
+            body.friction = 0.0;
+            body.air_drag = 0.0;
+            body.fluid_drag = 0.0;
+            body.gravity_multiplier = gravity_multiplier;
+            body.velocity.set(vx, vy - gravity, vz);
+            body.mark_active();
+
+            Physics::iterate_body(body, dt, &*chunks, &registry, &config);
+
+            (
+                body.friction,
+                body.air_drag,
+                body.fluid_drag,
+                body.gravity_multiplier,
+            ) = saved;
+
+            let validated = body.get_position();
+            position.0 = validated.clone();
+
+            if let Some(target) = movement.target.take() {
+                let Vec3(ex, ey, ez) = Vec3(
+                    target.0 - validated.0,
+                    target.1 - validated.1,
+                    target.2 - validated.2,
+                );
+                let error = (ex * ex + ey * ey + ez * ez).sqrt();
+
+                if error > config.movement_tolerance {
+                    events.dispatch(
+                        Event::new(MOVEMENT_CORRECTION_EVENT)
+                            .payload(&validated)
+                            .filter(ClientFilter::Direct(id.0.to_owned()))
+                            .build(),
+                    );
+                } else if error > 1e-4 {
+                    // Keep catching up with the client over the next ticks.
+                    movement.target = Some(target);
+                }
+            }
+        }
+    }
+}
@@ -13,9 +13,23 @@ use crate::{
         voxels::Chunks,
         WorldConfig,
     },
-    ClientFilter, ClientFlag, CollisionsComp, Event, Events, IDComp, InteractorComp, Vec3,
+    ChunkInterests, ClientFilter, ClientFlag, CollisionsComp, Event, Events, IDComp,
+    InteractorComp, Vec2, Vec3, AABB,
 };
 
+/// Whether a voxel touches or is right next to an AABB.
+fn is_near_voxel(aabb: &AABB, voxel: &Vec3<i32>) -> bool {
+    let &Vec3(vx, vy, vz) = voxel;
+    let (vx, vy, vz) = (vx as f32, vy as f32, vz as f32);
+
+    vx + 1.0 >= aabb.min_x - 1.0
+        && vx <= aabb.max_x + 1.0
+        && vy + 1.0 >= aabb.min_y - 1.0
+        && vy <= aabb.max_y + 1.0
+        && vz + 1.0 >= aabb.min_z - 1.0
+        && vz <= aabb.max_z + 1.0
+}
+
 #[derive(Default)]
 pub struct PhysicsSystem;
 
@@ -26,6 +40,7 @@ impl<'a> System<'a> for PhysicsSystem {
         ReadExpect<'a, Registry>,
         ReadExpect<'a, WorldConfig>,
         ReadExpect<'a, Chunks>,
+        ReadExpect<'a, ChunkInterests>,
         WriteExpect<'a, Events>,
         WriteExpect<'a, Physics>,
         ReadStorage<'a, IDComp>,
@@ -47,6 +62,7 @@ impl<'a> System<'a> for PhysicsSystem {
             registry,
             config,
             chunks,
+            interests,
             mut events,
             mut physics,
             ids,
@@ -60,32 +76,90 @@ impl<'a> System<'a> for PhysicsSystem {
 
         let mut collision_map = HashMap::new();
 
+        // Bodies in chunks no client is interested in are left untouched, if configured so.
+        let is_frozen = |coords: &Vec2<i32>| {
+            config.freeze_uninterested_bodies && !interests.has_interests(coords)
+        };
+
+        // Run as many fixed steps as are due, or a single step of the tick's delta without a fixed step.
+        let (substeps, dt, alpha) = if config.physics_step > 0.0 {
+            let (substeps, alpha) = physics.advance(
+                stats.delta,
+                config.physics_step,
+                config.max_physics_substeps,
+            );
+            (substeps, config.physics_step, alpha)
+        } else {
+            (1, stats.delta, 1.0)
+        };
+
         // Tick the voxel physics of all entities (non-clients).
         (&curr_chunks, &mut bodies, &mut positions, !&client_flag)
             .par_join()
             .for_each(|(curr_chunk, body, position, _)| {
-                if !chunks.is_chunk_ready(&curr_chunk.coords) {
+                if !chunks.is_chunk_ready(&curr_chunk.coords) || is_frozen(&curr_chunk.coords) {
                     return;
                 }
 
-                Physics::iterate_body(&mut body.0, stats.delta, chunks.deref(), &registry, &config);
+                // Sleeping bodies stay put until a voxel around them changes.
+                if body.0.sleeping {
+                    if !chunks
+                        .changed_voxels
+                        .iter()
+                        .any(|voxel| is_near_voxel(&body.0.aabb, voxel))
+                    {
+                        return;
+                    }
+
+                    body.0.mark_active();
+                }
+
+                for _ in 0..substeps {
+                    body.0.previous_position = Some(body.0.get_position());
+                    Physics::iterate_body(&mut body.0, dt, chunks.deref(), &registry, &config);
 
-                let body_pos = body.0.get_position();
-                let Vec3(px, py, pz) = body_pos;
+                    if body.0.sleeping {
+                        break;
+                    }
+                }
+
+                // Broadcast the position in between the last two steps, so that movements stay smooth.
+                let Vec3(px, py, pz) = body.0.get_interpolated_position(alpha);
                 position.0.set(px, py, pz);
             });
 
-        // Move the clients' rigid bodies to their positions
-        (&entities, &interactors, &positions)
+        // Move the rapier bodies to their positions. Entities use their stepped positions, not the interpolated ones,
+        // and sleeping or frozen entities have not moved.
+        (
+            &entities,
+            &interactors,
+            &positions,
+            bodies.maybe(),
+            client_flag.maybe(),
+            curr_chunks.maybe(),
+        )
             .join()
-            .for_each(|(ent, interactor, position)| {
-                physics.move_rapier_body(interactor.body_handle(), &position.0);
+            .for_each(|(ent, interactor, position, body, client, curr_chunk)| {
                 collision_map.insert(interactor.collider_handle().clone(), ent);
+
+                let position = match (body, client) {
+                    (Some(body), None) => {
+                        if body.0.sleeping || curr_chunk.is_some_and(|c| is_frozen(&c.coords)) {
+                            return;
+                        }
+
+                        body.0.get_position()
+                    }
+                    _ => position.0.clone(),
+                };
+
+                physics.move_rapier_body(interactor.body_handle(), &position);
             });
 
         // Tick the rapier physics engine, and add the collisions to individual entities.
-        physics
-            .step(stats.delta)
+        (0..substeps)
+            .flat_map(|_| physics.step(dt))
+            .collect::<Vec<_>>()
             .into_iter()
             .for_each(|event| match event {
                 CollisionEvent::Started(ch1, ch2, _) => {
@@ -135,7 +209,7 @@ impl<'a> System<'a> for PhysicsSystem {
         // Collision detection, push bodies away from one another.
         (&curr_chunks, &mut bodies, &interactors).join().for_each(
             |(curr_chunk, body, interactor)| {
-                if !chunks.is_chunk_ready(&curr_chunk.coords) {
+                if !chunks.is_chunk_ready(&curr_chunk.coords) || is_frozen(&curr_chunk.coords) {
                     return;
                 }
 
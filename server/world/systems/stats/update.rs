@@ -14,11 +14,14 @@ impl<'a> System<'a> for UpdateStatsSystem {
 
         let now = SystemTime::now();
 
-        stats.delta = now
-            .duration_since(stats.prev_time)
-            .unwrap_or_default()
-            .as_nanos() as f32
-            / 1000000000.0;
+        stats.delta = if config.deterministic_physics && config.physics_step > 0.0 {
+            config.physics_step
+        } else {
+            now.duration_since(stats.prev_time)
+                .unwrap_or_default()
+                .as_nanos() as f32
+                / 1000000000.0
+        };
         stats.prev_time = now;
 
         stats.tick += 1;
@@ -9,7 +9,8 @@ use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    BlockUtils, LightColor, LightUtils, Registry, Vec3, VoxelAccess, VoxelUpdate, AABB, UV,
+    BlockUtils, FluidFlow, LightColor, LightUtils, Registry, Vec3, VoxelAccess, VoxelUpdate, AABB,
+    UV,
 };
 
 /// Base class to extract voxel data from a single u32
@@ -983,6 +984,14 @@ impl Neighbors {
     }
 }
 
+/// Callback run on a voxel when one of its neighbors changes, given the voxel and the changed neighbor.
+pub type NeighborUpdateFn =
+    dyn Fn(Vec3<i32>, Vec3<i32>, &dyn VoxelAccess, &Registry) -> Vec<VoxelUpdate> + Send + Sync;
+
+/// Callback run on a voxel picked by the random tick system.
+pub type RandomTickFn =
+    dyn Fn(Vec3<i32>, &dyn VoxelAccess, &Registry) -> Vec<VoxelUpdate> + Send + Sync;
+
 /// Serializable struct representing block data.
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1067,6 +1076,22 @@ pub struct Block {
         Option<Arc<dyn Fn(Vec3<i32>, &dyn VoxelAccess, &Registry) -> u64 + Send + Sync>>,
 
     pub is_active: bool,
+
+    /// Function called when one of the 6 neighboring voxels changes, with the position of this voxel and of the
+    /// changed neighbor. Returns further voxel updates. Defaults to `None`.
+    ///
+    /// It is called once the height map reflects the change, but before the lights are flooded at the end of
+    /// the tick, so light levels around the changed voxel can still be stale.
+    #[serde(skip)]
+    pub on_neighbor_update: Option<Arc<NeighborUpdateFn>>,
+
+    /// Function called when this voxel is picked by a random tick, driving slow changes such as grass spreading
+    /// or crops growing. Returns further voxel updates. Defaults to `None`.
+    #[serde(skip)]
+    pub random_tick: Option<Arc<RandomTickFn>>,
+
+    /// How this fluid block flows, if it is simulated. Defaults to `None`.
+    pub fluid_flow: Option<FluidFlow>,
 }
 
 impl Block {
@@ -1148,6 +1173,9 @@ pub struct BlockBuilder {
         Arc<dyn Fn(Vec3<i32>, &dyn VoxelAccess, &Registry) -> Vec<VoxelUpdate> + Send + Sync>,
     >,
     active_ticker: Option<Arc<dyn Fn(Vec3<i32>, &dyn VoxelAccess, &Registry) -> u64 + Send + Sync>>,
+    on_neighbor_update: Option<Arc<NeighborUpdateFn>>,
+    random_tick: Option<Arc<RandomTickFn>>,
+    fluid_flow: Option<FluidFlow>,
 }
 
 impl BlockBuilder {
@@ -1352,6 +1380,47 @@ impl BlockBuilder {
         self
     }
 
+    /// Configure a function called when one of the 6 neighboring voxels changes, such as sand falling once the
+    /// block below is removed. Returned updates can cascade, limited by `WorldConfig::max_neighbor_updates_per_tick`.
+    pub fn on_neighbor_update<
+        F: Fn(Vec3<i32>, Vec3<i32>, &dyn VoxelAccess, &Registry) -> Vec<VoxelUpdate>
+            + 'static
+            + Send
+            + Sync,
+    >(
+        mut self,
+        on_neighbor_update: F,
+    ) -> Self {
+        self.on_neighbor_update = Some(Arc::new(on_neighbor_update));
+        self
+    }
+
+    /// Configure a function called when this voxel is picked by a random tick. On average, each voxel is picked
+    /// once every `sub-chunk volume / WorldConfig::random_tick_speed` ticks.
+    pub fn random_tick<
+        F: Fn(Vec3<i32>, &dyn VoxelAccess, &Registry) -> Vec<VoxelUpdate> + 'static + Send + Sync,
+    >(
+        mut self,
+        random_tick: F,
+    ) -> Self {
+        self.random_tick = Some(Arc::new(random_tick));
+        self
+    }
+
+    /// Make this block a simulated fluid that flows every `flow_rate` ticks and spreads at most `max_spread`
+    /// blocks from a source, using the voxel stage as the fluid level. Replaces the active functions.
+    pub fn fluid_flow(mut self, flow_rate: u64, max_spread: u32) -> Self {
+        let flow = FluidFlow::new(flow_rate, max_spread);
+
+        self.is_fluid = true;
+        self.fluid_flow = Some(flow);
+
+        self.active_fn(
+            move |_, _, _| flow.flow_rate,
+            move |voxel, space, registry| flow.update(voxel, space, registry),
+        )
+    }
+
     /// Construct a block instance, ready to be added into the registry.
     pub fn build(self) -> Block {
         Block {
@@ -1392,6 +1461,9 @@ impl BlockBuilder {
             is_active: self.active_updater.is_some() && self.active_ticker.is_some(),
             active_ticker: self.active_ticker,
             active_updater: self.active_updater,
+            on_neighbor_update: self.on_neighbor_update,
+            random_tick: self.random_tick,
+            fluid_flow: self.fluid_flow,
         }
     }
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::VecDeque,
     fs::{self, File},
-    io::{Read, Write},
+    io::{self, Read, Write},
     path::PathBuf,
 };
 
@@ -18,6 +18,7 @@ use super::{
     access::VoxelAccess,
     chunk::Chunk,
     space::{SpaceBuilder, SpaceOptions},
+    ticks::ScheduledTicks,
 };
 
 /// Prototype for chunk's internal data used to send to client
@@ -30,7 +31,6 @@ struct ChunkFileData {
 }
 
 /// A manager for all chunks in the Voxelize world.
-#[derive(Default)]
 pub struct Chunks {
     /// A map of all the chunks, coords -> Chunk.
     pub map: HashMap<Vec2<i32>, Chunk>,
@@ -44,7 +44,14 @@ pub struct Chunks {
     /// A list of chunks that are done meshing and ready to be saved, if `config.save` is true.
     pub(crate) to_save: VecDeque<Vec2<i32>>,
 
-    pub(crate) active_voxels: Vec<(u64, Vec3<i32>)>,
+    /// Voxels scheduled to be ticked by their block's active updater.
+    pub(crate) scheduled_ticks: ScheduledTicks,
+
+    /// Neighbor updates deferred past the per-tick limit, as the voxel to notify and the neighbor that changed.
+    pub(crate) neighbor_updates: VecDeque<(Vec3<i32>, Vec3<i32>)>,
+
+    /// Voxels changed by the last round of voxel updates, used to wake up the sleeping bodies around them.
+    pub(crate) changed_voxels: Vec<Vec3<i32>>,
 
     /// A listener for when a chunk is done generating or meshing.
     pub(crate) listeners: HashMap<Vec2<i32>, Vec<Vec2<i32>>>,
@@ -74,9 +81,17 @@ impl Chunks {
         };
 
         Self {
-            folder,
+            map: HashMap::new(),
+            updates: VecDeque::new(),
+            to_send: VecDeque::new(),
+            to_save: VecDeque::new(),
+            scheduled_ticks: ScheduledTicks::new(config.chunk_size),
+            neighbor_updates: VecDeque::new(),
+            changed_voxels: vec![],
+            listeners: HashMap::new(),
+            cache: HashSet::new(),
             config: config.to_owned(),
-            ..Default::default()
+            folder,
         }
     }
 
@@ -137,37 +152,49 @@ impl Chunks {
             panic!("Calling `chunks.save` when saving mode is not on.");
         }
 
+        self.try_save(coords)
+            .expect("Unable to write to chunk file.")
+    }
+
+    /// Save a certain chunk, returning the IO errors instead of panicking. Returns whether the chunk existed.
+    pub fn try_save(&self, coords: &Vec2<i32>) -> io::Result<bool> {
+        if !self.config.saving {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Saving chunks requires a world config with saving turned on.",
+            ));
+        }
+
         let chunk = if let Some(chunk) = self.get(coords) {
             chunk
         } else {
-            return false;
+            return Ok(false);
         };
 
         let path = self.get_chunk_file_path(&chunk.name);
-        let mut file = File::create(&path).expect("Could not create chunk file.");
+        let mut file = File::create(&path)?;
 
-        let to_base_64 = |data: &Vec<u32>| {
+        let to_base_64 = |data: &Vec<u32>| -> io::Result<String> {
             let mut bytes = vec![0; data.len() * 4];
             LittleEndian::write_u32_into(data, &mut bytes);
 
-            let mut encoder = Encoder::new(vec![]).unwrap();
-            encoder.write_all(bytes.as_slice()).unwrap();
-            let encoded = encoder.finish().into_result().unwrap();
-            base64::encode(&encoded)
+            let mut encoder = Encoder::new(vec![])?;
+            encoder.write_all(bytes.as_slice())?;
+            let encoded = encoder.finish().into_result()?;
+            Ok(base64::encode(&encoded))
         };
 
         let data = ChunkFileData {
             id: chunk.id.to_owned(),
-            voxels: to_base_64(&chunk.voxels.data),
-            height_map: to_base_64(&chunk.height_map.data),
+            voxels: to_base_64(&chunk.voxels.data)?,
+            height_map: to_base_64(&chunk.height_map.data)?,
         };
 
-        let j = serde_json::to_string(&data).unwrap();
+        let j = serde_json::to_string(&data)?;
 
-        file.write_all(j.as_bytes())
-            .expect("Unable to write to chunk file.");
+        file.write_all(j.as_bytes())?;
 
-        true
+        Ok(true)
     }
 
     /// Update a chunk, removing the old chunk instance and updating with a new one.
@@ -356,13 +383,34 @@ impl Chunks {
         }
     }
 
+    /// Schedule the active updater of a voxel to run at tick `active_at`, rescheduling it if it was already.
     pub fn mark_voxel_active(&mut self, voxel: &Vec3<i32>, active_at: u64) {
-        if let Some(_) = self.active_voxels.iter().find(|(_, v)| v == voxel) {
-            self.active_voxels
-                .retain(|(_, v)| !(v.0 == voxel.0 && v.1 == voxel.1 && v.2 == voxel.2));
-        }
+        self.scheduled_ticks.schedule(voxel, active_at);
+    }
+
+    /// Cancel the scheduled tick of a voxel, returning the tick it was scheduled at.
+    pub fn cancel_voxel_tick(&mut self, voxel: &Vec3<i32>) -> Option<u64> {
+        self.scheduled_ticks.cancel(voxel)
+    }
+
+    /// Pause the scheduled ticks of a chunk at `current_tick` as it unloads, keeping the remaining delay of each.
+    pub fn pause_chunk_ticks(&mut self, coords: &Vec2<i32>, current_tick: u64) {
+        self.scheduled_ticks.pause_chunk(coords, current_tick);
+    }
+
+    /// Resume the scheduled ticks of a chunk at `current_tick` as it loads again.
+    pub fn resume_chunk_ticks(&mut self, coords: &Vec2<i32>, current_tick: u64) {
+        self.scheduled_ticks.resume_chunk(coords, current_tick);
+    }
+
+    /// Whether the scheduled ticks of a chunk are paused.
+    pub fn is_chunk_ticks_paused(&self, coords: &Vec2<i32>) -> bool {
+        self.scheduled_ticks.is_paused(coords)
+    }
 
-        self.active_voxels.push((active_at, voxel.to_owned()));
+    /// Get the tick a voxel is scheduled to be ticked at. Voxels in unloaded chunks are paused and return `None`.
+    pub fn get_voxel_tick(&self, voxel: &Vec3<i32>) -> Option<u64> {
+        self.scheduled_ticks.get(voxel)
     }
 
     /// Add a chunk to be saved.
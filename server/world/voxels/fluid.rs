@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BlockUtils, Registry, Vec3, VoxelAccess, VoxelUpdate};
+
+/// The four horizontal directions fluids spread towards.
+const HORIZONTAL_NEIGHBORS: [[i32; 2]; 4] = [[1, 0], [-1, 0], [0, 1], [0, -1]];
+
+/// How a fluid block flows. The stage bits of a fluid voxel store its level: stage 0 is a source, and a
+/// flowing voxel's stage is its distance to the source it flows from. Fluid falling down resets to stage 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FluidFlow {
+    /// Number of ticks between each step of the flow.
+    pub flow_rate: u64,
+
+    /// How far the fluid spreads horizontally from a source, at most 15.
+    pub max_spread: u32,
+}
+
+impl FluidFlow {
+    /// Create fluid flow settings. Panics if the spread does not fit into the stage bits.
+    pub fn new(flow_rate: u64, max_spread: u32) -> Self {
+        assert!(max_spread <= 15, "Maximum fluid spread is 15");
+
+        Self {
+            flow_rate,
+            max_spread,
+        }
+    }
+
+    /// Compute one step of the flow for the fluid voxel at `voxel`. Flowing voxels first fix their own level
+    /// from their neighbors, drying up if nothing feeds them anymore, then the fluid falls into air below or
+    /// spreads horizontally into air and into flowing voxels of a higher level.
+    pub fn update(
+        &self,
+        voxel: Vec3<i32>,
+        space: &dyn VoxelAccess,
+        registry: &Registry,
+    ) -> Vec<VoxelUpdate> {
+        let Vec3(vx, vy, vz) = voxel;
+
+        let raw = space.get_raw_voxel(vx, vy, vz);
+        let id = BlockUtils::extract_id(raw);
+        let level = BlockUtils::extract_stage(raw);
+
+        let fluid = |level: u32| BlockUtils::insert_stage(id, level);
+
+        let mut updates = vec![];
+
+        if level > 0 {
+            let expected = if space.get_voxel(vx, vy + 1, vz) == id {
+                1
+            } else {
+                HORIZONTAL_NEIGHBORS
+                    .iter()
+                    .filter(|[ox, oz]| space.get_voxel(vx + ox, vy, vz + oz) == id)
+                    .map(|[ox, oz]| space.get_voxel_stage(vx + ox, vy, vz + oz) + 1)
+                    .min()
+                    .unwrap_or(u32::MAX)
+            };
+
+            if expected > self.max_spread {
+                updates.push((voxel, 0));
+                return updates;
+            }
+
+            if expected != level {
+                updates.push((voxel, fluid(expected)));
+                return updates;
+            }
+        }
+
+        if vy > 0 {
+            let below = space.get_voxel(vx, vy - 1, vz);
+
+            if registry.is_air(below) {
+                updates.push((Vec3(vx, vy - 1, vz), fluid(1)));
+                return updates;
+            }
+
+            // Fluid resting on the same fluid does not spread sideways.
+            if below == id {
+                return updates;
+            }
+        }
+
+        if level >= self.max_spread {
+            return updates;
+        }
+
+        HORIZONTAL_NEIGHBORS.iter().for_each(|[ox, oz]| {
+            let (nx, nz) = (vx + ox, vz + oz);
+            let n_id = space.get_voxel(nx, vy, nz);
+
+            if registry.is_air(n_id)
+                || (n_id == id && space.get_voxel_stage(nx, vy, nz) > level + 1)
+            {
+                updates.push((Vec3(nx, vy, nz), fluid(level + 1)));
+            }
+        });
+
+        updates
+    }
+}
@@ -2,10 +2,16 @@ mod access;
 mod block;
 mod chunk;
 mod chunks;
+mod fluid;
+mod schematic;
 mod space;
+mod ticks;
 
 pub use access::VoxelAccess;
 pub use block::*;
 pub use chunk::*;
 pub use chunks::Chunks;
+pub use fluid::FluidFlow;
+pub use schematic::*;
 pub use space::*;
+pub use ticks::ScheduledTicks;
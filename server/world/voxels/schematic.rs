@@ -0,0 +1,380 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use byteorder::{ByteOrder, LittleEndian};
+use hashbrown::HashMap;
+use libflate::zlib::{Decoder, Encoder};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::{BlockUtils, Ndarray, Registry, Vec2, Vec3, VoxelUpdate};
+
+use super::{
+    access::VoxelAccess,
+    block::{
+        BlockRotation, NX_ROTATION, NY_ROTATION, NZ_ROTATION, PX_ROTATION, PY_ROTATION,
+        PZ_ROTATION, Y_ROT_SEGMENTS,
+    },
+    chunks::Chunks,
+};
+
+/// Prototype of a schematic saved to the disk.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchematicFileData {
+    name: String,
+    size: [usize; 3],
+    palette: Vec<String>,
+    voxels: String,
+}
+
+/// How a schematic should be transformed when pasted into the world.
+#[derive(Debug, Clone, Default)]
+pub struct SchematicTransform {
+    /// Number of 90 degree turns around the y-axis, counter-clockwise looking down.
+    pub rotation: u32,
+
+    /// Whether the schematic is mirrored along the x-axis.
+    pub mirror_x: bool,
+
+    /// Whether the schematic is mirrored along the z-axis.
+    pub mirror_z: bool,
+
+    /// Whether air voxels in the schematic should overwrite the world.
+    pub include_air: bool,
+}
+
+impl SchematicTransform {
+    /// Create a transform that pastes the schematic as is, skipping air voxels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure the number of 90 degree turns around the y-axis. Defaults to 0.
+    pub fn rotate(mut self, quarter_turns: u32) -> Self {
+        self.rotation = quarter_turns % 4;
+        self
+    }
+
+    /// Configure whether the schematic is mirrored along the x-axis. Defaults to false.
+    pub fn mirror_x(mut self, mirror_x: bool) -> Self {
+        self.mirror_x = mirror_x;
+        self
+    }
+
+    /// Configure whether the schematic is mirrored along the z-axis. Defaults to false.
+    pub fn mirror_z(mut self, mirror_z: bool) -> Self {
+        self.mirror_z = mirror_z;
+        self
+    }
+
+    /// Configure whether air voxels should overwrite the world. Defaults to false.
+    pub fn include_air(mut self, include_air: bool) -> Self {
+        self.include_air = include_air;
+        self
+    }
+}
+
+/// A portable copy of a region of voxels. Voxels keep their rotations and stages, but their IDs
+/// are replaced with indices into a palette of block names so that schematics can be moved between
+/// worlds and servers with different registries.
+#[derive(Debug, Clone, Default)]
+pub struct Schematic {
+    /// Name of the schematic.
+    pub name: String,
+
+    /// Palette of block names, indexed by the ID bits of each voxel.
+    pub palette: Vec<String>,
+
+    /// The voxels of this schematic, in the shape of `[width, height, depth]`.
+    pub voxels: Ndarray<u32>,
+}
+
+impl Schematic {
+    /// Copy the voxels from `min` (inclusive) to `max` (exclusive) into a new schematic.
+    pub fn copy(
+        name: &str,
+        space: &dyn VoxelAccess,
+        min: &Vec3<i32>,
+        max: &Vec3<i32>,
+        registry: &Registry,
+    ) -> Self {
+        let &Vec3(min_x, min_y, min_z) = min;
+        let &Vec3(max_x, max_y, max_z) = max;
+
+        let width = (max_x - min_x).max(0) as usize;
+        let height = (max_y - min_y).max(0) as usize;
+        let depth = (max_z - min_z).max(0) as usize;
+
+        let mut voxels = Ndarray::new(&[width, height, depth], 0);
+        let mut palette = vec![];
+        let mut indices = HashMap::new();
+
+        for vx in min_x..max_x {
+            for vy in min_y..max_y {
+                for vz in min_z..max_z {
+                    let raw = space.get_raw_voxel(vx, vy, vz);
+                    let id = BlockUtils::extract_id(raw);
+
+                    let index = *indices.entry(id).or_insert_with(|| {
+                        palette.push(if registry.has_type(id) {
+                            registry.get_block_by_id(id).name.to_owned()
+                        } else {
+                            "Air".to_owned()
+                        });
+                        palette.len() as u32 - 1
+                    });
+
+                    let lx = (vx - min_x) as usize;
+                    let ly = (vy - min_y) as usize;
+                    let lz = (vz - min_z) as usize;
+
+                    voxels[&[lx, ly, lz]] = BlockUtils::insert_id(raw, index);
+                }
+            }
+        }
+
+        Self {
+            name: name.to_owned(),
+            palette,
+            voxels,
+        }
+    }
+
+    /// Get the size of this schematic, in `(width, height, depth)`.
+    pub fn size(&self) -> Vec3<usize> {
+        if self.voxels.shape.len() != 3 {
+            return Vec3(0, 0, 0);
+        }
+
+        Vec3(
+            self.voxels.shape[0],
+            self.voxels.shape[1],
+            self.voxels.shape[2],
+        )
+    }
+
+    /// Get the size of this schematic after being transformed, in `(width, height, depth)`.
+    pub fn transformed_size(&self, transform: &SchematicTransform) -> Vec3<usize> {
+        let Vec3(width, height, depth) = self.size();
+
+        if transform.rotation % 2 == 1 {
+            Vec3(depth, height, width)
+        } else {
+            Vec3(width, height, depth)
+        }
+    }
+
+    /// Generate the list of voxel updates to paste this schematic with its minimum corner at `at`.
+    /// Blocks that do not exist in the registry are skipped.
+    pub fn to_updates(
+        &self,
+        at: &Vec3<i32>,
+        transform: &SchematicTransform,
+        registry: &Registry,
+    ) -> Vec<VoxelUpdate> {
+        let Vec3(width, height, depth) = self.size();
+        let &Vec3(ax, ay, az) = at;
+
+        let ids = self
+            .palette
+            .iter()
+            .map(|name| {
+                if registry.blocks_by_name.contains_key(&name.to_lowercase()) {
+                    Some(registry.get_id_by_name(name))
+                } else {
+                    warn!("Schematic block not found in registry, skipping: {}", name);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut updates = vec![];
+
+        for lx in 0..width {
+            for ly in 0..height {
+                for lz in 0..depth {
+                    let raw = self.voxels[&[lx, ly, lz]];
+                    let index = BlockUtils::extract_id(raw) as usize;
+
+                    let id = if let Some(Some(id)) = ids.get(index) {
+                        *id
+                    } else {
+                        continue;
+                    };
+
+                    if !transform.include_air && registry.is_air(id) {
+                        continue;
+                    }
+
+                    let Vec2(tx, tz) = Self::transform_position(lx, lz, width, depth, transform);
+
+                    let mut voxel = BlockUtils::insert_id(raw, id);
+
+                    if registry.get_block_by_id(id).rotatable {
+                        let rotation = Self::transform_rotation(
+                            &BlockUtils::extract_rotation(voxel),
+                            transform,
+                        );
+                        voxel = BlockUtils::insert_rotation(voxel, &rotation);
+                    }
+
+                    updates.push((Vec3(ax + tx, ay + ly as i32, az + tz), voxel));
+                }
+            }
+        }
+
+        updates
+    }
+
+    /// Queue this schematic to be pasted into the world with its minimum corner at `at`. The updates
+    /// are processed in the background through `Chunks::update_voxels`.
+    pub fn paste(
+        &self,
+        chunks: &mut Chunks,
+        at: &Vec3<i32>,
+        transform: &SchematicTransform,
+        registry: &Registry,
+    ) {
+        chunks.update_voxels(&self.to_updates(at, transform, registry));
+    }
+
+    /// Load a schematic from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        let SchematicFileData {
+            name,
+            size,
+            palette,
+            voxels,
+        } = serde_json::from_reader(file)?;
+
+        let decoded = base64::decode(voxels)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let mut decoder = Decoder::new(&decoded[..])?;
+        let mut buf = Vec::new();
+        decoder.read_to_end(&mut buf)?;
+
+        let mut data = Ndarray::new(&size, 0);
+
+        if buf.len() != data.data.len() * 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Schematic voxels do not match its size.",
+            ));
+        }
+
+        LittleEndian::read_u32_into(&buf, &mut data.data);
+
+        Ok(Self {
+            name,
+            palette,
+            voxels: data,
+        })
+    }
+
+    /// Save this schematic to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let Vec3(width, height, depth) = self.size();
+
+        let mut bytes = vec![0; self.voxels.data.len() * 4];
+        LittleEndian::write_u32_into(&self.voxels.data, &mut bytes);
+
+        let mut encoder = Encoder::new(vec![])?;
+        encoder.write_all(bytes.as_slice())?;
+        let encoded = encoder.finish().into_result()?;
+
+        let data = SchematicFileData {
+            name: self.name.to_owned(),
+            size: [width, height, depth],
+            palette: self.palette.to_owned(),
+            voxels: base64::encode(&encoded),
+        };
+
+        let mut file = File::create(path)?;
+        file.write_all(serde_json::to_string(&data)?.as_bytes())
+    }
+
+    /// Map a local horizontal position into the transformed schematic.
+    fn transform_position(
+        lx: usize,
+        lz: usize,
+        width: usize,
+        depth: usize,
+        transform: &SchematicTransform,
+    ) -> Vec2<i32> {
+        let mut x = lx as i32;
+        let mut z = lz as i32;
+        let mut width = width as i32;
+        let mut depth = depth as i32;
+
+        if transform.mirror_x {
+            x = width - 1 - x;
+        }
+
+        if transform.mirror_z {
+            z = depth - 1 - z;
+        }
+
+        // Each quarter turn maps +x to -z, and +z to +x.
+        for _ in 0..(transform.rotation % 4) {
+            let (nx, nz) = (z, width - 1 - x);
+            x = nx;
+            z = nz;
+            std::mem::swap(&mut width, &mut depth);
+        }
+
+        Vec2(x, z)
+    }
+
+    /// Map a block rotation into the transformed schematic.
+    fn transform_rotation(
+        rotation: &BlockRotation,
+        transform: &SchematicTransform,
+    ) -> BlockRotation {
+        let (mut value, mut y_rot) = BlockRotation::decode(rotation);
+
+        let quarter = Y_ROT_SEGMENTS / 4;
+
+        if transform.mirror_x {
+            value = match value {
+                PX_ROTATION => NX_ROTATION,
+                NX_ROTATION => PX_ROTATION,
+                _ => value,
+            };
+            y_rot = (Y_ROT_SEGMENTS - y_rot) % Y_ROT_SEGMENTS;
+        }
+
+        if transform.mirror_z {
+            value = match value {
+                PZ_ROTATION => NZ_ROTATION,
+                NZ_ROTATION => PZ_ROTATION,
+                _ => value,
+            };
+            y_rot = (Y_ROT_SEGMENTS * 3 / 2 - y_rot) % Y_ROT_SEGMENTS;
+        }
+
+        for _ in 0..(transform.rotation % 4) {
+            value = match value {
+                PX_ROTATION => NZ_ROTATION,
+                NZ_ROTATION => NX_ROTATION,
+                NX_ROTATION => PZ_ROTATION,
+                PZ_ROTATION => PX_ROTATION,
+                _ => value,
+            };
+
+            // Upside-down blocks are flipped around the x-axis, reversing their y-rotation.
+            y_rot = match value {
+                PY_ROTATION => (y_rot + quarter) % Y_ROT_SEGMENTS,
+                NY_ROTATION => (y_rot + Y_ROT_SEGMENTS - quarter) % Y_ROT_SEGMENTS,
+                _ => y_rot,
+            };
+        }
+
+        BlockRotation::encode(value, y_rot)
+    }
+}
@@ -0,0 +1,202 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::{ChunkUtils, Vec2, Vec3};
+
+/// A queue of voxels scheduled to be ticked, ordered by the tick they are due at. Scheduled voxels are
+/// indexed by chunk so that the timers of a whole chunk can be paused while it is unloaded, keeping the
+/// remaining ticks until the chunk is resumed.
+#[derive(Debug)]
+pub struct ScheduledTicks {
+    /// Min-heap of `(tick, voxel)`. Entries that no longer match `ticks` are stale and skipped.
+    queue: BinaryHeap<Reverse<(u64, i32, i32, i32)>>,
+
+    /// The tick each scheduled voxel is due at.
+    ticks: HashMap<Vec3<i32>, u64>,
+
+    /// The scheduled voxels of each chunk.
+    chunks: HashMap<Vec2<i32>, HashSet<Vec3<i32>>>,
+
+    /// Paused chunks, with the remaining ticks of each of their voxels.
+    paused: HashMap<Vec2<i32>, HashMap<Vec3<i32>, u64>>,
+
+    /// The last tick the queue was polled at.
+    current_tick: u64,
+
+    /// The chunk size used to index the voxels.
+    chunk_size: usize,
+}
+
+impl ScheduledTicks {
+    /// Create an empty queue for a world with the given chunk size.
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            queue: BinaryHeap::new(),
+            ticks: HashMap::new(),
+            chunks: HashMap::new(),
+            paused: HashMap::new(),
+            current_tick: 0,
+            chunk_size,
+        }
+    }
+
+    /// Schedule a voxel to be ticked at `tick`, replacing its previous schedule if any.
+    pub fn schedule(&mut self, voxel: &Vec3<i32>, tick: u64) {
+        let coords = self.to_chunk(voxel);
+
+        if let Some(paused) = self.paused.get_mut(&coords) {
+            paused.insert(voxel.to_owned(), tick.saturating_sub(self.current_tick));
+            return;
+        }
+
+        self.ticks.insert(voxel.to_owned(), tick);
+        self.chunks
+            .entry(coords)
+            .or_default()
+            .insert(voxel.to_owned());
+
+        let Vec3(vx, vy, vz) = *voxel;
+        self.queue.push(Reverse((tick, vx, vy, vz)));
+
+        // Rescheduling leaves stale entries behind, so compact the heap once they pile up.
+        if self.queue.len() > self.ticks.len() * 2 + 64 {
+            self.compact();
+        }
+    }
+
+    /// Cancel the scheduled tick of a voxel. Returns the tick it was due at, or the remaining ticks if its
+    /// chunk is paused.
+    pub fn cancel(&mut self, voxel: &Vec3<i32>) -> Option<u64> {
+        let coords = self.to_chunk(voxel);
+
+        if let Some(paused) = self.paused.get_mut(&coords) {
+            let remaining = paused.remove(voxel);
+
+            if paused.is_empty() {
+                self.paused.remove(&coords);
+            }
+
+            return remaining;
+        }
+
+        let tick = self.ticks.remove(voxel)?;
+        self.unindex(&coords, voxel);
+
+        Some(tick)
+    }
+
+    /// Get the tick a voxel is scheduled at, if it is scheduled and its chunk is not paused.
+    pub fn get(&self, voxel: &Vec3<i32>) -> Option<u64> {
+        self.ticks.get(voxel).copied()
+    }
+
+    /// Pop all the voxels due at or before `current_tick`, earliest first.
+    pub fn pop_due(&mut self, current_tick: u64) -> Vec<Vec3<i32>> {
+        self.current_tick = current_tick;
+
+        let mut due = vec![];
+
+        while let Some(Reverse((tick, vx, vy, vz))) = self.queue.peek().copied() {
+            if tick > current_tick {
+                break;
+            }
+
+            self.queue.pop();
+
+            let voxel = Vec3(vx, vy, vz);
+
+            if self.ticks.get(&voxel) != Some(&tick) {
+                continue;
+            }
+
+            self.ticks.remove(&voxel);
+            self.unindex(&self.to_chunk(&voxel), &voxel);
+
+            due.push(voxel);
+        }
+
+        due
+    }
+
+    /// Pause the timers of a chunk at `current_tick`, keeping how many ticks each voxel had left. Chunks
+    /// without any scheduled voxel are left alone, so only the chunks with timers to resume are tracked.
+    pub fn pause_chunk(&mut self, coords: &Vec2<i32>, current_tick: u64) {
+        if self.paused.contains_key(coords) {
+            return;
+        }
+
+        let voxels = match self.chunks.remove(coords) {
+            Some(voxels) => voxels,
+            None => return,
+        };
+
+        let remaining = voxels
+            .into_iter()
+            .filter_map(|voxel| {
+                let tick = self.ticks.remove(&voxel)?;
+                Some((voxel, tick.saturating_sub(current_tick)))
+            })
+            .collect();
+
+        self.paused.insert(coords.to_owned(), remaining);
+    }
+
+    /// Resume the timers of a paused chunk at `current_tick`.
+    pub fn resume_chunk(&mut self, coords: &Vec2<i32>, current_tick: u64) {
+        if let Some(paused) = self.paused.remove(coords) {
+            paused.into_iter().for_each(|(voxel, remaining)| {
+                self.schedule(&voxel, current_tick + remaining);
+            });
+        }
+    }
+
+    /// Whether the timers of a chunk are paused.
+    pub fn is_paused(&self, coords: &Vec2<i32>) -> bool {
+        self.paused.contains_key(coords)
+    }
+
+    /// List the coordinates of the paused chunks.
+    pub fn paused_chunks(&self) -> Vec<Vec2<i32>> {
+        self.paused.keys().cloned().collect()
+    }
+
+    /// The number of scheduled voxels, including those of paused chunks.
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+            + self
+                .paused
+                .values()
+                .map(|paused| paused.len())
+                .sum::<usize>()
+    }
+
+    /// Whether no voxel is scheduled.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rebuild the heap from the live schedules, dropping stale entries.
+    fn compact(&mut self) {
+        self.queue = self
+            .ticks
+            .iter()
+            .map(|(Vec3(vx, vy, vz), tick)| Reverse((*tick, *vx, *vy, *vz)))
+            .collect();
+    }
+
+    fn unindex(&mut self, coords: &Vec2<i32>, voxel: &Vec3<i32>) {
+        if let Some(voxels) = self.chunks.get_mut(coords) {
+            voxels.remove(voxel);
+
+            if voxels.is_empty() {
+                self.chunks.remove(coords);
+            }
+        }
+    }
+
+    fn to_chunk(&self, voxel: &Vec3<i32>) -> Vec2<i32> {
+        let Vec3(vx, vy, vz) = *voxel;
+        ChunkUtils::map_voxel_to_chunk(vx, vy, vz, self.chunk_size)
+    }
+}
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, Entity, RunNow, World, WorldExt};
+    use voxelize::{
+        AttachmentComp, AttachmentSystem, ClientFlag, IDComp, InteractorComp, JointKind,
+        MetadataComp, Physics, PositionComp, RigidBody, RigidBodyComp, Vec3, AABB,
+    };
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register::<IDComp>();
+        world.register::<InteractorComp>();
+        world.register::<ClientFlag>();
+        world.register::<AttachmentComp>();
+        world.register::<RigidBodyComp>();
+        world.register::<PositionComp>();
+        world.insert(Physics::new());
+        world
+    }
+
+    fn spawn(world: &mut World, id: &str, position: Vec3<f32>) -> Entity {
+        let mut body = RigidBody::new(&AABB::new().build()).build();
+        body.set_position(position.0, position.1, position.2);
+
+        world
+            .create_entity()
+            .with(IDComp::new(id))
+            .with(RigidBodyComp::new(&body))
+            .with(PositionComp(position))
+            .build()
+    }
+
+    fn spawn_physical(world: &mut World, id: &str, position: Vec3<f32>) -> Entity {
+        let entity = spawn(world, id, position);
+        let body = world
+            .read_component::<RigidBodyComp>()
+            .get(entity)
+            .unwrap()
+            .0
+            .clone();
+        let interactor = world.write_resource::<Physics>().register(&body);
+
+        world
+            .write_component::<InteractorComp>()
+            .insert(entity, InteractorComp::new(&interactor))
+            .unwrap();
+
+        entity
+    }
+
+    fn attach(world: &World, entity: Entity, parent: &str, joint: JointKind) {
+        world
+            .write_component::<AttachmentComp>()
+            .insert(
+                entity,
+                AttachmentComp::new(parent, joint, &Vec3::default(), &Vec3::default()),
+            )
+            .unwrap();
+    }
+
+    fn move_to(world: &World, entity: Entity, position: Vec3<f32>) {
+        world
+            .write_component::<RigidBodyComp>()
+            .get_mut(entity)
+            .unwrap()
+            .0
+            .set_position(position.0, position.1, position.2);
+        world
+            .write_component::<PositionComp>()
+            .get_mut(entity)
+            .unwrap()
+            .0 = position;
+    }
+
+    fn position(world: &World, entity: Entity) -> Vec3<f32> {
+        world
+            .read_component::<PositionComp>()
+            .get(entity)
+            .unwrap()
+            .0
+            .clone()
+    }
+
+    #[test]
+    fn attached_entities_follow_their_parents() {
+        let mut world = world();
+
+        let horse = spawn(&mut world, "horse", Vec3(0.0, 1.0, 0.0));
+        let rider = spawn(&mut world, "rider", Vec3(5.0, 1.0, 0.0));
+        let cart = spawn(&mut world, "cart", Vec3(0.0, 1.0, 10.0));
+
+        let mut attachments = world.write_component::<AttachmentComp>();
+        attachments
+            .insert(
+                rider,
+                AttachmentComp::new(
+                    "horse",
+                    JointKind::Fixed,
+                    &Vec3(0.0, 1.0, 0.0),
+                    &Vec3(0.0, -0.5, 0.0),
+                ),
+            )
+            .unwrap();
+        attachments
+            .insert(
+                cart,
+                AttachmentComp::new(
+                    "horse",
+                    JointKind::Rope { length: 3.0 },
+                    &Vec3::default(),
+                    &Vec3::default(),
+                ),
+            )
+            .unwrap();
+        drop(attachments);
+
+        AttachmentSystem.run_now(&world);
+
+        // The rider sits on the horse, and the cart is pulled to the end of the rope.
+        assert_eq!(position(&world, rider), Vec3(0.0, 2.5, 0.0));
+        assert_eq!(position(&world, cart), Vec3(0.0, 1.0, 3.0));
+
+        world
+            .write_component::<RigidBodyComp>()
+            .get_mut(horse)
+            .unwrap()
+            .0
+            .set_position(0.0, 1.0, -2.0);
+        world
+            .write_component::<PositionComp>()
+            .get_mut(horse)
+            .unwrap()
+            .0
+            .set(0.0, 1.0, -2.0);
+
+        AttachmentSystem.run_now(&world);
+
+        assert_eq!(position(&world, rider), Vec3(0.0, 2.5, -2.0));
+        assert_eq!(position(&world, cart), Vec3(0.0, 1.0, 1.0));
+
+        // The attachment survives a round trip through the saved metadata.
+        let mut metadata = MetadataComp::new();
+        metadata.set(
+            "attachment",
+            world.read_component::<AttachmentComp>().get(cart).unwrap(),
+        );
+        let restored = metadata.get::<AttachmentComp>("attachment").unwrap();
+        assert_eq!(restored.parent, "horse");
+        assert_eq!(restored.joint, JointKind::Rope { length: 3.0 });
+    }
+
+    #[test]
+    fn chains_follow_in_one_tick() {
+        let mut world = world();
+
+        let horse = spawn(&mut world, "horse", Vec3(0.0, 1.0, 0.0));
+        let second = spawn(&mut world, "second", Vec3(0.0, 1.0, 4.0));
+        let first = spawn(&mut world, "first", Vec3(0.0, 1.0, 2.0));
+
+        // The last cart is created before the one it follows, so storage order alone would move it first.
+        attach(&world, second, "first", JointKind::Rope { length: 2.0 });
+        attach(&world, first, "horse", JointKind::Rope { length: 2.0 });
+
+        move_to(&world, horse, Vec3(0.0, 1.0, -3.0));
+        AttachmentSystem.run_now(&world);
+
+        assert_eq!(position(&world, first), Vec3(0.0, 1.0, -1.0));
+        assert_eq!(position(&world, second), Vec3(0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn physical_attachments_join_their_bodies() {
+        let mut world = world();
+
+        let horse = spawn_physical(&mut world, "horse", Vec3(0.0, 1.0, 0.0));
+        let rider = spawn_physical(&mut world, "rider", Vec3(5.0, 1.0, 0.0));
+        let ghost = spawn(&mut world, "ghost", Vec3(0.0, 1.0, 5.0));
+
+        attach(&world, rider, "horse", JointKind::Fixed);
+        attach(&world, ghost, "horse", JointKind::Rope { length: 1.0 });
+
+        AttachmentSystem.run_now(&world);
+
+        // Only entities with rapier bodies on both ends are joined in rapier, but all of them follow.
+        let handle = world
+            .read_component::<AttachmentComp>()
+            .get(rider)
+            .unwrap()
+            .handle()
+            .cloned()
+            .unwrap();
+        assert!(world.read_resource::<Physics>().is_attached(&handle));
+        assert!(world
+            .read_component::<AttachmentComp>()
+            .get(ghost)
+            .unwrap()
+            .handle()
+            .is_none());
+        assert_eq!(position(&world, rider), Vec3(0.0, 1.0, 0.0));
+        assert_eq!(position(&world, ghost), Vec3(0.0, 1.0, 1.0));
+
+        // The joint is kept across ticks, and removed once detached.
+        move_to(&world, horse, Vec3(2.0, 1.0, 0.0));
+        AttachmentSystem.run_now(&world);
+
+        assert_eq!(
+            world
+                .read_component::<AttachmentComp>()
+                .get(rider)
+                .unwrap()
+                .handle(),
+            Some(&handle)
+        );
+        assert_eq!(position(&world, rider), Vec3(2.0, 1.0, 0.0));
+
+        world.write_resource::<Physics>().detach(&handle);
+        assert!(!world.read_resource::<Physics>().is_attached(&handle));
+    }
+
+    #[test]
+    fn revolute_joints_swing_around_their_axis() {
+        let joint = JointKind::Revolute {
+            axis: [0.0, 1.0, 0.0],
+        };
+
+        // A door held at its edge stays one block from the hinge, in whatever direction it swung to.
+        let door = joint
+            .constrain(
+                &Vec3(0.0, 0.0, 0.0),
+                &Vec3(0.0, 0.5, 3.0),
+                &Vec3(-1.0, 0.0, 0.0),
+            )
+            .unwrap();
+
+        assert!((door.0).abs() < 1e-5);
+        assert!((door.1).abs() < 1e-5);
+        assert!((door.2 - 1.0).abs() < 1e-5);
+    }
+}
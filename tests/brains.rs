@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, RunNow, World, WorldExt};
+    use voxelize::{
+        BrainComp, BrainSystem, Chunk, ChunkOptions, ChunkStatus, Chunks, ClientFlag,
+        CurrentChunkComp, EntityFlag, Follow, Idle, PathComp, PathOptions, RigidBody,
+        RigidBodyComp, Search, SearchSystem, Selector, Sequence, Stats, Vec3, Wander, WorldConfig,
+        AABB,
+    };
+
+    fn body_at(x: f32, z: f32) -> RigidBody {
+        let mut body =
+            RigidBody::new(&AABB::new().scale_x(0.6).scale_y(1.8).scale_z(0.6).build()).build();
+        body.set_position(x, 1.9, z);
+        body
+    }
+
+    #[test]
+    fn brains_follow_clients_in_range_and_idle_otherwise() {
+        let config = WorldConfig::new().max_height(16).sub_chunks(1).build();
+
+        let mut chunks = Chunks::new(&config);
+        let mut chunk = Chunk::new(
+            "0|0",
+            0,
+            0,
+            &ChunkOptions {
+                size: config.chunk_size,
+                max_height: config.max_height,
+                sub_chunks: config.sub_chunks,
+            },
+        );
+        chunk.status = ChunkStatus::Ready;
+        chunks.add(chunk);
+
+        let mut stats = Stats::new(false, "");
+        stats.delta = 0.1;
+
+        let mut world = World::new();
+        world.register::<ClientFlag>();
+        world.register::<EntityFlag>();
+        world.register::<CurrentChunkComp>();
+        world.register::<RigidBodyComp>();
+        world.register::<PathComp>();
+        world.register::<BrainComp>();
+        world.insert(stats);
+        world.insert(chunks);
+        world.insert(Search::new());
+
+        let client = world
+            .create_entity()
+            .with(ClientFlag::default())
+            .with(RigidBodyComp::new(&body_at(6.5, 2.5)))
+            .build();
+
+        let brain = Selector::new(vec![
+            Box::new(Follow::new(8.0, 2.0)),
+            Box::new(Sequence::new(vec![
+                Box::new(Idle::new(0.25)),
+                Box::new(Wander::new(4)),
+            ])),
+        ]);
+
+        let entity = world
+            .create_entity()
+            .with(EntityFlag::default())
+            .with(CurrentChunkComp::default())
+            .with(RigidBodyComp::new(&body_at(2.5, 2.5)))
+            .with(PathComp::new(4.0, &PathOptions::new().build()))
+            .with(BrainComp::new(brain))
+            .build();
+
+        let tick = |world: &World| {
+            SearchSystem.run_now(world);
+            BrainSystem.run_now(world);
+            world
+                .read_component::<PathComp>()
+                .get(entity)
+                .unwrap()
+                .goal
+                .clone()
+        };
+
+        // Walks to the client nearby.
+        assert_eq!(tick(&world), Some(Vec3(6, 1, 2)));
+
+        // Stands still once the client is out of range, then wanders off.
+        world
+            .write_component::<RigidBodyComp>()
+            .insert(client, RigidBodyComp::new(&body_at(14.5, 2.5)))
+            .unwrap();
+
+        assert_eq!(tick(&world), None);
+        assert_eq!(tick(&world), None);
+
+        let Vec3(gx, gy, gz) = tick(&world).unwrap();
+        assert!((gx - 2).abs() <= 4 && (gz - 2).abs() <= 4);
+        assert_eq!(gy, 1);
+    }
+}
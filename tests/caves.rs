@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{
+        Block, CaveStage, Chunk, ChunkOptions, ChunkStage, NoiseOptions, Registry, Resources,
+        VoxelAccess, WorldConfig,
+    };
+
+    const STONE: u32 = 1;
+    const WATER: u32 = 2;
+
+    fn carve(stage: &CaveStage) -> Chunk {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(STONE).build());
+        registry.register_block(&Block::new("Water").id(WATER).is_fluid(true).build());
+        registry.generate();
+
+        let config = WorldConfig::new().max_height(16).water_level(5).build();
+
+        let mut chunk = Chunk::new(
+            "test",
+            0,
+            0,
+            &ChunkOptions {
+                size: 4,
+                max_height: 16,
+                sub_chunks: 1,
+            },
+        );
+
+        // Stone up to y = 10 everywhere, with a lake on top of the (0, 0) column.
+        for vx in 0..4 {
+            for vz in 0..4 {
+                for vy in 0..=10 {
+                    chunk.set_voxel(vx, vy, vz, STONE);
+                }
+                chunk.set_max_height(vx, vz, 10);
+            }
+        }
+
+        chunk.set_voxel(0, 11, 0, WATER);
+        chunk.set_voxel(0, 12, 0, WATER);
+        chunk.set_max_height(0, 0, 12);
+
+        stage.process(
+            chunk,
+            Resources {
+                registry: &registry,
+                config: &config,
+            },
+            None,
+        )
+    }
+
+    /// A cave stage whose cheese noise carves every voxel it is allowed to.
+    fn everywhere() -> CaveStage {
+        CaveStage::new().cheese(1, &NoiseOptions::default(), -2.0)
+    }
+
+    #[test]
+    fn carves_air() {
+        let chunk = carve(&everywhere());
+
+        for vy in 1..=10 {
+            assert_eq!(chunk.get_voxel(2, vy, 2), 0);
+        }
+
+        // The bottom of the world is never carved.
+        assert_eq!(chunk.get_voxel(2, 0, 2), STONE);
+
+        let chunk = carve(&CaveStage::new().cheese(1, &NoiseOptions::default(), 2.0));
+        assert_eq!(chunk.get_voxel(2, 5, 2), STONE);
+    }
+
+    #[test]
+    fn fills_fluid_below_water_level() {
+        let chunk = carve(&everywhere().fluid(WATER));
+
+        for vy in 1..=5 {
+            assert_eq!(chunk.get_voxel(2, vy, 2), WATER);
+        }
+
+        for vy in 6..=10 {
+            assert_eq!(chunk.get_voxel(2, vy, 2), 0);
+        }
+    }
+
+    #[test]
+    fn keeps_ground_under_fluids() {
+        let chunk = carve(&everywhere().depth(1, 15));
+
+        assert_eq!(chunk.get_voxel(0, 12, 0), WATER);
+        assert_eq!(chunk.get_voxel(0, 11, 0), WATER);
+        assert_eq!(chunk.get_voxel(0, 10, 0), STONE);
+
+        // Columns without fluid on top are carved all the way up.
+        assert_eq!(chunk.get_voxel(1, 10, 0), 0);
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{ColliderShape, CollisionGroups, Physics, RigidBody, AABB};
+
+    const ENTITIES: u32 = 1 << 1;
+    const BOATS: u32 = 1 << 2;
+
+    /// Register a one block wide body at `x`, returning the number of collisions after a step.
+    fn collide(a: (f32, ColliderShape, u32, u32), b: (f32, ColliderShape, u32, u32)) -> usize {
+        let mut physics = Physics::new();
+
+        for (x, shape, memberships, filter) in [a, b] {
+            let mut body = RigidBody::new(&AABB::new().build())
+                .collider(shape)
+                .collision_groups(memberships, filter)
+                .build();
+            body.set_position(x, 0.0, 0.0);
+            physics.register(&body);
+        }
+
+        physics.step(1.0 / 60.0).len()
+    }
+
+    #[test]
+    fn collider_shapes_and_groups() {
+        let aabb = AABB::new().scale_x(2.0).build();
+        let wide = ColliderShape::cuboid_from(&aabb);
+        let ball = ColliderShape::Ball { radius: 0.5 };
+
+        assert_eq!(
+            wide,
+            ColliderShape::Cuboid {
+                half_extents: [1.0, 0.5, 0.5]
+            }
+        );
+
+        // A wide box reaches a ball that a default capsule at the same position would miss.
+        assert_eq!(
+            collide(
+                (0.0, wide.clone(), ENTITIES, u32::MAX),
+                (1.4, ball.clone(), ENTITIES, u32::MAX)
+            ),
+            1
+        );
+        assert_eq!(
+            collide(
+                (0.0, ColliderShape::capsule_from(&aabb), ENTITIES, u32::MAX),
+                (1.4, ball.clone(), ENTITIES, u32::MAX)
+            ),
+            0
+        );
+
+        // Boats ignore other boats, but still collide with entities.
+        assert_eq!(
+            collide(
+                (0.0, wide.clone(), BOATS, !BOATS),
+                (1.4, ball.clone(), BOATS, !BOATS)
+            ),
+            0
+        );
+        assert_eq!(
+            collide((0.0, wide, BOATS, !BOATS), (1.4, ball, ENTITIES, u32::MAX)),
+            1
+        );
+
+        assert!(!CollisionGroups::new(BOATS, !BOATS).test(&CollisionGroups::new(BOATS, u32::MAX)));
+        assert!(CollisionGroups::default().test(&CollisionGroups::new(ENTITIES, ENTITIES)));
+    }
+}
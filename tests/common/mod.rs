@@ -0,0 +1,24 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use std::{thread, time::Duration};
+
+use specs::{World, WorldExt};
+use voxelize::{ChunkStatus, Chunks, Mesher};
+
+/// Wait for the chunks meshed by the updates to come back, and put them back into the chunks the way the
+/// generating system does, as nothing else collects them in the tests. Chunks that have been meshed once
+/// skip the light propagation on their next meshes.
+pub fn finish_meshing(world: &World) {
+    let mut mesher = world.write_resource::<Mesher>();
+    let mut chunks = world.write_resource::<Chunks>();
+
+    while !mesher.is_idle() {
+        if let Some((mut chunk, _)) = mesher.results() {
+            chunk.status = ChunkStatus::Ready;
+            chunks.renew(chunk);
+        } else {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{
+        Block, Chunk, ChunkOptions, MeshExporter, MeshFormat, Registry, Vec3, VoxelAccess,
+    };
+
+    fn single_block_chunk() -> (Chunk, Registry) {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.generate();
+
+        let mut chunk = Chunk::new(
+            "test",
+            0,
+            0,
+            &ChunkOptions {
+                size: 4,
+                max_height: 8,
+                sub_chunks: 2,
+            },
+        );
+
+        chunk.set_voxel(1, 0, 1, 1);
+        chunk.calculate_max_height(&registry);
+
+        (chunk, registry)
+    }
+
+    #[test]
+    fn obj_export() {
+        let (chunk, registry) = single_block_chunk();
+
+        let mut exporter = MeshExporter::new();
+        exporter.add_space(&chunk.min, &chunk.max, &chunk, &registry);
+
+        let obj = exporter.export(&MeshFormat::Obj);
+
+        assert!(obj.contains("o Stone"));
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("v ")).count(),
+            24
+        );
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("vt ")).count(),
+            24
+        );
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("f ")).count(),
+            12
+        );
+    }
+
+    #[test]
+    fn gltf_export() {
+        let (chunk, registry) = single_block_chunk();
+
+        let mut exporter = MeshExporter::new();
+        exporter.add_space(&chunk.min, &Vec3(4, 8, 4), &chunk, &registry);
+
+        let gltf: serde_json::Value =
+            serde_json::from_str(&exporter.export(&MeshFormat::Gltf)).unwrap();
+
+        assert_eq!(gltf["meshes"].as_array().unwrap().len(), 1);
+        assert_eq!(gltf["accessors"][0]["count"], 24);
+        assert_eq!(gltf["accessors"][4]["count"], 36);
+        assert!(gltf["meshes"][0]["primitives"][0]["attributes"]["COLOR_0"].is_number());
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use specs::{RunNow, World, WorldExt};
+    use voxelize::{
+        Block, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, ChunkUpdatingSystem, Chunks,
+        Mesher, MessageQueue, Registry, Stats, Vec3, VoxelAccess, WorldConfig, WorldEvents,
+    };
+
+    use crate::common::finish_meshing;
+
+    const WATER: u32 = 2;
+
+    /// Run the voxel updates for a number of ticks, letting the fluid flow.
+    fn run(world: &World, ticks: u64) {
+        for _ in 0..ticks {
+            world.write_resource::<Stats>().tick += 1;
+            ChunkUpdatingSystem.run_now(world);
+            finish_meshing(world);
+        }
+    }
+
+    #[test]
+    fn fluid_spreads_and_dries_up() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Water").id(WATER).fluid_flow(1, 3).build());
+        registry.generate();
+        assert!(registry.get_block_by_id(WATER).is_fluid);
+
+        let config = WorldConfig::new().max_height(16).sub_chunks(1).build();
+
+        let mut chunks = Chunks::new(&config);
+
+        for cx in -1..=1 {
+            for cz in -1..=1 {
+                let mut chunk = Chunk::new(
+                    &format!("{cx}|{cz}"),
+                    cx,
+                    cz,
+                    &ChunkOptions {
+                        size: config.chunk_size,
+                        max_height: config.max_height,
+                        sub_chunks: config.sub_chunks,
+                    },
+                );
+                chunk.status = ChunkStatus::Ready;
+                chunks.add(chunk);
+            }
+        }
+
+        for vx in 0..16 {
+            for vz in 0..16 {
+                chunks.set_voxel(vx, 0, vz, 1);
+            }
+        }
+
+        chunks.update_voxel(&Vec3(8, 4, 8), WATER);
+
+        let mut world = World::new();
+        world.insert(config);
+        world.insert(registry);
+        world.insert(Stats::new(false, ""));
+        world.insert(MessageQueue::new());
+        world.insert(chunks);
+        world.insert(Mesher::new());
+        world.insert(WorldEvents::new());
+        world.insert(ChunkInterests::new());
+
+        run(&world, 20);
+
+        {
+            let chunks = world.read_resource::<Chunks>();
+
+            // The source falls onto the floor, and spreads three blocks from where it lands.
+            assert_eq!(chunks.get_voxel(8, 2, 8), WATER);
+            assert_eq!(chunks.get_voxel_stage(8, 1, 8), 1);
+            assert_eq!(chunks.get_voxel_stage(10, 1, 8), 3);
+            assert_eq!(chunks.get_voxel_stage(9, 1, 9), 3);
+            assert_eq!(chunks.get_voxel(11, 1, 8), 0);
+            assert_eq!(chunks.get_voxel(9, 2, 8), 0);
+        }
+
+        world
+            .write_resource::<Chunks>()
+            .update_voxel(&Vec3(8, 4, 8), 0);
+
+        run(&world, 20);
+
+        let chunks = world.read_resource::<Chunks>();
+        let registry = world.read_resource::<Registry>();
+
+        for vx in 0..16 {
+            for vy in 1..16 {
+                for vz in 0..16 {
+                    assert!(registry.is_air(chunks.get_voxel(vx, vy, vz)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, Entity, RunNow, World, WorldExt};
+    use voxelize::{
+        Block, Chunk, ChunkOptions, ChunkStatus, Chunks, ClientFlag, ClientMovementComp,
+        ClientMovementSystem, CurrentChunkComp, Events, IDComp, PositionComp, Registry, RigidBody,
+        RigidBodyComp, Stats, Vec3, VoxelAccess, WorldConfig, AABB, MOVEMENT_CORRECTION_EVENT,
+    };
+
+    fn setup() -> (World, Entity) {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.generate();
+
+        let config = WorldConfig::new()
+            .max_height(16)
+            .sub_chunks(1)
+            .authoritative_movement(true)
+            .max_client_speed(10.0)
+            .build();
+
+        let mut chunks = Chunks::new(&config);
+        let mut chunk = Chunk::new(
+            "0|0",
+            0,
+            0,
+            &ChunkOptions {
+                size: config.chunk_size,
+                max_height: config.max_height,
+                sub_chunks: config.sub_chunks,
+            },
+        );
+        chunk.status = ChunkStatus::Ready;
+        chunks.add(chunk);
+
+        // A floor, and a wall along x = 8.
+        for vx in 0..16 {
+            for vz in 0..16 {
+                chunks.set_voxel(vx, 0, vz, 1);
+            }
+        }
+        for vy in 1..4 {
+            for vz in 0..16 {
+                chunks.set_voxel(8, vy, vz, 1);
+            }
+        }
+
+        let mut stats = Stats::new(false, "");
+        stats.delta = 0.1;
+
+        let mut world = World::new();
+        world.register::<ClientFlag>();
+        world.register::<IDComp>();
+        world.register::<CurrentChunkComp>();
+        world.register::<ClientMovementComp>();
+        world.register::<RigidBodyComp>();
+        world.register::<PositionComp>();
+        world.insert(config);
+        world.insert(registry);
+        world.insert(stats);
+        world.insert(chunks);
+        world.insert(Events::new());
+
+        let mut body =
+            RigidBody::new(&AABB::new().scale_x(0.8).scale_y(1.8).scale_z(0.8).build()).build();
+        body.set_position(4.5, 1.9, 4.5);
+
+        let client = world
+            .create_entity()
+            .with(ClientFlag)
+            .with(IDComp::new("client"))
+            .with(CurrentChunkComp::default())
+            .with(ClientMovementComp::new())
+            .with(RigidBodyComp::new(&body))
+            .with(PositionComp::default())
+            .build();
+
+        (world, client)
+    }
+
+    fn move_to(world: &mut World, client: Entity, target: Vec3<f32>) -> Vec3<f32> {
+        world
+            .write_component::<ClientMovementComp>()
+            .get_mut(client)
+            .unwrap()
+            .target = Some(target);
+
+        ClientMovementSystem.run_now(world);
+
+        world
+            .read_component::<PositionComp>()
+            .get(client)
+            .unwrap()
+            .0
+            .clone()
+    }
+
+    fn corrections(world: &World) -> usize {
+        world
+            .read_resource::<Events>()
+            .queue
+            .iter()
+            .filter(|event| event.name == MOVEMENT_CORRECTION_EVENT)
+            .count()
+    }
+
+    #[test]
+    fn movement_is_validated() {
+        let (mut world, client) = setup();
+
+        // Walking within the speed limit is accepted as is.
+        let position = move_to(&mut world, client, Vec3(5.3, 1.9, 4.5));
+        assert!((position.0 - 5.3).abs() < 1e-3);
+        assert_eq!(corrections(&world), 0);
+
+        // Teleporting is limited to the maximum speed, and the client gets corrected.
+        let position = move_to(&mut world, client, Vec3(5.3, 1.9, 12.0));
+        assert!((position.2 - 5.5).abs() < 1e-3);
+        assert_eq!(corrections(&world), 1);
+
+        // Walking into the wall stops at the wall.
+        for _ in 0..10 {
+            move_to(&mut world, client, Vec3(9.5, 1.9, 5.5));
+        }
+        let position = world
+            .read_component::<PositionComp>()
+            .get(client)
+            .unwrap()
+            .0
+            .clone();
+        assert!(position.0 < 7.7);
+
+        // Hovering in the air ends with the client falling back to the floor.
+        for _ in 0..40 {
+            move_to(&mut world, client, Vec3(6.0, 6.0, 5.5));
+        }
+        let position = world
+            .read_component::<PositionComp>()
+            .get(client)
+            .unwrap()
+            .0
+            .clone();
+        assert!(position.1 < 2.0);
+    }
+}
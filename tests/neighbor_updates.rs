@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use specs::{RunNow, World, WorldExt};
+    use voxelize::{
+        Block, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, ChunkUpdatingSystem, Chunks,
+        Mesher, MessageQueue, Registry, Stats, Vec2, Vec3, VoxelAccess, WorldConfig, WorldEvent,
+        WorldEvents,
+    };
+
+    use crate::common::finish_meshing;
+
+    #[test]
+    fn sand_falls_with_cascade_limit() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(
+            &Block::new("Sand")
+                .id(2)
+                .on_neighbor_update(|voxel, _, space, registry| {
+                    let Vec3(vx, vy, vz) = voxel;
+
+                    if vy > 0 && registry.is_air(space.get_voxel(vx, vy - 1, vz)) {
+                        vec![(voxel, 0), (Vec3(vx, vy - 1, vz), 2)]
+                    } else {
+                        vec![]
+                    }
+                })
+                .build(),
+        );
+        registry.generate();
+
+        let config = WorldConfig::new()
+            .max_height(16)
+            .sub_chunks(1)
+            .max_neighbor_updates_per_tick(2)
+            .build();
+
+        let mut chunks = Chunks::new(&config);
+
+        for cx in -1..=1 {
+            for cz in -1..=1 {
+                let mut chunk = Chunk::new(
+                    &format!("{cx}|{cz}"),
+                    cx,
+                    cz,
+                    &ChunkOptions {
+                        size: config.chunk_size,
+                        max_height: config.max_height,
+                        sub_chunks: config.sub_chunks,
+                    },
+                );
+                chunk.status = ChunkStatus::Ready;
+                chunks.add(chunk);
+            }
+        }
+
+        chunks.set_voxel(8, 0, 8, 1);
+        chunks.set_voxel(8, 1, 8, 1);
+        for vy in 2..=6 {
+            chunks.set_voxel(8, vy, 8, 2);
+        }
+
+        chunks.update_voxel(&Vec3(8, 1, 8), 0);
+
+        let mut world = World::new();
+        world.insert(config.to_owned());
+        world.insert(registry);
+        world.insert(Stats::new(false, ""));
+        world.insert(MessageQueue::new());
+        world.insert(chunks);
+        world.insert(Mesher::new());
+        world.insert(WorldEvents::new());
+        world.insert(ChunkInterests::new());
+
+        let column = |world: &World| {
+            let chunks = world.read_resource::<Chunks>();
+            (1..=6)
+                .map(|vy| chunks.get_voxel(8, vy, 8))
+                .collect::<Vec<_>>()
+        };
+
+        ChunkUpdatingSystem.run_now(&world);
+        finish_meshing(&world);
+        assert_eq!(column(&world), vec![2, 0, 2, 2, 2, 2]);
+        assert_eq!(
+            world.write_resource::<WorldEvents>().drain().first(),
+            Some(&WorldEvent::VoxelChanged {
+                voxel: Vec3(8, 1, 8),
+                from: 1,
+                to: 0,
+            })
+        );
+
+        for _ in 0..10 {
+            ChunkUpdatingSystem.run_now(&world);
+            finish_meshing(&world);
+        }
+        assert_eq!(column(&world), vec![2, 2, 2, 2, 2, 0]);
+        assert!(world.read_resource::<Chunks>().raw(&Vec2(0, 0)).is_some());
+    }
+}
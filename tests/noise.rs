@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{NoiseGraph, NoiseNode, NoiseOptions, SeededNoise};
+
+    #[test]
+    fn fractal_graph_matches_seeded_noise() {
+        let options = NoiseOptions::new().frequency(0.01).seed(3).build();
+
+        let mut noise = SeededNoise::new(0, &options);
+        noise.set_seed(42);
+
+        let graph = NoiseGraph::new(42, &NoiseNode::fractal(&options));
+
+        for (vx, vz) in [(0, 0), (13, -7), (-120, 45)] {
+            assert_eq!(noise.get2d(vx, vz), graph.get2d(vx, vz));
+            assert_eq!(noise.get3d(vx, 5, vz), graph.get3d(vx, 5, vz));
+        }
+
+        // Identical fractals in the same graph are seeded apart.
+        let sum = NoiseGraph::new(
+            42,
+            &NoiseNode::Add {
+                a: Box::new(NoiseNode::fractal(&options)),
+                b: Box::new(NoiseNode::fractal(&options)),
+            },
+        );
+
+        assert!([(0, 0), (13, -7), (-120, 45)]
+            .iter()
+            .any(|&(vx, vz)| sum.get2d(vx, vz) != 2.0 * graph.get2d(vx, vz)));
+    }
+
+    #[test]
+    fn combinators() {
+        let node: NoiseNode = serde_json::from_str(
+            r#"{
+                "type": "clamp",
+                "min": -0.5,
+                "max": 0.5,
+                "source": {
+                    "type": "domainWarp",
+                    "strength": 8.0,
+                    "source": { "type": "worley", "frequency": 0.05, "distance": true },
+                    "warp": { "type": "billow", "options": { "frequency": 0.02 } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let first = NoiseGraph::new(7, &node);
+        let second = NoiseGraph::new(7, &node);
+
+        for vx in -20..20 {
+            let value = first.get3d(vx, vx * 2, -vx);
+            assert!((-0.5..=0.5).contains(&value));
+            assert_eq!(value, second.get3d(vx, vx * 2, -vx));
+        }
+    }
+}
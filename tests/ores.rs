@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{
+        Block, Chunk, ChunkOptions, ChunkStage, Ore, OreStage, Registry, Resources, VoxelAccess,
+        WorldConfig,
+    };
+
+    fn generate(seed: u32) -> Chunk {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Iron Ore").id(2).build());
+        registry.generate();
+
+        let config = WorldConfig::new().seed(seed).build();
+
+        let mut chunk = Chunk::new(
+            "test",
+            1,
+            -2,
+            &ChunkOptions {
+                size: 8,
+                max_height: 16,
+                sub_chunks: 2,
+            },
+        );
+
+        for vx in chunk.min.0..chunk.max.0 {
+            for vy in 0..16 {
+                for vz in chunk.min.2..chunk.max.2 {
+                    chunk.set_voxel(vx, vy, vz, 1);
+                }
+            }
+        }
+
+        let stage = OreStage::new()
+            .add_ore(
+                Ore::new("Iron Ore")
+                    .height_range(2, 10)
+                    .size(6)
+                    .frequency(3.0)
+                    .replaceable(&["Stone"])
+                    .build(),
+                &registry,
+            )
+            .unwrap();
+
+        stage.process(
+            chunk,
+            Resources {
+                registry: &registry,
+                config: &config,
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn deterministic_veins() {
+        let first = generate(1234);
+        let second = generate(1234);
+
+        assert_eq!(first.voxels.data, second.voxels.data);
+        assert!(first.voxels.data.iter().any(|&voxel| voxel == 2));
+    }
+
+    #[test]
+    fn unknown_blocks_and_missing_terrain() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Iron Ore").id(2).build());
+        registry.generate();
+
+        let add = |ore: Ore| OreStage::new().add_ore(ore, &registry).is_ok();
+
+        assert!(add(Ore::new("Iron Ore").replaceable(&["Stone"]).build()));
+        assert!(!add(Ore::new("Gold Ore").build()));
+        assert!(!add(Ore::new("Iron Ore").replaceable(&["Granite"]).build()));
+        assert!(!add(Ore::new("Iron Ore").biomes(&["Mountains"]).build()));
+    }
+}
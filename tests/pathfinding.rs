@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use specs::{Builder, RunNow, World, WorldExt};
+    use voxelize::{
+        find_path, Block, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, Chunks, ClientFlag,
+        CollisionsComp, CurrentChunkComp, Events, IDComp, InteractorComp, PathComp, PathOptions,
+        Pathfinder, PathfindingSystem, Physics, PhysicsSystem, PositionComp, Registry, RigidBody,
+        RigidBodyComp, Stats, Vec3, VoxelAccess, WorldConfig, AABB,
+    };
+
+    /// A floor with a wall along x = 8 that has a gap at z >= 14, and a raised platform in a corner.
+    fn terrain() -> (WorldConfig, Registry, Chunks) {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.generate();
+
+        let config = WorldConfig::new().max_height(16).sub_chunks(1).build();
+
+        let mut chunks = Chunks::new(&config);
+        let mut chunk = Chunk::new(
+            "0|0",
+            0,
+            0,
+            &ChunkOptions {
+                size: config.chunk_size,
+                max_height: config.max_height,
+                sub_chunks: config.sub_chunks,
+            },
+        );
+        chunk.status = ChunkStatus::Ready;
+        chunks.add(chunk);
+
+        for vx in 0..16 {
+            for vz in 0..16 {
+                chunks.set_voxel(vx, 0, vz, 1);
+            }
+        }
+        for vy in 1..4 {
+            for vz in 0..14 {
+                chunks.set_voxel(8, vy, vz, 1);
+            }
+        }
+        for vx in 0..4 {
+            for vz in 0..4 {
+                chunks.set_voxel(vx, 1, 12 + vz, 1);
+            }
+        }
+
+        (config, registry, chunks)
+    }
+
+    #[test]
+    fn paths_go_around_walls_and_up_steps() {
+        let (_, registry, chunks) = terrain();
+        let options = PathOptions::new().build();
+
+        let path = find_path(
+            &chunks,
+            &registry,
+            &Vec3(2, 1, 2),
+            &Vec3(12, 1, 2),
+            &options,
+        )
+        .unwrap();
+        assert!(path.complete);
+        assert_eq!(path.nodes.last(), Some(&Vec3(12, 1, 2)));
+
+        // The path goes through the gap, one voxel at a time.
+        assert!(path.nodes.iter().any(|node| node.0 == 8 && node.2 >= 14));
+        path.nodes.windows(2).for_each(|pair| {
+            assert!((pair[0].0 - pair[1].0).abs() <= 1);
+            assert!((pair[0].2 - pair[1].2).abs() <= 1);
+        });
+
+        // Only entities that can step up get onto the platform.
+        let path = find_path(
+            &chunks,
+            &registry,
+            &Vec3(2, 1, 2),
+            &Vec3(1, 2, 13),
+            &options,
+        )
+        .unwrap();
+        assert!(path.complete);
+
+        let flat = PathOptions::new().step_height(0).build();
+        let path = find_path(&chunks, &registry, &Vec3(2, 1, 2), &Vec3(1, 2, 13), &flat).unwrap();
+        assert!(!path.complete);
+
+        // Bounded searches end at the closest voxel they found.
+        let bounded = PathOptions::new().max_nodes(8).build();
+        let path = find_path(
+            &chunks,
+            &registry,
+            &Vec3(2, 1, 2),
+            &Vec3(12, 1, 2),
+            &bounded,
+        )
+        .unwrap();
+        assert!(!path.complete);
+        assert!(path.nodes.len() > 1);
+    }
+
+    #[test]
+    fn entities_walk_paths_found_in_the_background() {
+        let (config, registry, chunks) = terrain();
+
+        let mut stats = Stats::new(false, "");
+        stats.delta = config.physics_step;
+
+        let mut world = World::new();
+        world.register::<ClientFlag>();
+        world.register::<IDComp>();
+        world.register::<CurrentChunkComp>();
+        world.register::<InteractorComp>();
+        world.register::<CollisionsComp>();
+        world.register::<RigidBodyComp>();
+        world.register::<PositionComp>();
+        world.register::<PathComp>();
+        world.insert(config);
+        world.insert(registry);
+        world.insert(stats);
+        world.insert(chunks);
+        world.insert(ChunkInterests::new());
+        world.insert(Events::new());
+        world.insert(Physics::new());
+        world.insert(Pathfinder::new());
+
+        let mut body =
+            RigidBody::new(&AABB::new().scale_x(0.6).scale_y(1.8).scale_z(0.6).build()).build();
+        body.set_position(2.5, 1.9, 2.5);
+
+        let mut path = PathComp::new(4.0, &PathOptions::new().body(&body).build());
+        path.navigate_to(&Vec3(12, 1, 2));
+
+        let entity = world
+            .create_entity()
+            .with(IDComp::new("walker"))
+            .with(CurrentChunkComp::default())
+            .with(RigidBodyComp::new(&body))
+            .with(PositionComp::default())
+            .with(path)
+            .build();
+
+        for _ in 0..1200 {
+            PathfindingSystem.run_now(&world);
+            PhysicsSystem.run_now(&world);
+
+            if !world
+                .read_component::<PathComp>()
+                .get(entity)
+                .unwrap()
+                .is_navigating()
+            {
+                break;
+            }
+
+            // Give the workers time to find the path.
+            if world.read_resource::<Pathfinder>().is_pending(entity) {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        assert!(!world
+            .read_component::<PathComp>()
+            .get(entity)
+            .unwrap()
+            .is_navigating());
+
+        let Vec3(px, _, pz) = world
+            .read_component::<RigidBodyComp>()
+            .get(entity)
+            .unwrap()
+            .0
+            .get_position();
+        assert!((px - 12.5).abs() < 0.5);
+        assert!((pz - 2.5).abs() < 0.5);
+
+        while !world.read_resource::<Pathfinder>().is_idle() {
+            world.write_resource::<Pathfinder>().results();
+        }
+    }
+}
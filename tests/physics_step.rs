@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{Physics, RigidBody, Vec3, AABB};
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn fixed_timestep_accumulator() {
+        let mut physics = Physics::new();
+
+        // Exact deltas run exactly one step each.
+        for _ in 0..10 {
+            assert_eq!(physics.advance(STEP, STEP, 5), (1, 0.0));
+        }
+
+        // Short ticks accumulate until a step is due, and report how far into the next step they are.
+        let (steps, alpha) = physics.advance(STEP / 2.0, STEP, 5);
+        assert_eq!(steps, 0);
+        assert!((alpha - 0.5).abs() < 1e-4);
+
+        let (steps, alpha) = physics.advance(STEP, STEP, 5);
+        assert_eq!(steps, 1);
+        assert!((alpha - 0.5).abs() < 1e-4);
+
+        // A long tick is capped, and the time beyond the cap is dropped.
+        let (steps, alpha) = physics.advance(STEP * 20.0, STEP, 5);
+        assert_eq!(steps, 5);
+        assert!((alpha - 0.5).abs() < 1e-3);
+
+        let (steps, _) = physics.advance(STEP / 2.0, STEP, 5);
+        assert_eq!(steps, 1);
+    }
+
+    #[test]
+    fn interpolated_position() {
+        let mut body = RigidBody::new(&AABB::new().build()).build();
+        body.set_position(0.0, 0.0, 0.0);
+        body.previous_position = Some(body.get_position());
+        body.set_position(2.0, 4.0, 0.0);
+
+        // Teleporting clears the previous step, so there is nothing to interpolate from.
+        assert_eq!(body.get_interpolated_position(0.5), Vec3(2.0, 4.0, 0.0));
+
+        body.previous_position = Some(Vec3(0.0, 0.0, 0.0));
+        assert_eq!(body.get_interpolated_position(0.5), Vec3(1.0, 2.0, 0.0));
+        assert_eq!(body.get_interpolated_position(1.0), Vec3(2.0, 4.0, 0.0));
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use voxelize::{
+        Block, ChunkUtils, Chunks, Pregenerator, Registry, Vec2, WorldConfig, WorldGenConfig,
+        WorldGenHarness,
+    };
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Dirt").id(2).build());
+        registry.generate();
+        registry
+    }
+
+    fn worldgen() -> WorldGenConfig {
+        WorldGenConfig::from_json(
+            r#"{
+                "terrain": {
+                    "layers": [{ "name": "hills", "noise": { "frequency": 0.05 }, "weight": 1.0,
+                                 "bias": [[-1.0, 2.0], [1.0, 2.0]],
+                                 "offset": [[-1.0, 0.3], [1.0, 0.6]] }]
+                },
+                "stages": [{ "type": "baseTerrain", "base": "Stone" }]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn config(save_dir: &str) -> WorldConfig {
+        let save_dir = env::temp_dir().join(save_dir);
+        let _ = fs::remove_dir_all(&save_dir);
+
+        WorldConfig::new()
+            .seed(7)
+            .chunk_size(8)
+            .max_height(32)
+            .saving(true)
+            .save_dir(save_dir.to_str().unwrap())
+            .build()
+    }
+
+    /// Check that the saved chunks of a region are the ones the harness generates.
+    fn assert_saved(worldgen: &WorldGenConfig, config: &WorldConfig, registry: &Registry) {
+        let mut harness = WorldGenHarness::new(config, registry);
+        harness.stages = worldgen.build_stages(config, registry).unwrap();
+
+        let mut chunks = Chunks::new(config);
+
+        for expected in harness.generate(&Vec2(-1, -1), &Vec2(1, 1)) {
+            let loaded = chunks.try_load(&expected.coords).unwrap();
+            assert_eq!(loaded.voxels.data, expected.voxels.data);
+        }
+    }
+
+    #[test]
+    fn pregenerated_chunks_load_back() {
+        let registry = registry();
+        let worldgen = worldgen();
+        let config = config("voxelize-pregen-test");
+
+        let mut pregenerator = Pregenerator::from_worldgen(&worldgen, &config, &registry).unwrap();
+        pregenerator.set_tile_size(1);
+
+        let mut calls = 0;
+        let count = pregenerator
+            .run(&Vec2(-1, -1), &Vec2(0, 0), |_, total| {
+                calls += 1;
+                assert_eq!(total, 4);
+            })
+            .unwrap();
+
+        assert_eq!(count, 4);
+        assert_eq!(calls, 4);
+
+        let mut harness = WorldGenHarness::new(&config, &registry);
+        harness.stages = worldgen.build_stages(&config, &registry).unwrap();
+
+        let mut chunks = Chunks::new(&config);
+
+        for expected in harness.generate(&Vec2(-1, -1), &Vec2(0, 0)) {
+            let loaded = chunks.try_load(&expected.coords).unwrap();
+            assert_eq!(loaded.voxels.data, expected.voxels.data);
+        }
+
+        fs::remove_dir_all(&config.save_dir).unwrap();
+    }
+
+    #[test]
+    fn meshed_tiles_share_their_padding() {
+        let registry = registry();
+        let worldgen = worldgen();
+        let config = config("voxelize-pregen-meshed-test");
+
+        let mut pregenerator = Pregenerator::from_worldgen(&worldgen, &config, &registry).unwrap();
+        pregenerator.set_tile_size(1);
+        pregenerator.set_meshing(true);
+
+        // Tiles of one chunk reuse most of the padding light needs from the tiles before them.
+        let count = pregenerator
+            .run(&Vec2(-1, -1), &Vec2(1, 1), |_, _| {})
+            .unwrap();
+        assert_eq!(count, 9);
+
+        assert_saved(&worldgen, &config, &registry);
+
+        fs::remove_dir_all(&config.save_dir).unwrap();
+    }
+
+    #[test]
+    fn save_errors_are_returned() {
+        let registry = registry();
+        let worldgen = worldgen();
+        let config = config("voxelize-pregen-error-test");
+
+        // A folder in the way of a chunk file.
+        let path = env::temp_dir()
+            .join("voxelize-pregen-error-test")
+            .join("chunks")
+            .join(format!("{}.json", ChunkUtils::get_chunk_name(0, 0)));
+        fs::create_dir_all(&path).unwrap();
+
+        let pregenerator = Pregenerator::from_worldgen(&worldgen, &config, &registry).unwrap();
+
+        assert!(pregenerator
+            .run(&Vec2(0, 0), &Vec2(0, 0), |_, _| {})
+            .is_err());
+
+        fs::remove_dir_all(&config.save_dir).unwrap();
+    }
+}
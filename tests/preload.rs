@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{PreloadShape, Vec2, WorldConfig};
+
+    #[test]
+    fn preload_regions() {
+        let circle = PreloadShape::Circle {
+            center: Vec2(10, 10),
+            radius: 2,
+        };
+        assert_eq!(circle.chunks().len(), 13);
+        assert!(!circle.chunks().contains(&Vec2(12, 12)));
+
+        let config = WorldConfig::new()
+            .max_chunk([11, 11])
+            .add_preload_region(circle)
+            .add_preload_region(PreloadShape::Rect {
+                min: Vec2(9, 9),
+                max: Vec2(10, 10),
+            })
+            .build();
+
+        let chunks = config.preload_chunks();
+        assert!(config.preload);
+        assert_eq!(chunks.len(), 11);
+        assert_eq!(chunks.first(), Some(&Vec2(8, 10)));
+
+        let legacy = WorldConfig::new()
+            .preload(true)
+            .preload_radius(3)
+            .build()
+            .preload_chunks();
+        assert_eq!(legacy.len(), 25);
+    }
+}
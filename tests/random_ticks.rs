@@ -0,0 +1,114 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use specs::{RunNow, World, WorldExt};
+    use voxelize::{
+        Block, BlockUtils, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, ChunkUpdatingSystem,
+        Chunks, Mesher, MessageQueue, RandomTickSystem, Registry, Stats, Vec2, Vec3, VoxelAccess,
+        WorldConfig, WorldEvents,
+    };
+
+    use crate::common::finish_meshing;
+
+    /// Grow a field of crops in chunk (0, 0) and (1, 0) with only the first one watched, returning the stages.
+    fn grow_crops(ticks: u64) -> (Vec<u32>, Vec<u32>) {
+        let mut registry = Registry::new();
+        registry.register_block(
+            &Block::new("Crop")
+                .id(1)
+                .random_tick(|voxel, space, _| {
+                    let Vec3(vx, vy, vz) = voxel;
+                    let stage = space.get_voxel_stage(vx, vy, vz);
+
+                    if stage < 3 {
+                        vec![(voxel, BlockUtils::insert_stage(1, stage + 1))]
+                    } else {
+                        vec![]
+                    }
+                })
+                .build(),
+        );
+        registry.generate();
+
+        let config = WorldConfig::new()
+            .chunk_size(8)
+            .max_light_level(8)
+            .max_height(8)
+            .sub_chunks(2)
+            .random_tick_speed(16)
+            .build();
+
+        let mut chunks = Chunks::new(&config);
+
+        for cx in -1..=2 {
+            for cz in -1..=1 {
+                let mut chunk = Chunk::new(
+                    &format!("{cx}|{cz}"),
+                    cx,
+                    cz,
+                    &ChunkOptions {
+                        size: config.chunk_size,
+                        max_height: config.max_height,
+                        sub_chunks: config.sub_chunks,
+                    },
+                );
+                chunk.status = ChunkStatus::Ready;
+                chunks.add(chunk);
+            }
+        }
+
+        for vx in 0..16 {
+            for vz in 0..8 {
+                chunks.set_voxel(vx, 1, vz, 1);
+                chunks.set_voxel(vx, 5, vz, 1);
+            }
+        }
+
+        let mut interests = ChunkInterests::new();
+        interests.add("client", &Vec2(0, 0));
+
+        let mut world = World::new();
+        world.insert(config.to_owned());
+        world.insert(registry);
+        world.insert(Stats::new(false, ""));
+        world.insert(MessageQueue::new());
+        world.insert(interests);
+        world.insert(chunks);
+        world.insert(Mesher::new());
+        world.insert(WorldEvents::new());
+
+        for tick in 0..ticks {
+            world.write_resource::<Stats>().tick = tick;
+
+            RandomTickSystem.run_now(&world);
+            ChunkUpdatingSystem.run_now(&world);
+            finish_meshing(&world);
+        }
+
+        let chunks = world.read_resource::<Chunks>();
+        let stages = |min_x: i32| {
+            (min_x..min_x + 8)
+                .flat_map(|vx| {
+                    [1, 5]
+                        .into_iter()
+                        .flat_map(move |vy| (0..8).map(move |vz| (vx, vy, vz)))
+                })
+                .map(|(vx, vy, vz)| chunks.get_voxel_stage(vx, vy, vz))
+                .collect::<Vec<_>>()
+        };
+
+        (stages(0), stages(8))
+    }
+
+    #[test]
+    fn random_ticks_are_deterministic() {
+        let (watched, unwatched) = grow_crops(6);
+
+        assert!(watched.iter().any(|&stage| stage > 0));
+        assert!(watched.iter().any(|&stage| stage < 3));
+        assert!(unwatched.iter().all(|&stage| stage == 0));
+
+        assert_eq!(grow_crops(6).0, watched);
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{
+        raycast, Block, BlockRotation, Chunk, ChunkOptions, Registry, Vec3, VoxelAccess, AABB,
+    };
+
+    #[test]
+    fn raycast_hits_rotated_aabbs() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(
+            &Block::new("Slab")
+                .id(2)
+                .rotatable(true)
+                .aabbs(&[AABB::new().scale_y(0.5).build()])
+                .build(),
+        );
+        registry.register_block(&Block::new("Water").id(3).is_fluid(true).build());
+        registry.generate();
+
+        let mut chunk = Chunk::new(
+            "test",
+            0,
+            0,
+            &ChunkOptions {
+                size: 16,
+                max_height: 16,
+                sub_chunks: 1,
+            },
+        );
+
+        chunk.set_voxel(2, 1, 2, 1);
+        chunk.set_voxel(4, 1, 2, 2);
+        chunk.set_voxel(6, 1, 2, 2);
+        chunk.set_voxel_rotation(6, 1, 2, &BlockRotation::NY(0.0));
+        chunk.set_voxel(8, 3, 2, 3);
+        chunk.set_voxel(8, 1, 2, 1);
+
+        let down = Vec3(0.0, -1.0, 0.0);
+        let cast = |x: f32| raycast(&chunk, &registry, &Vec3(x, 10.0, 2.5), &down, 20.0);
+
+        let stone = cast(2.5).unwrap();
+        assert_eq!(stone.voxel, Vec3(2, 1, 2));
+        assert_eq!(stone.normal, Vec3(0, 1, 0));
+        assert!((stone.distance - 8.0).abs() < 1e-4);
+
+        let slab = cast(4.5).unwrap();
+        assert_eq!(slab.voxel, Vec3(4, 1, 2));
+        assert!((slab.point.1 - 1.5).abs() < 1e-4);
+
+        let flipped = cast(6.5).unwrap();
+        assert!((flipped.point.1 - 2.0).abs() < 1e-4);
+
+        assert_eq!(cast(8.5).unwrap().voxel, Vec3(8, 1, 2));
+        assert!(cast(10.5).is_none());
+
+        let side = raycast(
+            &chunk,
+            &registry,
+            &Vec3(0.5, 1.25, 2.5),
+            &Vec3(1.0, 0.0, 0.0),
+            1.0,
+        );
+        assert!(side.is_none());
+    }
+}
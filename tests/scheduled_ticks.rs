@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use specs::{RunNow, World, WorldExt};
+    use voxelize::{
+        Block, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, ChunkUpdatingSystem, Chunks,
+        Mesher, MessageQueue, Registry, ScheduledTicks, Stats, Vec2, Vec3, VoxelAccess,
+        WorldConfig, WorldEvents,
+    };
+
+    #[test]
+    fn schedule_reschedule_and_cancel() {
+        let mut ticks = ScheduledTicks::new(16);
+
+        ticks.schedule(&Vec3(0, 0, 0), 5);
+        ticks.schedule(&Vec3(1, 0, 0), 3);
+        ticks.schedule(&Vec3(2, 0, 0), 8);
+        ticks.schedule(&Vec3(0, 0, 0), 2);
+
+        assert_eq!(ticks.len(), 3);
+        assert_eq!(ticks.get(&Vec3(0, 0, 0)), Some(2));
+        assert_eq!(ticks.cancel(&Vec3(2, 0, 0)), Some(8));
+
+        assert!(ticks.pop_due(1).is_empty());
+        assert_eq!(ticks.pop_due(4), vec![Vec3(0, 0, 0), Vec3(1, 0, 0)]);
+        assert!(ticks.pop_due(10).is_empty());
+        assert!(ticks.is_empty());
+    }
+
+    #[test]
+    fn paused_chunks_keep_remaining_ticks() {
+        let mut ticks = ScheduledTicks::new(16);
+
+        ticks.schedule(&Vec3(1, 0, 1), 10);
+        ticks.schedule(&Vec3(17, 0, 1), 10);
+
+        ticks.pause_chunk(&Vec2(0, 0), 4);
+        assert!(ticks.is_paused(&Vec2(0, 0)));
+        assert_eq!(ticks.get(&Vec3(1, 0, 1)), None);
+        assert_eq!(ticks.len(), 2);
+
+        assert_eq!(ticks.pop_due(20), vec![Vec3(17, 0, 1)]);
+
+        ticks.resume_chunk(&Vec2(0, 0), 30);
+        assert_eq!(ticks.get(&Vec3(1, 0, 1)), Some(36));
+        assert!(ticks.pop_due(35).is_empty());
+        assert_eq!(ticks.pop_due(36), vec![Vec3(1, 0, 1)]);
+
+        // Chunks without timers aren't tracked, and a paused chunk is dropped once its last timer is.
+        ticks.pause_chunk(&Vec2(0, 0), 40);
+        assert!(!ticks.is_paused(&Vec2(0, 0)));
+
+        ticks.schedule(&Vec3(1, 0, 1), 50);
+        ticks.pause_chunk(&Vec2(0, 0), 40);
+        assert_eq!(ticks.cancel(&Vec3(1, 0, 1)), Some(10));
+        assert!(ticks.paused_chunks().is_empty());
+    }
+
+    #[test]
+    fn unloaded_chunks_keep_their_remaining_delay() {
+        let mut registry = Registry::new();
+        registry.register_block(
+            &Block::new("Timer")
+                .id(1)
+                .active_fn(|_, _, _| 10, |_, _, _| vec![])
+                .build(),
+        );
+        registry.generate();
+
+        let config = WorldConfig::new().max_height(16).sub_chunks(1).build();
+
+        let mut chunks = Chunks::new(&config);
+        let mut chunk = Chunk::new(
+            "0|0",
+            0,
+            0,
+            &ChunkOptions {
+                size: config.chunk_size,
+                max_height: config.max_height,
+                sub_chunks: config.sub_chunks,
+            },
+        );
+        chunk.status = ChunkStatus::Ready;
+        chunks.add(chunk);
+
+        let timer = Vec3(4, 1, 4);
+        chunks.set_voxel(4, 1, 4, 1);
+        chunks.mark_voxel_active(&timer, 10);
+
+        let mut interests = ChunkInterests::new();
+        interests.add("client", &Vec2(0, 0));
+
+        let mut world = World::new();
+        world.insert(config);
+        world.insert(registry);
+        world.insert(Stats::new(false, ""));
+        world.insert(interests);
+        world.insert(MessageQueue::new());
+        world.insert(chunks);
+        world.insert(Mesher::new());
+        world.insert(WorldEvents::new());
+
+        let run = |world: &World, tick: u64| {
+            world.write_resource::<Stats>().tick = tick;
+            ChunkUpdatingSystem.run_now(world);
+        };
+
+        run(&world, 2);
+
+        // The last client stops watching the chunk, the way `World` unloads it.
+        world
+            .write_resource::<ChunkInterests>()
+            .remove("client", &Vec2(0, 0));
+        world
+            .write_resource::<Chunks>()
+            .pause_chunk_ticks(&Vec2(0, 0), 2);
+
+        for tick in 3..30 {
+            run(&world, tick);
+        }
+
+        assert!(world
+            .read_resource::<Chunks>()
+            .is_chunk_ticks_paused(&Vec2(0, 0)));
+        assert_eq!(world.read_resource::<Chunks>().get_voxel_tick(&timer), None);
+
+        // Loading the chunk again resumes the timer with the 8 ticks it had left.
+        world
+            .write_resource::<ChunkInterests>()
+            .add("client", &Vec2(0, 0));
+        run(&world, 30);
+
+        assert!(!world
+            .read_resource::<Chunks>()
+            .is_chunk_ticks_paused(&Vec2(0, 0)));
+        assert_eq!(
+            world.read_resource::<Chunks>().get_voxel_tick(&timer),
+            Some(38)
+        );
+    }
+}
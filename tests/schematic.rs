@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use voxelize::{
+        Block, Chunk, ChunkOptions, Registry, Schematic, SchematicTransform, Vec3, VoxelAccess,
+    };
+
+    fn sample() -> (Schematic, Registry) {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Dirt").id(2).build());
+        registry.generate();
+
+        let mut chunk = Chunk::new(
+            "test",
+            0,
+            0,
+            &ChunkOptions {
+                size: 4,
+                max_height: 8,
+                sub_chunks: 2,
+            },
+        );
+
+        chunk.set_voxel(0, 0, 0, 1);
+        chunk.set_voxel(2, 0, 0, 2);
+
+        let schematic =
+            Schematic::copy("sample", &chunk, &Vec3(0, 0, 0), &Vec3(3, 1, 2), &registry);
+
+        (schematic, registry)
+    }
+
+    #[test]
+    fn copy_and_transform() {
+        let (schematic, registry) = sample();
+
+        assert_eq!(schematic.size(), Vec3(3, 1, 2));
+        assert_eq!(schematic.palette.len(), 3);
+
+        let updates = schematic.to_updates(&Vec3(10, 5, 10), &SchematicTransform::new(), &registry);
+        assert_eq!(updates.len(), 2);
+        assert!(updates.contains(&(Vec3(10, 5, 10), 1)));
+        assert!(updates.contains(&(Vec3(12, 5, 10), 2)));
+
+        let mirrored = schematic.to_updates(
+            &Vec3(0, 0, 0),
+            &SchematicTransform::new().mirror_x(true),
+            &registry,
+        );
+        assert!(mirrored.contains(&(Vec3(2, 0, 0), 1)));
+        assert!(mirrored.contains(&(Vec3(0, 0, 0), 2)));
+
+        let transform = SchematicTransform::new().rotate(1);
+        assert_eq!(schematic.transformed_size(&transform), Vec3(2, 1, 3));
+
+        let rotated = schematic.to_updates(&Vec3(0, 0, 0), &transform, &registry);
+        assert!(rotated.contains(&(Vec3(0, 0, 2), 1)));
+        assert!(rotated.contains(&(Vec3(0, 0, 0), 2)));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let (schematic, _) = sample();
+
+        let path = env::temp_dir().join("voxelize-schematic-test.json");
+        schematic.save(&path).unwrap();
+
+        let loaded = Schematic::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.name, "sample");
+        assert_eq!(loaded.palette, schematic.palette);
+        assert_eq!(loaded.voxels.data, schematic.voxels.data);
+    }
+}
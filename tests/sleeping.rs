@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, Entity, RunNow, World, WorldExt};
+    use voxelize::{
+        Block, Chunk, ChunkInterests, ChunkOptions, ChunkStatus, Chunks, ClientFlag,
+        CollisionsComp, CurrentChunkComp, Events, IDComp, InteractorComp, Physics, PhysicsSystem,
+        PositionComp, Registry, RigidBody, RigidBodyComp, Stats, Vec2, VoxelAccess, WorldConfig,
+        AABB,
+    };
+
+    fn setup(freeze: bool) -> (World, Entity) {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.generate();
+
+        let config = WorldConfig::new()
+            .max_height(16)
+            .sub_chunks(1)
+            .deterministic_physics(true)
+            .freeze_uninterested_bodies(freeze)
+            .build();
+
+        let mut chunks = Chunks::new(&config);
+        let mut chunk = Chunk::new(
+            "0|0",
+            0,
+            0,
+            &ChunkOptions {
+                size: config.chunk_size,
+                max_height: config.max_height,
+                sub_chunks: config.sub_chunks,
+            },
+        );
+        chunk.status = ChunkStatus::Ready;
+        chunks.add(chunk);
+
+        for vx in 0..16 {
+            for vz in 0..16 {
+                chunks.set_voxel(vx, 0, vz, 1);
+            }
+        }
+
+        let mut stats = Stats::new(false, "");
+        stats.delta = config.physics_step;
+
+        let mut world = World::new();
+        world.register::<ClientFlag>();
+        world.register::<IDComp>();
+        world.register::<CurrentChunkComp>();
+        world.register::<InteractorComp>();
+        world.register::<CollisionsComp>();
+        world.register::<RigidBodyComp>();
+        world.register::<PositionComp>();
+        world.insert(config);
+        world.insert(registry);
+        world.insert(stats);
+        world.insert(chunks);
+        world.insert(ChunkInterests::new());
+        world.insert(Events::new());
+        world.insert(Physics::new());
+
+        let mut body = RigidBody::new(&AABB::new().build()).build();
+        body.set_position(4.5, 3.0, 4.5);
+
+        let entity = world
+            .create_entity()
+            .with(IDComp::new("crate"))
+            .with(CurrentChunkComp::default())
+            .with(RigidBodyComp::new(&body))
+            .with(PositionComp::default())
+            .build();
+
+        (world, entity)
+    }
+
+    fn step(world: &mut World, entity: Entity, ticks: usize) -> (bool, f32) {
+        for _ in 0..ticks {
+            PhysicsSystem.run_now(world);
+        }
+
+        let bodies = world.read_component::<RigidBodyComp>();
+        let body = &bodies.get(entity).unwrap().0;
+
+        (body.sleeping, body.get_position().1)
+    }
+
+    #[test]
+    fn bodies_sleep_until_woken() {
+        let (mut world, entity) = setup(false);
+
+        // The body falls onto the floor and falls asleep.
+        let (sleeping, y) = step(&mut world, entity, 120);
+        assert!(sleeping);
+        assert!((y - 1.5).abs() < 0.05);
+
+        // An impulse wakes it up, and it settles back to sleep.
+        world
+            .write_component::<RigidBodyComp>()
+            .get_mut(entity)
+            .unwrap()
+            .0
+            .apply_impulse(0.0, 5.0, 0.0);
+
+        let (sleeping, y) = step(&mut world, entity, 1);
+        assert!(!sleeping);
+        assert!(y > 1.5);
+
+        let (sleeping, _) = step(&mut world, entity, 120);
+        assert!(sleeping);
+    }
+
+    #[test]
+    fn uninterested_bodies_freeze() {
+        let (mut world, entity) = setup(true);
+
+        // Nobody is watching the chunk, so the body stays in the air.
+        let (_, y) = step(&mut world, entity, 30);
+        assert_eq!(y, 3.0);
+
+        world
+            .write_resource::<ChunkInterests>()
+            .add("client", &Vec2(0, 0));
+
+        let (_, y) = step(&mut world, entity, 30);
+        assert!(y < 3.0);
+    }
+}
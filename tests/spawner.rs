@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, World, WorldExt};
+    use voxelize::{
+        Block, Chunk, ChunkOptions, ChunkStatus, Chunks, Registry, SpawnRule, Spawner, Vec3,
+        VoxelAccess, WorldConfig,
+    };
+
+    #[test]
+    fn spawners_follow_rules_and_caps() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Grass").id(2).build());
+        registry.generate();
+
+        let config = WorldConfig::new().max_height(16).sub_chunks(1).build();
+
+        let mut chunks = Chunks::new(&config);
+        let mut chunk = Chunk::new(
+            "0|0",
+            0,
+            0,
+            &ChunkOptions {
+                size: config.chunk_size,
+                max_height: config.max_height,
+                sub_chunks: config.sub_chunks,
+            },
+        );
+        chunk.status = ChunkStatus::Ready;
+        chunks.add(chunk);
+
+        // Grass on one half of the chunk, stone on the other.
+        for vx in 0..16 {
+            for vz in 0..16 {
+                chunks.set_voxel(vx, 0, vz, if vx < 8 { 2 } else { 1 });
+            }
+        }
+
+        let mut spawner = Spawner::new();
+        spawner.add_rule(
+            SpawnRule::new("Pig")
+                .ground(&["Grass"])
+                .distance_range(0.0, 12.0)
+                .despawn_distance(32.0)
+                .max_per_chunk(3)
+                .attempts(64)
+                .build(),
+        );
+
+        let clients = vec![Vec3(8.0, 2.0, 8.0)];
+        let spawns = spawner.spawns(&chunks, &registry, &config, &clients, &[]);
+
+        assert!(!spawns.is_empty() && spawns.len() <= 3);
+        spawns.iter().for_each(|(etype, Vec3(px, py, pz))| {
+            assert_eq!(etype, "Pig");
+            assert!(*px >= 0.0 && *px < 8.0 && *pz >= 0.0 && *pz < 16.0);
+            assert_eq!(*py, 2.0);
+        });
+
+        // Full chunks get no more spawns.
+        let mut world = World::new();
+        let population = (0..3)
+            .map(|_| {
+                (
+                    world.create_entity().build(),
+                    "pig".to_owned(),
+                    Vec3(4.5, 2.0, 4.5),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(spawner
+            .spawns(&chunks, &registry, &config, &clients, &population)
+            .is_empty());
+
+        // Only entities far from every client are despawned.
+        let far = world.create_entity().build();
+        let mut population = population;
+        population.push((far, "Pig".to_owned(), Vec3(100.0, 2.0, 8.0)));
+        assert_eq!(spawner.despawns(&clients, &population), vec![far]);
+
+        // Entities that need sunlight only spawn in the lit spots.
+        for vx in 4..8 {
+            for vz in 4..8 {
+                chunks.set_sunlight(vx, 1, vz, 15);
+            }
+        }
+
+        let mut spawner = Spawner::new();
+        spawner.add_rule(
+            SpawnRule::new("Pig")
+                .distance_range(0.0, 6.0)
+                .sunlight(10, 15)
+                .attempts(256)
+                .build(),
+        );
+
+        let spawns = spawner.spawns(&chunks, &registry, &config, &clients, &[]);
+
+        assert!(!spawns.is_empty());
+        spawns.iter().for_each(|(_, Vec3(px, _, pz))| {
+            assert!(*px >= 4.0 && *px < 8.0 && *pz >= 4.0 && *pz < 8.0);
+        });
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{
+        Block, Chunk, ChunkOptions, ChunkStage, Registry, Resources, Structure, StructurePlacement,
+        StructureStage, Terrain, Vec3, VoxelAccess, WorldConfig,
+    };
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Grass").id(2).build());
+        registry.register_block(&Block::new("Log").id(3).build());
+        registry.generate();
+        registry
+    }
+
+    /// A stone floor with a grass top at y = 3.
+    fn floor(cx: i32, cz: i32, registry: &Registry) -> Chunk {
+        let mut chunk = Chunk::new(
+            &format!("{cx}|{cz}"),
+            cx,
+            cz,
+            &ChunkOptions {
+                size: 8,
+                max_height: 16,
+                sub_chunks: 1,
+            },
+        );
+
+        for vx in chunk.min.0..chunk.max.0 {
+            for vz in chunk.min.2..chunk.max.2 {
+                for vy in 0..3 {
+                    chunk.set_voxel(vx, vy, vz, 1);
+                }
+                chunk.set_voxel(vx, 3, vz, 2);
+            }
+        }
+
+        chunk.calculate_max_height(registry);
+        chunk
+    }
+
+    /// A log bar running 6 voxels along the x-axis from the placement position.
+    fn bar(ground: &[&str]) -> Structure {
+        Structure::new("Bar")
+            .generator(|at, _| (0..6).map(|dx| (Vec3(at.0 + dx, at.1, at.2), 3)).collect())
+            .ground(ground)
+            .build()
+    }
+
+    fn process(stage: &StructureStage, chunk: Chunk, registry: &Registry) -> Chunk {
+        stage.process(
+            chunk,
+            Resources {
+                registry,
+                config: &WorldConfig::new().build(),
+            },
+            None,
+        )
+    }
+
+    fn grid(spacing: i32, jitter: i32, chance: f64) -> StructurePlacement {
+        StructurePlacement::Grid {
+            spacing,
+            jitter,
+            chance,
+        }
+    }
+
+    #[test]
+    fn places_on_matching_ground() {
+        let registry = registry();
+
+        let stage = StructureStage::new(1, grid(8, 0, 1.0))
+            .add_structure(bar(&["Grass"]))
+            .unwrap();
+        let chunk = process(&stage, floor(0, 0, &registry), &registry);
+
+        // The cell center is (4, 4), and the structure is placed one voxel above the ground.
+        for vx in 4..8 {
+            assert_eq!(chunk.get_voxel(vx, 4, 4), 3);
+        }
+        assert_eq!(chunk.get_voxel(3, 4, 4), 0);
+        assert_eq!(chunk.get_voxel(4, 4, 3), 0);
+        assert_eq!(chunk.get_voxel(4, 3, 4), 2);
+
+        let stage = StructureStage::new(1, grid(8, 0, 1.0))
+            .add_structure(bar(&["Stone"]))
+            .unwrap();
+        let chunk = process(&stage, floor(0, 0, &registry), &registry);
+
+        assert_eq!(chunk.get_voxel(4, 4, 4), 0);
+        assert!(chunk.extra_changes.is_empty());
+    }
+
+    #[test]
+    fn spills_into_neighbors() {
+        let registry = registry();
+
+        let stage = StructureStage::new(1, grid(8, 0, 1.0))
+            .add_structure(bar(&[]))
+            .unwrap();
+        let chunk = process(&stage, floor(0, 0, &registry), &registry);
+
+        let mut spilled = chunk
+            .extra_changes
+            .iter()
+            .map(|(Vec3(vx, vy, vz), id)| (*vx, *vy, *vz, *id))
+            .collect::<Vec<_>>();
+        spilled.sort();
+
+        assert_eq!(spilled, vec![(8, 4, 4, 3), (9, 4, 4, 3)]);
+    }
+
+    #[test]
+    fn seeded_placement() {
+        let registry = registry();
+
+        let generate = |seed: u32| {
+            let stage = StructureStage::new(seed, grid(4, 1, 0.5))
+                .add_structure(bar(&[]))
+                .unwrap();
+
+            let mut voxels = vec![];
+            for cx in -2..2 {
+                for cz in -2..2 {
+                    let chunk = process(&stage, floor(cx, cz, &registry), &registry);
+                    voxels.push((chunk.voxels.data.clone(), chunk.extra_changes.len()));
+                }
+            }
+            voxels
+        };
+
+        let first = generate(1234);
+
+        assert_eq!(first, generate(1234));
+        assert_ne!(first, generate(4321));
+        assert!(first
+            .iter()
+            .any(|(voxels, _)| voxels.iter().any(|&voxel| voxel == 3)));
+    }
+
+    #[test]
+    fn biomes_need_terrain() {
+        let forest = || {
+            Structure::new("Bar")
+                .generator(|_, _| vec![])
+                .biomes(&["Forest"])
+                .build()
+        };
+
+        assert!(StructureStage::new(1, grid(8, 0, 1.0))
+            .add_structure(forest())
+            .is_err());
+        assert!(StructureStage::new(1, grid(8, 0, 1.0))
+            .terrain(Terrain::new(&WorldConfig::new().build()))
+            .add_structure(forest())
+            .is_ok());
+    }
+}
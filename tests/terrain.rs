@@ -1,6 +1,22 @@
 #[cfg(test)]
 mod tests {
-    use voxelize::{NoiseOptions, TerrainLayer};
+    use voxelize::{
+        Biome, BiomeSurfaceStage, Block, Chunk, ChunkOptions, ChunkStage, NoiseOptions, Registry,
+        Resources, Terrain, TerrainLayer, VoxelAccess, WorldConfig,
+    };
+
+    fn flat_terrain(config: &WorldConfig) -> Terrain {
+        let mut terrain = Terrain::new(config);
+
+        terrain.add_layer(
+            &TerrainLayer::new("Test", &NoiseOptions::new().frequency(0.01).build())
+                .add_bias_points(&[[-1.0, 1.0], [1.0, 1.0]])
+                .add_offset_points(&[[-1.0, 0.2], [1.0, 0.2]]),
+            1.0,
+        );
+
+        terrain
+    }
 
     #[test]
     fn terrain_layer_remap() {
@@ -20,4 +36,137 @@ mod tests {
             -1.0 + 2.0 * (5.0 - 3.0) / (8.5 - 3.0)
         );
     }
+
+    #[test]
+    fn biome_blending() {
+        let config = WorldConfig::new().seed(1).build();
+        let mut terrain = Terrain::new(&config);
+
+        terrain.add_layer(
+            &TerrainLayer::new("Test", &NoiseOptions::new().frequency(0.01).build())
+                .add_bias_points(&[[-1.0, 1.0], [1.0, 1.0]])
+                .add_offset_points(&[[-1.0, 0.2], [1.0, 0.6]]),
+            1.0,
+        );
+        terrain.add_biome(&[-1.0], Biome::new("Low", "Stone").surface("Sand"));
+        terrain.add_biome(&[1.0], Biome::new("High", "Stone").surface("Grass"));
+        terrain.set_biome_blend(2, 4.0);
+
+        let weights = terrain.get_biome_weights_at(12, 34);
+        let total = weights.iter().map(|(_, weight)| weight).sum::<f64>();
+
+        assert_eq!(weights.len(), 2);
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(weights[0].0, terrain.get_biome_at(12, 34));
+        assert!(weights[0].1 >= weights[1].1);
+    }
+
+    #[test]
+    fn biome_height_override_blending() {
+        let config = WorldConfig::new().seed(1).build();
+        let mut terrain = flat_terrain(&config);
+
+        terrain.add_biome(&[-1.0], Biome::new("Plains", "Stone"));
+        terrain.add_biome(
+            &[1.0],
+            Biome::new("Peaks", "Stone").add_offset_points(&[[-1.0, 0.6], [1.0, 0.6]]),
+        );
+        terrain.set_biome_blend(2, 4.0);
+
+        for (vx, vz) in [(0, 0), (12, 34), (-50, 80)] {
+            let (bias, offset) = terrain.get_bias_offset(vx, vz);
+
+            // Biomes without an override keep the layer values, and overrides are mixed in by weight.
+            let expected = terrain
+                .get_biome_weights_at(vx, vz)
+                .iter()
+                .map(|(biome, weight)| weight * if biome.name == "Peaks" { 0.6 } else { 0.2 })
+                .sum::<f64>();
+
+            assert!((bias - 1.0).abs() < 1e-9);
+            assert!((offset - expected).abs() < 1e-9);
+            assert!((0.2 - 1e-9..=0.6 + 1e-9).contains(&offset));
+        }
+    }
+
+    fn surface_registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Grass").id(2).build());
+        registry.register_block(&Block::new("Dirt").id(3).build());
+        registry.register_block(&Block::new("Water").id(4).is_fluid(true).build());
+        registry.generate();
+        registry
+    }
+
+    #[test]
+    fn biome_surface_blocks() {
+        let registry = surface_registry();
+        let config = WorldConfig::new().seed(1).build();
+        let mut terrain = flat_terrain(&config);
+        terrain.add_biome(
+            &[0.0],
+            Biome::new("Meadow", "Stone")
+                .surface("Grass")
+                .subsurface("Dirt", 2),
+        );
+
+        let stage = BiomeSurfaceStage::new(terrain, &registry).unwrap();
+
+        let mut chunk = Chunk::new(
+            "test",
+            0,
+            0,
+            &ChunkOptions {
+                size: 4,
+                max_height: 16,
+                sub_chunks: 1,
+            },
+        );
+
+        for vx in 0..4 {
+            for vz in 0..4 {
+                for vy in 0..=8 {
+                    chunk.set_voxel(vx, vy, vz, 1);
+                }
+                chunk.set_max_height(vx, vz, 8);
+            }
+        }
+
+        // A pond on one column, whose surface rules apply to the ground under the water.
+        chunk.set_voxel(0, 9, 0, 4);
+        chunk.set_voxel(0, 10, 0, 4);
+        chunk.set_max_height(0, 0, 10);
+
+        let chunk = stage.process(
+            chunk,
+            Resources {
+                registry: &registry,
+                config: &config,
+            },
+            None,
+        );
+
+        for vx in 0..4 {
+            for vz in 0..4 {
+                assert_eq!(chunk.get_voxel(vx, 8, vz), 2);
+                assert_eq!(chunk.get_voxel(vx, 7, vz), 3);
+                assert_eq!(chunk.get_voxel(vx, 6, vz), 3);
+                assert_eq!(chunk.get_voxel(vx, 5, vz), 1);
+            }
+        }
+
+        assert_eq!(chunk.get_voxel(0, 9, 0), 4);
+        assert_eq!(chunk.get_voxel(0, 10, 0), 4);
+    }
+
+    #[test]
+    fn biome_surface_unknown_block() {
+        let registry = surface_registry();
+        let config = WorldConfig::new().seed(1).build();
+        let mut terrain = flat_terrain(&config);
+        terrain.add_biome(&[0.0], Biome::new("Desert", "Stone").surface("Sand"));
+
+        assert!(BiomeSurfaceStage::new(terrain, &registry).is_err());
+    }
 }
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, RunNow, World, WorldExt};
+    use voxelize::{
+        CurrentChunkComp, CurrentChunkSystem, PositionComp, Vec2, Vec3, WorldConfig, WorldEvent,
+        WorldEvents,
+    };
+
+    #[test]
+    fn chunk_changes() {
+        let mut world = World::new();
+        world.register::<CurrentChunkComp>();
+        world.register::<PositionComp>();
+        world.insert(WorldConfig::new().build());
+        world.insert(WorldEvents::new());
+
+        let entity = world
+            .create_entity()
+            .with(CurrentChunkComp::default())
+            .with(PositionComp(Vec3(4.5, 1.0, 4.5)))
+            .build();
+
+        // New entities come from no chunk, even when they are added to the origin.
+        CurrentChunkSystem.run_now(&world);
+        assert_eq!(
+            world.write_resource::<WorldEvents>().drain(),
+            vec![WorldEvent::ChunkChanged {
+                entity,
+                from: None,
+                to: Vec2(0, 0),
+            }]
+        );
+
+        // Moving within the chunk is no change.
+        world
+            .write_component::<PositionComp>()
+            .get_mut(entity)
+            .unwrap()
+            .0 = Vec3(8.5, 1.0, 4.5);
+        CurrentChunkSystem.run_now(&world);
+        assert!(world.write_resource::<WorldEvents>().drain().is_empty());
+
+        world
+            .write_component::<PositionComp>()
+            .get_mut(entity)
+            .unwrap()
+            .0 = Vec3(20.5, 1.0, 4.5);
+        CurrentChunkSystem.run_now(&world);
+        assert_eq!(
+            world.write_resource::<WorldEvents>().drain(),
+            vec![WorldEvent::ChunkChanged {
+                entity,
+                from: Some(Vec2(0, 0)),
+                to: Vec2(1, 0),
+            }]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use voxelize::{
+        Block, Chunk, ChunkOptions, Registry, Resources, VoxelAccess, WorldConfig, WorldGenConfig,
+    };
+
+    #[test]
+    fn flatland_from_json() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Dirt").id(2).build());
+        registry.generate();
+
+        let worldgen = WorldGenConfig::from_json(
+            r#"{
+                "stages": [
+                    { "type": "flatland", "soiling": [
+                        { "block": "Stone", "height": 2 },
+                        { "block": "Dirt", "height": 1 }
+                    ] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let config = WorldConfig::new().build();
+        let stages = worldgen.build_stages(&config, &registry).unwrap();
+        assert_eq!(stages.len(), 1);
+
+        let chunk = Chunk::new(
+            "test",
+            0,
+            0,
+            &ChunkOptions {
+                size: 4,
+                max_height: 8,
+                sub_chunks: 2,
+            },
+        );
+
+        let chunk = stages[0].process(
+            chunk,
+            Resources {
+                registry: &registry,
+                config: &config,
+            },
+            None,
+        );
+
+        assert_eq!(chunk.get_voxel(1, 1, 1), 1);
+        assert_eq!(chunk.get_voxel(1, 2, 1), 2);
+        assert_eq!(chunk.get_voxel(1, 3, 1), 0);
+
+        let unknown = WorldGenConfig::from_json(
+            r#"{ "stages": [{ "type": "baseTerrain", "base": "Lava" }] }"#,
+        )
+        .unwrap();
+        assert!(unknown.build_stages(&config, &registry).is_err());
+    }
+
+    #[test]
+    fn unknown_blocks_are_errors() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.generate();
+
+        let config = WorldConfig::new().build();
+
+        for json in [
+            r#"{ "terrain": {
+                    "layers": [{ "name": "height", "weight": 1.0 }],
+                    "biomes": [{ "name": "Plains", "point": [0.0], "surface": "Grass" }]
+                },
+                "stages": [{ "type": "baseTerrain", "base": "Stone" }] }"#,
+            r#"{ "terrain": {
+                    "layers": [{ "name": "height", "weight": 1.0 }],
+                    "biomes": [{ "name": "Plains", "point": [0.0], "subsurface": "Dirt" }]
+                },
+                "stages": [{ "type": "biomeSurface" }] }"#,
+            r#"{ "stages": [{ "type": "ores", "ores": [
+                    { "block": "Gold", "minHeight": 0, "maxHeight": 8, "size": 4,
+                      "frequency": 1.0, "replaceable": ["Stone"] }
+                ] }] }"#,
+            r#"{ "stages": [{ "type": "ores", "ores": [
+                    { "block": "Stone", "minHeight": 0, "maxHeight": 8, "size": 4,
+                      "frequency": 1.0, "replaceable": ["Granite"] }
+                ] }] }"#,
+        ] {
+            let worldgen = WorldGenConfig::from_json(json).unwrap();
+            assert!(worldgen.build_stages(&config, &registry).is_err());
+        }
+    }
+
+    #[test]
+    fn mismatched_biome_points_are_errors() {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.generate();
+
+        let config = WorldConfig::new().build();
+
+        for json in [
+            r#"{ "terrain": {
+                    "layers": [{ "name": "height", "weight": 1.0 }],
+                    "biomes": [{ "name": "Plains", "point": [0.0, 0.5] }]
+                },
+                "stages": [{ "type": "baseTerrain", "base": "Stone" }] }"#,
+            r#"{ "terrain": {
+                    "layers": [
+                        { "name": "height", "weight": 1.0 },
+                        { "name": "erosion", "weight": 1.0 }
+                    ],
+                    "biomes": [{ "name": "Plains", "point": [0.0] }]
+                },
+                "stages": [{ "type": "baseTerrain", "base": "Stone" }] }"#,
+            r#"{ "terrain": { "biomes": [{ "name": "Plains", "point": [] }] },
+                "stages": [{ "type": "baseTerrain", "base": "Stone" }] }"#,
+        ] {
+            let worldgen = WorldGenConfig::from_json(json).unwrap();
+            let error = worldgen.build_stages(&config, &registry).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
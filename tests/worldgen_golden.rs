@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use voxelize::{
+        Block, Registry, Vec2, WorldConfig, WorldGenConfig, WorldGenHarness, UPDATE_GOLDEN_ENV,
+    };
+
+    const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/worldgen.json");
+
+    fn harness() -> WorldGenHarness {
+        let mut registry = Registry::new();
+        registry.register_block(&Block::new("Stone").id(1).build());
+        registry.register_block(&Block::new("Dirt").id(2).build());
+        registry.register_block(&Block::new("Grass Block").id(3).build());
+        registry.register_block(&Block::new("Sand").id(4).build());
+        registry.register_block(&Block::new("Coal Ore").id(5).build());
+        registry.register_block(&Block::new("Oak Log").id(6).build());
+        registry.register_block(&Block::new("Oak Leaves").id(7).build());
+        registry.generate();
+
+        let worldgen = WorldGenConfig::from_json(
+            r#"{
+                "terrain": {
+                    "layers": [{ "name": "hills", "noise": { "frequency": 0.02 }, "weight": 1.0,
+                                 "bias": [[-1.0, 2.0], [1.0, 2.0]],
+                                 "offset": [[-1.0, 0.3], [1.0, 0.6]] }],
+                    "biomes": [
+                        { "name": "Beach", "point": [-1.0], "surface": "Sand" },
+                        { "name": "Plains", "point": [1.0], "surface": "Grass Block",
+                          "subsurface": "Dirt" }
+                    ],
+                    "biomeBlend": 2
+                },
+                "trees": [{ "name": "Oak", "leaf": "Oak Leaves", "trunk": "Oak Log",
+                            "branchInitialLength": 4, "branchInitialRadius": 1,
+                            "system": { "axiom": "A", "rules": { "A": "F[#F]+%A" },
+                                        "iterations": 2 } }],
+                "stages": [
+                    { "type": "baseTerrain", "base": "Stone" },
+                    { "type": "biomeSurface" },
+                    { "type": "caves", "cheese": { "noise": { "frequency": 0.08 },
+                                                   "threshold": 0.4 },
+                      "maxHeight": 16 },
+                    { "type": "ores", "ores": [{ "block": "Coal Ore", "size": 6,
+                                                 "frequency": 2.0 }] },
+                    { "type": "trees", "placement": { "type": "grid", "spacing": 6 },
+                      "trees": ["Oak"], "ground": ["Grass Block", "Sand"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let config = WorldConfig::new()
+            .seed(1234)
+            .chunk_size(8)
+            .max_height(48)
+            .sub_chunks(2)
+            .build();
+
+        let mut harness = WorldGenHarness::new(&config, &registry);
+        harness.stages = worldgen.build_stages(&config, &registry).unwrap();
+        harness
+    }
+
+    #[test]
+    fn reproducible_across_threads_and_merging() {
+        let (min, max) = (Vec2(-1, -1), Vec2(1, 1));
+
+        let sequential = harness().hash_region(&min, &max);
+        assert_eq!(sequential.len(), 9);
+        assert_eq!(sequential, harness().hash_region(&min, &max));
+
+        let mut threaded = harness();
+        threaded.set_threads(4);
+        assert_eq!(sequential, threaded.hash_region(&min, &max));
+
+        let mut merged = harness();
+        merged.merge_stages();
+        merged.set_threads(4);
+        assert_eq!(sequential, merged.hash_region(&min, &max));
+    }
+
+    #[test]
+    fn matches_golden_hashes() {
+        let mismatches = harness()
+            .check_golden(GOLDEN, &Vec2(-1, -1), &Vec2(1, 1))
+            .unwrap();
+
+        assert!(
+            mismatches.is_empty(),
+            "worldgen changed for chunks {mismatches:?}, rerun with VOXELIZE_UPDATE_GOLDEN=1 if intended"
+        );
+    }
+
+    #[test]
+    fn missing_golden_is_an_error() {
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            return;
+        }
+
+        let path = std::env::temp_dir().join("voxelize-missing-golden.json");
+        let _ = std::fs::remove_file(&path);
+
+        assert!(harness()
+            .check_golden(&path, &Vec2(0, 0), &Vec2(0, 0))
+            .is_err());
+        assert!(!path.exists());
+    }
+}